@@ -1,7 +1,11 @@
+#![cfg(all(feature = "nightly", not(any(feature = "signed-only", feature = "unsigned-only"))))]
 #![feature(test)]
 
+extern crate nt_leb128 as leb128;
 extern crate test;
-extern crate leb128;
+
+use leb128::read::Leb128Read;
+use leb128::write::Leb128Write;
 
 #[bench]
 fn write_signed(b: &mut test::Bencher) {
@@ -10,7 +14,7 @@ fn write_signed(b: &mut test::Bencher) {
     b.iter(|| {
         let mut writable = &mut buf[..];
         for i in -1025..1025 {
-            test::black_box(leb128::write::signed(&mut writable, i).unwrap());
+            test::black_box(writable.write_signed(i).unwrap());
         }
     });
 }
@@ -22,7 +26,7 @@ fn write_unsigned(b: &mut test::Bencher) {
     b.iter(|| {
         let mut writable = &mut buf[..];
         for i in 0..2050 {
-            test::black_box(leb128::write::unsigned(&mut writable, i).unwrap());
+            test::black_box(writable.write_unsigned(i).unwrap());
         }
     });
 }
@@ -34,14 +38,14 @@ fn read_signed(b: &mut test::Bencher) {
     {
         let mut writable = &mut buf[..];
         for i in -1025..1025 {
-            leb128::write::signed(&mut writable, i).unwrap();
+            writable.write_signed(i).unwrap();
         }
     }
 
     b.iter(|| {
         let mut readable = &buf[..];
         for _ in -1025..1025 {
-            test::black_box(leb128::read::signed(&mut readable).unwrap());
+            test::black_box(readable.read_signed().unwrap());
         }
     });
 }
@@ -53,14 +57,14 @@ fn read_unsigned(b: &mut test::Bencher) {
     {
         let mut writable = &mut buf[..];
         for i in 0..2050 {
-            leb128::write::unsigned(&mut writable, i).unwrap();
+            writable.write_unsigned(i).unwrap();
         }
     }
 
     b.iter(|| {
         let mut readable = &buf[..];
         for _ in 0..2050 {
-            test::black_box(leb128::read::unsigned(&mut readable).unwrap());
+            test::black_box(readable.read_unsigned().unwrap());
         }
     });
 }
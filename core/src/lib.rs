@@ -0,0 +1,328 @@
+//! The dependency free core of `nt-leb128`: the bit manipulation LEB128 is
+//! built from, encoded lengths, and encoding and decoding on plain slices and
+//! arrays, all usable in const contexts and without `std`.
+//!
+//! `nt-leb128` re-exports everything here, adding the reading and writing
+//! traits and the integrations with `bytes`, `std::io` and others. Depend on
+//! this crate directly from `no_std` code that only needs the encoding.
+//!
+//! ```
+//! # #[cfg(feature = "unsigned")] {
+//! use nt_leb128_core::{decode_unsigned_const, encode_unsigned_const};
+//!
+//! let (bytes, len) = encode_unsigned_const::<10>(624485);
+//! assert_eq!(&bytes[..len], [0xe5, 0x8e, 0x26]);
+//! assert_eq!(decode_unsigned_const(&bytes, 0), Ok((624485, 3)));
+//! # }
+//! ```
+//!
+//! ## Features
+//!
+//! * `unsigned` (default): unsigned LEB128, the `encoded_len_unsigned`,
+//!   `encode_unsigned_const` and `decode_unsigned_const` functions, and the
+//!   `MAX_LEN_U*` constants.
+//! * `signed` (default): signed LEB128, the `*_signed*` functions, the
+//!   `MAX_LEN_I*` constants, and `raw::sign_extend`.
+//!
+//! Firmware that only ever needs one half can disable the default features
+//! and enable just that one, so the other can't be linked in by accident.
+//! `ConstDecodeError` stays whole, since both halves fail in the same two
+//! ways.
+//!
+//! `nt-leb128` always enables both halves of this crate, which cost nothing
+//! when uncalled; its own `signed-only` and `unsigned-only` features leave a
+//! half out of its traits, functions and types.
+
+#![no_std]
+#![deny(missing_docs)]
+
+/// The bit-level building blocks of LEB128, for implementing custom variants
+/// of the encoding.
+pub mod raw;
+
+#[cfg(any(feature = "signed", feature = "unsigned"))]
+use crate::raw::{CONTINUATION_BIT, low_bits_of_byte};
+#[cfg(feature = "unsigned")]
+use crate::raw::low_bits_of_u64;
+
+#[cfg(feature = "unsigned")]
+/// The number of bytes needed to encode `val` as unsigned LEB128.
+///
+/// ```
+/// assert_eq!(nt_leb128_core::encoded_len_unsigned(127), 1);
+/// assert_eq!(nt_leb128_core::encoded_len_unsigned(128), 2);
+/// assert_eq!(nt_leb128_core::encoded_len_unsigned(u64::MAX), 10);
+/// ```
+#[inline]
+pub const fn encoded_len_unsigned(val: u64) -> usize {
+    let bits = 64 - val.leading_zeros() as usize;
+    if bits == 0 { 1 } else { bits.div_ceil(7) }
+}
+
+#[cfg(feature = "signed")]
+/// The number of bytes needed to encode `val` as signed LEB128.
+///
+/// ```
+/// assert_eq!(nt_leb128_core::encoded_len_signed(63), 1);
+/// assert_eq!(nt_leb128_core::encoded_len_signed(64), 2);
+/// assert_eq!(nt_leb128_core::encoded_len_signed(-64), 1);
+/// assert_eq!(nt_leb128_core::encoded_len_signed(i64::MIN), 10);
+/// ```
+#[inline]
+pub const fn encoded_len_signed(val: i64) -> usize {
+    // Every byte carries seven bits, and the last one must also hold the sign.
+    let bits = if val < 0 {
+        64 - val.leading_ones() as usize + 1
+    } else {
+        64 - val.leading_zeros() as usize + 1
+    };
+    bits.div_ceil(7)
+}
+
+/// The number of bytes the LEB128 encoding of a `bits` wide integer can
+/// occupy.
+#[cfg(any(feature = "signed", feature = "unsigned"))]
+const fn max_len(bits: u32) -> usize {
+    (bits as usize).div_ceil(7)
+}
+
+#[cfg(feature = "unsigned")]
+/// The longest unsigned LEB128 encoding of a `u8`, in bytes.
+pub const MAX_LEN_U8: usize = max_len(u8::BITS);
+#[cfg(feature = "unsigned")]
+/// The longest unsigned LEB128 encoding of a `u16`, in bytes.
+pub const MAX_LEN_U16: usize = max_len(u16::BITS);
+#[cfg(feature = "unsigned")]
+/// The longest unsigned LEB128 encoding of a `u32`, in bytes.
+pub const MAX_LEN_U32: usize = max_len(u32::BITS);
+#[cfg(feature = "unsigned")]
+/// The longest unsigned LEB128 encoding of a `u64`, in bytes.
+pub const MAX_LEN_U64: usize = max_len(u64::BITS);
+#[cfg(feature = "unsigned")]
+/// The longest unsigned LEB128 encoding of a `usize`, in bytes.
+pub const MAX_LEN_USIZE: usize = max_len(usize::BITS);
+#[cfg(feature = "signed")]
+/// The longest signed LEB128 encoding of an `i8`, in bytes.
+pub const MAX_LEN_I8: usize = max_len(i8::BITS);
+#[cfg(feature = "signed")]
+/// The longest signed LEB128 encoding of an `i16`, in bytes.
+pub const MAX_LEN_I16: usize = max_len(i16::BITS);
+#[cfg(feature = "signed")]
+/// The longest signed LEB128 encoding of an `i32`, in bytes.
+pub const MAX_LEN_I32: usize = max_len(i32::BITS);
+#[cfg(feature = "signed")]
+/// The longest signed LEB128 encoding of an `i64`, in bytes.
+pub const MAX_LEN_I64: usize = max_len(i64::BITS);
+#[cfg(feature = "signed")]
+/// The longest signed LEB128 encoding of an `isize`, in bytes.
+pub const MAX_LEN_ISIZE: usize = max_len(isize::BITS);
+
+/// Integer types with a known longest LEB128 encoding: unsigned LEB128 for
+/// unsigned types and signed LEB128 for signed ones.
+pub trait MaxEncodedLen {
+    /// The longest encoding of a value of this type, in bytes.
+    const MAX_ENCODED_LEN: usize;
+}
+
+#[cfg(any(feature = "signed", feature = "unsigned"))]
+macro_rules! impl_max_encoded_len {
+    ($($ty:ty => $len:ident),*) => {
+        $(
+            impl MaxEncodedLen for $ty {
+                const MAX_ENCODED_LEN: usize = $len;
+            }
+        )*
+    }
+}
+
+#[cfg(feature = "unsigned")]
+impl_max_encoded_len!(u8 => MAX_LEN_U8, u16 => MAX_LEN_U16, u32 => MAX_LEN_U32,
+                      u64 => MAX_LEN_U64, usize => MAX_LEN_USIZE);
+#[cfg(feature = "signed")]
+impl_max_encoded_len!(i8 => MAX_LEN_I8, i16 => MAX_LEN_I16, i32 => MAX_LEN_I32,
+                      i64 => MAX_LEN_I64, isize => MAX_LEN_ISIZE);
+
+/// The longest LEB128 encoding of a `T`, in bytes, for sizing fixed buffers.
+///
+/// ```
+/// # #[cfg(all(feature = "signed", feature = "unsigned"))] {
+/// use nt_leb128_core::{max_encoded_len, MAX_LEN_U32};
+///
+/// let buf = [0u8; max_encoded_len::<u32>()];
+/// assert_eq!(buf.len(), MAX_LEN_U32);
+/// assert_eq!(max_encoded_len::<i64>(), 10);
+/// # }
+/// ```
+pub const fn max_encoded_len<T: MaxEncodedLen>() -> usize {
+    T::MAX_ENCODED_LEN
+}
+
+#[cfg(feature = "unsigned")]
+/// Encode `val` as unsigned LEB128 into an `N` byte array, in a const
+/// context. Returns the array, zero padded after the encoding, along with the
+/// encoded length.
+///
+/// Fails to compile (or panics, outside of const contexts) if the encoding
+/// doesn't fit in `N` bytes.
+///
+/// ```
+/// const ENCODED: ([u8; 10], usize) = nt_leb128_core::encode_unsigned_const::<10>(624485);
+/// assert_eq!(&ENCODED.0[..ENCODED.1], &[0xe5, 0x8e, 0x26]);
+/// ```
+pub const fn encode_unsigned_const<const N: usize>(mut val: u64) -> ([u8; N], usize) {
+    let mut out = [0; N];
+    let mut i = 0;
+    loop {
+        let mut byte = low_bits_of_u64(val);
+        val >>= 7;
+        if val != 0 {
+            // More bytes to come, so set the continuation bit.
+            byte |= CONTINUATION_BIT;
+        }
+
+        assert!(i < N, "the encoding doesn't fit in the output array");
+        out[i] = byte;
+        i += 1;
+
+        if val == 0 {
+            return (out, i);
+        }
+    }
+}
+
+#[cfg(feature = "signed")]
+/// Encode `val` as signed LEB128 into an `N` byte array, in a const context.
+/// Returns the array, zero padded after the encoding, along with the encoded
+/// length.
+///
+/// Fails to compile (or panics, outside of const contexts) if the encoding
+/// doesn't fit in `N` bytes.
+///
+/// ```
+/// const ENCODED: ([u8; 10], usize) = nt_leb128_core::encode_signed_const::<10>(-129);
+/// assert_eq!(&ENCODED.0[..ENCODED.1], &[0xff, 0x7e]);
+/// ```
+pub const fn encode_signed_const<const N: usize>(mut val: i64) -> ([u8; N], usize) {
+    let mut out = [0; N];
+    let mut i = 0;
+    loop {
+        let mut byte = val as u8;
+        // Keep the sign bit for testing
+        val >>= 6;
+        let done = val == 0 || val == -1;
+        if done {
+            byte &= !CONTINUATION_BIT;
+        } else {
+            // Remove the sign bit
+            val >>= 1;
+            // More bytes to come, so set the continuation bit.
+            byte |= CONTINUATION_BIT;
+        }
+
+        assert!(i < N, "the encoding doesn't fit in the output array");
+        out[i] = byte;
+        i += 1;
+
+        if done {
+            return (out, i);
+        }
+    }
+}
+
+/// The ways decoding in a const context can fail.
+///
+/// `nt_leb128::read::Error` can't be used in const contexts because it may
+/// hold an `io::Error`, so the const decoders report this plain, `Copy` error
+/// instead. `nt_leb128` converts it into the equivalent `read::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstDecodeError {
+    /// The input ended before the encoding did.
+    UnexpectedEof,
+    /// The number being read is larger than can be represented.
+    Overflow,
+}
+
+#[cfg(feature = "unsigned")]
+/// Decode the unsigned LEB128 number starting at `offset` in `bytes`, in a
+/// const context. Returns the number along with the number of bytes it
+/// occupies.
+///
+/// ```
+/// static TABLE: &[u8] = &[0x02, 0xe5, 0x8e, 0x26];
+/// const SECOND: u64 = match nt_leb128_core::decode_unsigned_const(TABLE, 1) {
+///     Ok((val, _len)) => val,
+///     Err(_) => panic!("malformed table"),
+/// };
+/// assert_eq!(SECOND, 624485);
+/// ```
+pub const fn decode_unsigned_const(bytes: &[u8],
+                                   offset: usize)
+                                   -> Result<(u64, usize), ConstDecodeError> {
+    let mut result = 0;
+    let mut shift = 0;
+    let mut i = offset;
+
+    loop {
+        if i >= bytes.len() {
+            return Err(ConstDecodeError::UnexpectedEof);
+        }
+        let byte = bytes[i];
+        i += 1;
+
+        if shift == 63 && byte != 0x00 && byte != 0x01 {
+            return Err(ConstDecodeError::Overflow);
+        }
+
+        let low_bits = low_bits_of_byte(byte) as u64;
+        result |= low_bits << shift;
+
+        if byte & CONTINUATION_BIT == 0 {
+            return Ok((result, i - offset));
+        }
+
+        shift += 7;
+    }
+}
+
+#[cfg(feature = "signed")]
+/// Decode the signed LEB128 number starting at `offset` in `bytes`, in a
+/// const context. Returns the number along with the number of bytes it
+/// occupies.
+///
+/// ```
+/// const VALUE: i64 = match nt_leb128_core::decode_signed_const(&[0xff, 0x7e], 0) {
+///     Ok((val, _len)) => val,
+///     Err(_) => panic!("malformed value"),
+/// };
+/// assert_eq!(VALUE, -129);
+/// ```
+pub const fn decode_signed_const(bytes: &[u8],
+                                 offset: usize)
+                                 -> Result<(i64, usize), ConstDecodeError> {
+    let mut result = 0;
+    let mut shift = 0;
+    let mut i = offset;
+    let mut byte;
+
+    loop {
+        if i >= bytes.len() {
+            return Err(ConstDecodeError::UnexpectedEof);
+        }
+        byte = bytes[i];
+        i += 1;
+
+        if shift == 63 && byte != 0x00 && byte != 0x7f {
+            return Err(ConstDecodeError::Overflow);
+        }
+
+        let low_bits = low_bits_of_byte(byte) as i64;
+        result |= low_bits << shift;
+        shift += 7;
+
+        if byte & CONTINUATION_BIT == 0 {
+            break;
+        }
+    }
+
+    Ok((raw::sign_extend(result, shift, byte), i - offset))
+}
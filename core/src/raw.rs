@@ -0,0 +1,116 @@
+//! Everything in this module is part of the crate's stable API: the values of
+//! the constants and the behaviour of the functions will not change outside a
+//! major release.
+//!
+//! ```
+//! use nt_leb128_core::raw;
+//!
+//! // 624485 as unsigned LEB128, decoded by hand.
+//! let mut val = 0u64;
+//! for (i, &byte) in [0xe5, 0x8e, 0x26].iter().enumerate() {
+//!     let (payload, more) = raw::split_payload(byte);
+//!     val |= (payload as u64) << raw::shift_of(i);
+//!     assert_eq!(more, !raw::is_terminal(byte));
+//! }
+//! assert_eq!(val, 624485);
+//! ```
+
+/// The high bit of every byte, set on all but the last byte of an encoding.
+pub const CONTINUATION_BIT: u8 = 1 << 7;
+
+/// The highest payload bit, which holds the sign in the last byte of a signed
+/// encoding.
+pub const SIGN_BIT: u8 = 1 << 6;
+
+/// The number of payload bits each byte carries.
+pub const PAYLOAD_BITS: u32 = 7;
+
+/// The payload of `byte`, without its continuation bit.
+#[inline]
+pub const fn low_bits_of_byte(byte: u8) -> u8 {
+    byte & !CONTINUATION_BIT
+}
+
+/// The lowest seven bits of `val`, as the payload of a byte.
+#[inline]
+pub const fn low_bits_of_u64(val: u64) -> u8 {
+    let byte = val & (u8::MAX as u64);
+    low_bits_of_byte(byte as u8)
+}
+
+/// Split `byte` into its payload and whether another byte follows it.
+///
+/// ```
+/// assert_eq!(nt_leb128_core::raw::split_payload(0xe5), (0x65, true));
+/// assert_eq!(nt_leb128_core::raw::split_payload(0x26), (0x26, false));
+/// ```
+#[inline]
+pub const fn split_payload(byte: u8) -> (u8, bool) {
+    (low_bits_of_byte(byte), !is_terminal(byte))
+}
+
+/// Whether `byte` is the last of an encoding, having no continuation bit.
+#[inline]
+pub const fn is_terminal(byte: u8) -> bool {
+    byte & CONTINUATION_BIT == 0
+}
+
+/// The position, in the decoded value, of the lowest payload bit of the byte
+/// at `index` in an encoding.
+///
+/// ```
+/// assert_eq!(nt_leb128_core::raw::shift_of(0), 0);
+/// assert_eq!(nt_leb128_core::raw::shift_of(9), 63);
+/// ```
+#[inline]
+pub const fn shift_of(index: usize) -> u32 {
+    index as u32 * PAYLOAD_BITS
+}
+
+/// Extend the sign of a signed value whose lowest `shift` bits have been
+/// decoded, given the last byte of its encoding. Values that already fill 64
+/// bits are returned unchanged.
+///
+/// ```
+/// use nt_leb128_core::raw::sign_extend;
+///
+/// // -2 is the single byte 0x7e.
+/// assert_eq!(sign_extend(0x7e, 7, 0x7e), -2);
+/// assert_eq!(sign_extend(0x3e, 7, 0x3e), 62);
+/// ```
+#[cfg(feature = "signed")]
+#[inline]
+pub const fn sign_extend(val: i64, shift: u32, last_byte: u8) -> i64 {
+    if shift < 64 && last_byte & SIGN_BIT == SIGN_BIT {
+        val | (!0 << shift)
+    } else {
+        val
+    }
+}
+
+/// Map a signed value to an unsigned one by zigzag encoding, as protobuf's
+/// `sint64` does, so that values near zero of either sign encode in few
+/// bytes when written as unsigned LEB128.
+///
+/// ```
+/// use nt_leb128_core::raw::zigzag_encode;
+///
+/// assert_eq!(zigzag_encode(0), 0);
+/// assert_eq!(zigzag_encode(-1), 1);
+/// assert_eq!(zigzag_encode(1), 2);
+/// assert_eq!(zigzag_encode(i64::MIN), u64::MAX);
+/// ```
+#[inline]
+pub const fn zigzag_encode(val: i64) -> u64 {
+    ((val << 1) ^ (val >> 63)) as u64
+}
+
+/// Undo `zigzag_encode`.
+///
+/// ```
+/// assert_eq!(nt_leb128_core::raw::zigzag_decode(3), -2);
+/// ```
+#[inline]
+pub const fn zigzag_decode(val: u64) -> i64 {
+    (val >> 1) as i64 ^ -((val & 1) as i64)
+}
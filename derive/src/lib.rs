@@ -0,0 +1,143 @@
+//! Derive macros for `nt-leb128`'s `Leb128Encode` and `Leb128Decode` traits.
+//!
+//! Use them through `nt-leb128` with its `derive` feature enabled, rather than
+//! depending on this crate directly:
+//!
+//! ```ignore
+//! use nt_leb128::{Leb128Decode, Leb128Encode};
+//!
+//! #[derive(Leb128Encode, Leb128Decode)]
+//! struct Header {
+//!     version: u8,
+//!     offset: i64,
+//!     names: Vec<String>,
+//! }
+//! ```
+//!
+//! Fields are encoded in declaration order: integers as LEB128, nested types
+//! through their own implementations, and `Vec`s and `String`s with a ULEB128
+//! length prefix.
+
+#![deny(missing_docs)]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, Index};
+
+/// Derive `nt_leb128::Leb128Encode`, encoding each field in declaration order.
+#[proc_macro_derive(Leb128Encode)]
+pub fn derive_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let fields = match struct_fields(&input) {
+        Ok(fields) => fields,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let name = &input.ident;
+    let generics = add_bound(input.generics.clone(), quote!(::nt_leb128::Leb128Encode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let access: Vec<_> = fields.iter()
+        .map(|field| match field {
+            FieldName::Named(ident) => quote!(self.#ident),
+            FieldName::Unnamed(index) => quote!(self.#index),
+        })
+        .collect();
+
+    let expanded = quote! {
+        impl #impl_generics ::nt_leb128::Leb128Encode for #name #ty_generics #where_clause {
+            fn encode<W: ::nt_leb128::Leb128Write>(&self, buf: &mut W)
+                -> ::std::result::Result<usize, ::nt_leb128::write::Error>
+            {
+                let mut written = 0;
+                #(written += ::nt_leb128::Leb128Encode::encode(&#access, buf)?;)*
+                ::std::result::Result::Ok(written)
+            }
+
+            fn encoded_len(&self) -> usize {
+                0 #(+ ::nt_leb128::Leb128Encode::encoded_len(&#access))*
+            }
+        }
+    };
+    expanded.into()
+}
+
+/// Derive `nt_leb128::Leb128Decode`, decoding each field in declaration order.
+#[proc_macro_derive(Leb128Decode)]
+pub fn derive_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let fields = match struct_fields(&input) {
+        Ok(fields) => fields,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let name = &input.ident;
+    let generics = add_bound(input.generics.clone(), quote!(::nt_leb128::Leb128Decode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let decode = quote!(::nt_leb128::Leb128Decode::decode(buf)?);
+    let construct = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(_) => {
+                let fields = fields.iter().map(|field| match field {
+                    FieldName::Named(ident) => quote!(#ident: #decode),
+                    FieldName::Unnamed(_) => unreachable!(),
+                });
+                quote!(#name { #(#fields),* })
+            }
+            Fields::Unnamed(_) => {
+                let fields = fields.iter().map(|_| &decode);
+                quote!(#name(#(#fields),*))
+            }
+            Fields::Unit => quote!(#name),
+        },
+        _ => unreachable!(),
+    };
+
+    let expanded = quote! {
+        impl #impl_generics ::nt_leb128::Leb128Decode for #name #ty_generics #where_clause {
+            fn decode<R: ::nt_leb128::Leb128Read>(buf: &mut R)
+                -> ::std::result::Result<Self, ::nt_leb128::read::Error>
+            {
+                // Struct expressions evaluate their fields in source order.
+                ::std::result::Result::Ok(#construct)
+            }
+        }
+    };
+    expanded.into()
+}
+
+enum FieldName {
+    Named(syn::Ident),
+    Unnamed(Index),
+}
+
+fn struct_fields(input: &DeriveInput) -> Result<Vec<FieldName>, syn::Error> {
+    let data = match input.data {
+        Data::Struct(ref data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(&input.ident,
+                                               "Leb128Encode and Leb128Decode can only be \
+                                                derived for structs"))
+        }
+    };
+
+    Ok(data.fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match field.ident {
+            Some(ref ident) => FieldName::Named(format_ident!("{}", ident)),
+            None => FieldName::Unnamed(Index::from(i)),
+        })
+        .collect())
+}
+
+fn add_bound(mut generics: Generics, bound: TokenStream2) -> Generics {
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
@@ -0,0 +1,70 @@
+//! Cross-checks decoding and encoding against gimli's `leb128` crate.
+//!
+//! Run with `cargo fuzz run differential` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nt_leb128::read::{self, Leb128Read};
+use nt_leb128::write::Leb128Write;
+use std::convert::TryInto;
+use std::io;
+
+#[derive(Debug, PartialEq)]
+enum Outcome<T> {
+    Value(T, usize),
+    Eof,
+    Overflow,
+}
+
+fn ours<T>(result: Result<(T, usize), read::Error>) -> Outcome<T> {
+    match result {
+        Ok((val, len)) => Outcome::Value(val, len),
+        Err(read::Error::Overflow) => Outcome::Overflow,
+        Err(read::Error::Incomplete) => Outcome::Eof,
+        Err(e) => panic!("unexpected error from nt-leb128: {}", e),
+    }
+}
+
+fn reference<T, F>(data: &[u8], decode: F) -> Outcome<T>
+    where F: FnOnce(&mut &[u8]) -> Result<T, leb128::read::Error>
+{
+    let mut readable = data;
+    match decode(&mut readable) {
+        Ok(val) => Outcome::Value(val, data.len() - readable.len()),
+        Err(leb128::read::Error::Overflow) => Outcome::Overflow,
+        Err(leb128::read::Error::IoError(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            Outcome::Eof
+        }
+        Err(e) => panic!("unexpected error from leb128: {}", e),
+    }
+}
+
+/// Compare decoding outcomes. On overflow, gimli keeps reading to the end of
+/// the encoding before reporting it, so it sees an EOF where this crate,
+/// which stops at the tenth byte, already reports the overflow.
+fn agree<T: PartialEq + std::fmt::Debug>(data: &[u8], ours: Outcome<T>, reference: Outcome<T>) {
+    if ours == Outcome::Overflow && reference == Outcome::Eof && data.len() >= 10 {
+        return;
+    }
+    assert_eq!(ours, reference, "decoding {:02x?}", data);
+}
+
+fuzz_target!(|data: &[u8]| {
+    agree(data, ours((&data[..]).read_unsigned()), reference(data, |r| leb128::read::unsigned(r)));
+    agree(data, ours((&data[..]).read_signed()), reference(data, |r| leb128::read::signed(r)));
+
+    if let Some(prefix) = data.get(..8) {
+        let bits = u64::from_le_bytes(prefix.try_into().unwrap());
+
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        a.write_unsigned(bits).unwrap();
+        leb128::write::unsigned(&mut b, bits).unwrap();
+        assert_eq!(a, b, "encoding {} as unsigned", bits);
+
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        a.write_signed(bits as i64).unwrap();
+        leb128::write::signed(&mut b, bits as i64).unwrap();
+        assert_eq!(a, b, "encoding {} as signed", bits as i64);
+    }
+});
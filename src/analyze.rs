@@ -0,0 +1,80 @@
+use nt_leb128_core::{encoded_len_signed, encoded_len_unsigned, MAX_LEN_U64};
+use std::fmt;
+
+/// How a set of values would fare encoded as plain LEB128, as LEB128 deltas
+/// and bit packed, for choosing between them before settling on a storage
+/// layout.
+///
+/// Produced by [`analyze`](fn.analyze.html). The `Display` implementation
+/// prints a summary followed by the histogram.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeReport {
+    /// The number of values analyzed.
+    pub count: usize,
+    /// `histogram[i]` is the number of values whose unsigned LEB128 encoding
+    /// occupies `i + 1` bytes.
+    pub histogram: [usize; MAX_LEN_U64],
+    /// The total bytes of the values as unsigned LEB128.
+    pub total_bytes: usize,
+    /// The total bytes of the difference between each value and the one
+    /// before it (the first from zero), as signed LEB128. Small when the
+    /// values are sorted or clustered, however large they are.
+    pub delta_bytes: usize,
+    /// The total bytes of the values bit packed, each in as many bits as the
+    /// largest needs, rounded up to a whole byte overall.
+    pub bitpacked_bytes: usize,
+}
+
+impl SizeReport {
+    /// The average bytes per value as unsigned LEB128, or zero if there were
+    /// no values.
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.total_bytes as f64 / self.count as f64
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "values:     {}", self.count)?;
+        writeln!(f, "leb128:     {} bytes ({:.2} per value)", self.total_bytes, self.average())?;
+        writeln!(f, "delta:      {} bytes", self.delta_bytes)?;
+        writeln!(f, "bitpacked:  {} bytes", self.bitpacked_bytes)?;
+        for (i, &n) in self.histogram.iter().enumerate().filter(|&(_, &n)| n > 0) {
+            writeln!(f, "{:>2} byte{}: {}", i + 1, if i == 0 { " " } else { "s" }, n)?;
+        }
+        Ok(())
+    }
+}
+
+/// Measure how `vals` would encode, without encoding them.
+///
+/// ```
+/// let report = nt_leb128::analyze(&[1_000_000, 1_000_001, 1_000_003]);
+/// assert_eq!(report.histogram[2], 3);
+/// assert_eq!(report.total_bytes, 9);
+/// assert_eq!(report.delta_bytes, 3 + 1 + 1);
+/// assert_eq!(report.bitpacked_bytes, 8);
+/// assert_eq!(report.average(), 3.0);
+/// ```
+pub fn analyze(vals: &[u64]) -> SizeReport {
+    let mut report = SizeReport {
+        count: vals.len(),
+        ..SizeReport::default()
+    };
+    let mut prev = 0u64;
+    let mut max = 0u64;
+    for &val in vals {
+        let len = encoded_len_unsigned(val);
+        report.histogram[len - 1] += 1;
+        report.total_bytes += len;
+        report.delta_bytes += encoded_len_signed(val.wrapping_sub(prev) as i64);
+        prev = val;
+        max = max.max(val);
+    }
+    let width = (64 - max.leading_zeros()) as usize;
+    report.bitpacked_bytes = (width * vals.len()).div_ceil(8);
+    report
+}
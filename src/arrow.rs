@@ -0,0 +1,89 @@
+//! An encoded array is its length and its null count, each as unsigned
+//! LEB128, then its validity bitmap if the null count isn't zero (in Arrow's
+//! layout: one bit per element, least significant first, set if the element
+//! is valid), then each valid element as unsigned LEB128. Null elements take
+//! no space beyond their bit.
+//!
+//! ```
+//! use arrow_array::UInt64Array;
+//! use nt_leb128::arrow::{decode_to_array, encode_array};
+//!
+//! let array = UInt64Array::from(vec![Some(1), None, Some(300)]);
+//! let buffer = encode_array(&array);
+//! assert_eq!(buffer.as_slice(), [0x03, 0x01, 0b101, 0x01, 0xac, 0x02]);
+//! assert_eq!(decode_to_array(&buffer).unwrap(), array);
+//! ```
+
+use super::codec::MAX_PREALLOC;
+use super::io_error;
+use super::read::Error;
+use super::slice::decode_unsigned;
+use super::write::put_unsigned;
+use arrow_array::{Array, UInt64Array};
+use arrow_buffer::{BooleanBuffer, Buffer, NullBuffer, ScalarBuffer};
+use std::io;
+
+/// Encode `array`, nulls included, into a new buffer.
+pub fn encode_array(array: &UInt64Array) -> Buffer {
+    let mut buf = Vec::new();
+    put_unsigned(&mut buf, array.len() as u64);
+    put_unsigned(&mut buf, array.null_count() as u64);
+    if array.null_count() != 0 {
+        let mut bitmap = vec![0u8; array.len().div_ceil(8)];
+        for i in (0..array.len()).filter(|&i| array.is_valid(i)) {
+            bitmap[i / 8] |= 1 << (i % 8);
+        }
+        buf.extend_from_slice(&bitmap);
+    }
+    for i in (0..array.len()).filter(|&i| array.is_valid(i)) {
+        put_unsigned(&mut buf, array.value(i));
+    }
+    Buffer::from_vec(buf)
+}
+
+/// Decode an array encoded by `encode_array`, which must fill `bytes`.
+///
+/// Fails with `Error::Incomplete` if `bytes` ends early, and with an
+/// `InvalidData` `Error::IoError` if the null count disagrees with the
+/// bitmap or bytes are left over.
+pub fn decode_to_array(bytes: &[u8]) -> Result<UInt64Array, Error> {
+    let (len, bytes) = decode_unsigned(bytes)?;
+    let (null_count, mut bytes) = decode_unsigned(bytes)?;
+    if len > usize::MAX as u64 || null_count > len {
+        return Err(Error::Overflow);
+    }
+    let len = len as usize;
+    let nulls = if null_count == 0 {
+        None
+    } else {
+        let bitmap_len = len.div_ceil(8);
+        if bytes.len() < bitmap_len {
+            return Err(Error::Incomplete);
+        }
+        let (bitmap, rest) = bytes.split_at(bitmap_len);
+        bytes = rest;
+        let nulls = NullBuffer::new(BooleanBuffer::new(Buffer::from(bitmap), 0, len));
+        if nulls.null_count() as u64 != null_count {
+            return Err(invalid_data("Null count disagrees with validity bitmap"));
+        }
+        Some(nulls)
+    };
+    let mut values = Vec::with_capacity(len.min(MAX_PREALLOC));
+    for i in 0..len {
+        if nulls.as_ref().is_none_or(|nulls| nulls.is_valid(i)) {
+            let (val, rest) = decode_unsigned(bytes)?;
+            values.push(val);
+            bytes = rest;
+        } else {
+            values.push(0);
+        }
+    }
+    if !bytes.is_empty() {
+        return Err(invalid_data("Trailing bytes after array"));
+    }
+    Ok(UInt64Array::new(ScalarBuffer::from(values), nulls))
+}
+
+fn invalid_data(msg: &'static str) -> Error {
+    Error::IoError(io_error(io::ErrorKind::InvalidData, msg))
+}
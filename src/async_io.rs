@@ -0,0 +1,148 @@
+//! # Cancellation
+//!
+//! The read methods of [`AsyncReader`](struct.AsyncReader.html) are cancel
+//! safe: if a read future is dropped, for example because another branch of a
+//! `select!` finished first, no input is lost. Every byte taken from the
+//! underlying reader is kept in the `AsyncReader` until the value it belongs
+//! to is complete, and the next read, of either signedness, carries on from
+//! there.
+
+use super::CONTINUATION_BIT;
+use super::read::{not_enough_data, Error, MAX_LEN};
+#[cfg(not(feature = "signed-only"))]
+use super::read::decode_unsigned;
+#[cfg(not(feature = "unsigned-only"))]
+use super::read::decode_signed;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::AsyncBufRead;
+
+/// Reads LEB128 numbers from a tokio `AsyncBufRead`.
+///
+/// Wrap unbuffered readers in a `tokio::io::BufReader` first. A value that is
+/// wholly in the reader's buffer is decoded where it is and consumed at once;
+/// only one split across refills is copied aside.
+///
+/// ```
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// use nt_leb128::async_io::AsyncReader;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut reader = AsyncReader::new(&[0xe5, 0x8e, 0x26, 0x7e][..]);
+/// assert_eq!(reader.read_unsigned().await.unwrap(), (624485, 3));
+/// assert_eq!(reader.read_signed().await.unwrap(), (-2, 1));
+/// # });
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncReader<R> {
+    inner: R,
+    pending: [u8; MAX_LEN],
+    len: usize,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncReader<R> {
+    /// Wrap `inner`.
+    pub fn new(inner: R) -> Self {
+        AsyncReader {
+            inner,
+            pending: [0; MAX_LEN],
+            len: 0,
+        }
+    }
+
+    /// Read a signed LEB128 number, returning it along with the number of
+    /// bytes it occupied.
+    ///
+    /// Cancel safe; see the [module documentation](index.html#cancellation).
+    #[cfg(not(feature = "unsigned-only"))]
+    pub async fn read_signed(&mut self) -> Result<(i64, usize), Error> {
+        poll_fn(|cx| self.poll_read_signed(cx)).await
+    }
+
+    /// Read an unsigned LEB128 number, returning it along with the number of
+    /// bytes it occupied.
+    ///
+    /// Cancel safe; see the [module documentation](index.html#cancellation).
+    #[cfg(not(feature = "signed-only"))]
+    pub async fn read_unsigned(&mut self) -> Result<(u64, usize), Error> {
+        poll_fn(|cx| self.poll_read_unsigned(cx)).await
+    }
+
+    /// Like `read_signed`, for hand-written futures and codecs.
+    #[cfg(not(feature = "unsigned-only"))]
+    pub fn poll_read_signed(&mut self, cx: &mut Context<'_>)
+                            -> Poll<Result<(i64, usize), Error>> {
+        self.poll_read(cx, |bytes| decode_signed(bytes.iter().copied()))
+    }
+
+    /// Like `read_unsigned`, for hand-written futures and codecs.
+    #[cfg(not(feature = "signed-only"))]
+    pub fn poll_read_unsigned(&mut self, cx: &mut Context<'_>)
+                              -> Poll<Result<(u64, usize), Error>> {
+        self.poll_read(cx, |bytes| decode_unsigned(bytes.iter().copied()))
+    }
+
+    /// The number of bytes of a partially read value held from an earlier,
+    /// cancelled or failed, read.
+    pub fn pending(&self) -> usize {
+        self.len
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader. Reading from it directly
+    /// while a value is pending corrupts that value.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner reader, discarding any pending bytes.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Decode the next value straight from the inner reader's buffer when
+    /// all of it is there, consuming it at once. Otherwise, move bytes into
+    /// `pending` until it holds a whole encoding, or ten bytes of one. Bytes
+    /// are consumed only once they are decoded or stored.
+    fn poll_read<T, F>(&mut self, cx: &mut Context<'_>, decode: F)
+                       -> Poll<Result<(T, usize), Error>>
+        where F: Fn(&[u8]) -> Result<(T, usize), Error>
+    {
+        loop {
+            let chunk = ready!(Pin::new(&mut self.inner).poll_fill_buf(cx))?;
+            if chunk.is_empty() {
+                return Poll::Ready(Err(not_enough_data()));
+            }
+
+            let want = &chunk[..chunk.len().min(MAX_LEN - self.len)];
+            let end = want.iter().position(|b| b & CONTINUATION_BIT == 0).map(|pos| pos + 1);
+            if self.len == 0 && (end.is_some() || want.len() == MAX_LEN) {
+                // Fast path: the whole encoding is in the buffer.
+                let take = end.unwrap_or(MAX_LEN);
+                let result = decode(&want[..take]);
+                Pin::new(&mut self.inner).consume(take);
+                return Poll::Ready(result);
+            }
+
+            let take = end.unwrap_or(want.len());
+            self.pending[self.len..self.len + take].copy_from_slice(&want[..take]);
+            self.len += take;
+            Pin::new(&mut self.inner).consume(take);
+            if self.has_value() {
+                let result = decode(&self.pending[..self.len]);
+                self.len = 0;
+                return Poll::Ready(result);
+            }
+        }
+    }
+
+    fn has_value(&self) -> bool {
+        self.len == MAX_LEN || (self.len > 0 && self.pending[self.len - 1] & CONTINUATION_BIT == 0)
+    }
+}
@@ -2,14 +2,14 @@ extern crate nt_leb128 as leb128;
 
 use std::io::{self, BufRead, Write};
 use std::str;
-use leb128::read::LEB128Read;
-use leb128::write::LEB128Write;
+use leb128::write::Leb128Write;
+use leb128::Config;
 
 fn display(bytes: &[u8]) -> String {
     let mut s = vec![];
 
     // Base 10.
-    write!(&mut s, "{:?}\n", bytes).unwrap();
+    writeln!(&mut s, "{:?}", bytes).unwrap();
 
     // Hex.
     write!(&mut s, "[").unwrap();
@@ -60,35 +60,41 @@ base-10, hex, and binary.
             break;
         }
 
-        let uleb = str::from_utf8(&buf)
-            .ok()
-            .and_then(|s| s.trim().parse().ok())
-            .and_then(|n: u64| {
-                let mut s = vec![];
-                s.write_unsigned(n).ok()?;
-                Some(display(&s))
-            })
-            .unwrap_or_else(|| "error\n".into());
-        stdout
-            .write_all(b"# unsigned LEB128\n")
-            .and_then(|_| stdout.write_all(uleb.as_bytes()))
-            .and_then(|_| stdout.write_all(b"\n"))
-            .expect("failed to write to stdout");
+        #[cfg(not(feature = "signed-only"))]
+        {
+            let uleb = str::from_utf8(&buf)
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+                .and_then(|n: u64| {
+                    let mut s = vec![];
+                    s.write_unsigned_with(n, &Config::new()).ok()?;
+                    Some(display(&s))
+                })
+                .unwrap_or_else(|| "error\n".into());
+            stdout
+                .write_all(b"# unsigned LEB128\n")
+                .and_then(|_| stdout.write_all(uleb.as_bytes()))
+                .and_then(|_| stdout.write_all(b"\n"))
+                .expect("failed to write to stdout");
+        }
 
-        let leb = str::from_utf8(&buf)
-            .ok()
-            .and_then(|s| s.trim().parse().ok())
-            .and_then(|n: i64| {
-                let mut s = vec![];
-                s.write_signed(n).ok()?;
-                Some(display(&s))
-            })
-            .unwrap_or_else(|| "error\n".into());
-        stdout
-            .write_all(b"# signed LEB128\n")
-            .and_then(|_| stdout.write_all(leb.as_bytes()))
-            .and_then(|_| stdout.write_all(b"\n"))
-            .expect("failed to write to stdout");
+        #[cfg(not(feature = "unsigned-only"))]
+        {
+            let leb = str::from_utf8(&buf)
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+                .and_then(|n: i64| {
+                    let mut s = vec![];
+                    s.write_signed_with(n, &Config::new()).ok()?;
+                    Some(display(&s))
+                })
+                .unwrap_or_else(|| "error\n".into());
+            stdout
+                .write_all(b"# signed LEB128\n")
+                .and_then(|_| stdout.write_all(leb.as_bytes()))
+                .and_then(|_| stdout.write_all(b"\n"))
+                .expect("failed to write to stdout");
+        }
 
         stdout.flush().expect("failed to flush stdout");
     }
@@ -0,0 +1,202 @@
+extern crate nt_leb128 as leb128;
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process;
+use leb128::read;
+use leb128::slice;
+
+const USAGE: &str = "\
+Usage:
+    leb128 encode [--signed] [--raw] <VALUE>...
+    leb128 decode [--signed] [--strict] [--raw] [<HEX>...]
+
+Encodes decimal or 0x-prefixed hex integers as LEB128, printing one line of hex
+bytes per value, or decodes LEB128 from hex bytes given as arguments (or read
+from stdin when there are none), printing one value per line.
+
+Options:
+    -s, --signed    Use signed LEB128 (the default is unsigned).
+    -r, --raw       Write (encode) or read (decode) raw bytes instead of hex.
+        --strict    Reject encodings that are longer than necessary.
+    -h, --help      Show this message.
+";
+
+struct Options {
+    signed: bool,
+    raw: bool,
+    strict: bool,
+    args: Vec<String>,
+}
+
+fn usage_error(msg: &str) -> ! {
+    eprintln!("leb128: {}\n\n{}", msg, USAGE);
+    process::exit(2);
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("leb128: {}", msg);
+    process::exit(1);
+}
+
+fn parse_options<I: Iterator<Item = String>>(args: I) -> Options {
+    let mut opts = Options {
+        // Builds with only one half of the encoding always use that half.
+        signed: cfg!(feature = "signed-only"),
+        raw: false,
+        strict: false,
+        args: Vec::new(),
+    };
+    let mut only_values = false;
+    for arg in args {
+        if only_values {
+            opts.args.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "-s" | "--signed" if cfg!(feature = "unsigned-only") => {
+                usage_error("signed LEB128 was left out of this build")
+            }
+            "-s" | "--signed" => opts.signed = true,
+            "-r" | "--raw" => opts.raw = true,
+            "--strict" => opts.strict = true,
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                process::exit(0);
+            }
+            "--" => only_values = true,
+            // Negative values look like flags, so only reject unknown words.
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option `{}`", arg)),
+            _ => opts.args.push(arg),
+        }
+    }
+    opts
+}
+
+fn parse_magnitude(s: &str) -> Option<u64> {
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+fn parse_signed(s: &str) -> Option<i64> {
+    match s.strip_prefix('-') {
+        Some(rest) => {
+            let magnitude = parse_magnitude(rest)?;
+            if magnitude > 1 << 63 {
+                return None;
+            }
+            Some(0u64.wrapping_sub(magnitude) as i64)
+        }
+        None => parse_magnitude(s).filter(|&m| m <= i64::MAX as u64).map(|m| m as i64),
+    }
+}
+
+fn parse_hex(args: &[String]) -> Result<Vec<u8>, String> {
+    leb128::hex::parse_hex(&args.join(" ")).map_err(|e| e.to_string())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+fn encode(opts: &Options) {
+    if opts.args.is_empty() {
+        usage_error("nothing to encode");
+    }
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for arg in &opts.args {
+        let encoded = match opts.signed {
+            #[cfg(not(feature = "unsigned-only"))]
+            true => parse_signed(arg).map(leb128::encode_signed_const::<{ leb128::MAX_LEN_I64 }>),
+            #[cfg(not(feature = "signed-only"))]
+            false => {
+                parse_magnitude(arg).map(leb128::encode_unsigned_const::<{ leb128::MAX_LEN_U64 }>)
+            }
+            #[cfg(any(feature = "signed-only", feature = "unsigned-only"))]
+            _ => unreachable!("only the half in this build is selected"),
+        };
+        let (bytes, len) = encoded.unwrap_or_else(|| {
+            fail(&format!("`{}` is not a {} 64 bit integer",
+                          arg,
+                          if opts.signed { "signed" } else { "unsigned" }))
+        });
+        let encoded = &bytes[..len];
+        let result = if opts.raw {
+            stdout.write_all(encoded)
+        } else {
+            writeln!(stdout, "{}", hex(encoded))
+        };
+        result.expect("failed to write to stdout");
+    }
+}
+
+fn decode(opts: &Options) {
+    let bytes = if !opts.args.is_empty() {
+        if opts.raw {
+            usage_error("--raw input is only read from stdin");
+        }
+        parse_hex(&opts.args)
+    } else {
+        let mut input = Vec::new();
+        io::stdin().read_to_end(&mut input).expect("failed to read from stdin");
+        if opts.raw {
+            Ok(input)
+        } else {
+            String::from_utf8(input)
+                .map_err(|_| "stdin is not hex text; use --raw for binary input".to_string())
+                .and_then(|text| parse_hex(&[text]))
+        }
+    };
+    let bytes = bytes.unwrap_or_else(|e| fail(&e));
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        let result = match (opts.signed, opts.strict) {
+            #[cfg(not(feature = "signed-only"))]
+            (false, false) => leb128::validate_unsigned(rest),
+            #[cfg(not(feature = "signed-only"))]
+            (false, true) => leb128::validate_unsigned_canonical(rest),
+            #[cfg(not(feature = "unsigned-only"))]
+            (true, false) => leb128::validate_signed(rest),
+            #[cfg(not(feature = "unsigned-only"))]
+            (true, true) => leb128::validate_signed_canonical(rest),
+            #[cfg(any(feature = "signed-only", feature = "unsigned-only"))]
+            _ => unreachable!("only the half in this build is selected"),
+        };
+        let len = result.unwrap_or_else(|e: read::Error| {
+            fail(&format!("at byte offset {}: {}", offset, e))
+        });
+        let encoding = &rest[..len];
+        let result = match opts.signed {
+            #[cfg(not(feature = "unsigned-only"))]
+            true => slice::decode_signed(encoding).map(|(v, _)| v.to_string()),
+            #[cfg(not(feature = "signed-only"))]
+            false => slice::decode_unsigned(encoding).map(|(v, _)| v.to_string()),
+            #[cfg(any(feature = "signed-only", feature = "unsigned-only"))]
+            _ => unreachable!("only the half in this build is selected"),
+        };
+        let value = result.expect("validated encodings decode");
+        writeln!(stdout, "{}", value).expect("failed to write to stdout");
+        offset += len;
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_else(|| usage_error("missing command"));
+    let opts = parse_options(args);
+    match command.as_str() {
+        "encode" => encode(&opts),
+        "decode" => decode(&opts),
+        "-h" | "--help" => print!("{}", USAGE),
+        _ => usage_error(&format!("unknown command `{}`", command)),
+    }
+}
@@ -0,0 +1,204 @@
+//! Each block starts with a one byte header naming its
+//! [`BlockCodec`](enum.BlockCodec.html), then the number of values as
+//! unsigned LEB128, then the values in that codec:
+//!
+//! * `Leb128`: each value as unsigned LEB128.
+//! * `ForLeb128` (frame of reference): the smallest value as unsigned LEB128,
+//!   then each value's difference from it as unsigned LEB128.
+//! * `BitPacked`: one byte giving the width in bits of the largest value,
+//!   then every value in that many bits, least significant first.
+//!
+//! [`encode_block`](fn.encode_block.html) measures the block and picks the
+//! smallest, so a column whose distribution changes from block to block is
+//! stored well throughout.
+//!
+//! ```
+//! use nt_leb128::block::{decode_block, encode_block, BlockCodec};
+//!
+//! let mut buf = Vec::new();
+//! // Large but close together.
+//! assert_eq!(encode_block(&[1 << 40, (1 << 40) + 3, (1 << 40) + 1], &mut buf),
+//!            BlockCodec::ForLeb128);
+//! // Uniformly spread over 12 bits.
+//! assert_eq!(encode_block(&[4000, 17, 2900, 3333, 1024, 777, 4095, 2], &mut buf),
+//!            BlockCodec::BitPacked);
+//!
+//! let mut vals = Vec::new();
+//! let rest = decode_block(&buf, 1024, &mut vals).unwrap();
+//! let rest = decode_block(rest, 1024, &mut vals).unwrap();
+//! assert!(rest.is_empty());
+//! assert_eq!(vals[..3], [1 << 40, (1 << 40) + 3, (1 << 40) + 1]);
+//! assert_eq!(vals[3..], [4000, 17, 2900, 3333, 1024, 777, 4095, 2]);
+//! ```
+
+use super::analyze::analyze;
+use super::encoded_len_unsigned;
+use super::codec::MAX_PREALLOC;
+use super::read::{not_enough_data, Error};
+use super::slice::decode_unsigned;
+use super::write::put_unsigned;
+
+/// How the values of a block are stored, named by its header byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BlockCodec {
+    /// Each value as unsigned LEB128.
+    Leb128,
+    /// The smallest value, then each value's difference from it, as
+    /// unsigned LEB128.
+    ForLeb128,
+    /// Every value in as many bits as the largest needs.
+    BitPacked,
+}
+
+impl BlockCodec {
+    /// The header byte naming this codec.
+    pub fn tag(self) -> u8 {
+        match self {
+            BlockCodec::Leb128 => 0,
+            BlockCodec::ForLeb128 => 1,
+            BlockCodec::BitPacked => 2,
+        }
+    }
+
+    /// The codec named by the header byte `tag`, if any.
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(BlockCodec::Leb128),
+            1 => Some(BlockCodec::ForLeb128),
+            2 => Some(BlockCodec::BitPacked),
+            _ => None,
+        }
+    }
+
+    /// The codec that stores `vals` in the fewest bytes, preferring plain
+    /// LEB128 on a tie.
+    pub fn choose(vals: &[u64]) -> Self {
+        let report = analyze(vals);
+        let min = vals.iter().copied().min().unwrap_or(0);
+        let for_bytes = encoded_len_unsigned(min)
+            + vals.iter().map(|&val| encoded_len_unsigned(val - min)).sum::<usize>();
+        let bitpacked_bytes = 1 + report.bitpacked_bytes;
+        if report.total_bytes <= for_bytes && report.total_bytes <= bitpacked_bytes {
+            BlockCodec::Leb128
+        } else if for_bytes <= bitpacked_bytes {
+            BlockCodec::ForLeb128
+        } else {
+            BlockCodec::BitPacked
+        }
+    }
+}
+
+/// Append `vals` to `out` as one block, in whichever codec stores them in
+/// the fewest bytes, and return that codec.
+pub fn encode_block(vals: &[u64], out: &mut Vec<u8>) -> BlockCodec {
+    let codec = BlockCodec::choose(vals);
+    encode_block_with(vals, codec, out);
+    codec
+}
+
+/// Append `vals` to `out` as one block in `codec`, whether or not it's the
+/// smallest.
+pub fn encode_block_with(vals: &[u64], codec: BlockCodec, out: &mut Vec<u8>) {
+    out.push(codec.tag());
+    put_unsigned(out, vals.len() as u64);
+    match codec {
+        BlockCodec::Leb128 => {
+            for &val in vals {
+                put_unsigned(out, val);
+            }
+        }
+        BlockCodec::ForLeb128 => {
+            let min = vals.iter().copied().min().unwrap_or(0);
+            put_unsigned(out, min);
+            for &val in vals {
+                put_unsigned(out, val - min);
+            }
+        }
+        BlockCodec::BitPacked => {
+            let width = bit_width(vals.iter().copied().max().unwrap_or(0));
+            out.push(width as u8);
+            let (mut acc, mut bits) = (0u128, 0);
+            for &val in vals {
+                acc |= (val as u128) << bits;
+                bits += width;
+                while bits >= 8 {
+                    out.push(acc as u8);
+                    acc >>= 8;
+                    bits -= 8;
+                }
+            }
+            if bits > 0 {
+                out.push(acc as u8);
+            }
+        }
+    }
+}
+
+/// Decode the block at the start of `input`, appending its values to `out`,
+/// and return the rest of `input`.
+///
+/// Fails with `Error::LengthExceeded`, before decoding or allocating for the
+/// values, if the block holds more than `max_len` of them, and with
+/// `Error::UnknownDiscriminant` if the header names no codec.
+pub fn decode_block<'a>(input: &'a [u8], max_len: usize, out: &mut Vec<u64>)
+                        -> Result<&'a [u8], Error> {
+    let (&tag, rest) = input.split_first().ok_or_else(not_enough_data)?;
+    let codec = BlockCodec::from_tag(tag).ok_or(Error::UnknownDiscriminant(tag as u64))?;
+    let (len, mut rest) = decode_unsigned(rest)?;
+    if len > max_len as u64 {
+        return Err(Error::LengthExceeded { len, max: max_len });
+    }
+    let len = len as usize;
+    out.reserve(len.min(MAX_PREALLOC));
+
+    match codec {
+        BlockCodec::Leb128 => {
+            for _ in 0..len {
+                let (val, next) = decode_unsigned(rest)?;
+                out.push(val);
+                rest = next;
+            }
+        }
+        BlockCodec::ForLeb128 => {
+            let (min, next) = decode_unsigned(rest)?;
+            rest = next;
+            for _ in 0..len {
+                let (delta, next) = decode_unsigned(rest)?;
+                out.push(min.checked_add(delta).ok_or(Error::Overflow)?);
+                rest = next;
+            }
+        }
+        BlockCodec::BitPacked => {
+            let (&width, packed) = rest.split_first().ok_or_else(not_enough_data)?;
+            let width = width as u32;
+            if width > 64 {
+                return Err(Error::Overflow);
+            }
+            let packed_len = len.checked_mul(width as usize).ok_or_else(not_enough_data)?
+                                .div_ceil(8);
+            if packed.len() < packed_len {
+                return Err(not_enough_data());
+            }
+            let mask = if width == 0 { 0 } else { u64::MAX >> (64 - width) };
+            let (mut acc, mut bits) = (0u128, 0);
+            let mut bytes = packed[..packed_len].iter();
+            for _ in 0..len {
+                while bits < width {
+                    acc |= (*bytes.next().expect("length was checked") as u128) << bits;
+                    bits += 8;
+                }
+                out.push(acc as u64 & mask);
+                acc >>= width;
+                bits -= width;
+            }
+            rest = &packed[packed_len..];
+        }
+    }
+    Ok(rest)
+}
+
+/// The number of bits `val` needs.
+fn bit_width(val: u64) -> u32 {
+    64 - val.leading_zeros()
+}
@@ -0,0 +1,243 @@
+//! The few buffer operations the readers and writers are built on, so the
+//! same logic serves byte slices and every version of the `bytes` crate that
+//! is supported.
+
+use std::io::IoSlice;
+use std::mem;
+
+/// A readable buffer, consumed from the front a chunk at a time.
+pub(crate) trait Source {
+    /// The bytes at the front of the buffer, empty only at its end.
+    fn chunk(&self) -> &[u8];
+
+    /// Fill `dst` with the buffer's leading chunks, returning how many.
+    #[cfg_attr(all(feature = "bytes", not(feature = "bytes-1")), allow(dead_code))]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize;
+
+    /// The number of bytes left in the buffer.
+    #[cfg_attr(any(not(feature = "alloc"), feature = "signed-only"), allow(dead_code))]
+    fn remaining(&self) -> usize;
+
+    /// Consume `cnt` bytes from the front of the buffer.
+    fn advance(&mut self, cnt: usize);
+}
+
+/// A writable buffer, appended to at the back.
+pub(crate) trait Sink {
+    /// The number of bytes that can still be appended.
+    fn remaining_mut(&self) -> usize;
+
+    /// Append `src`, which must fit.
+    fn put_slice(&mut self, src: &[u8]);
+}
+
+impl Source for &[u8] {
+    fn chunk(&self) -> &[u8] {
+        self
+    }
+
+    fn chunks_vectored<'b>(&'b self, dst: &mut [IoSlice<'b>]) -> usize {
+        if self.is_empty() || dst.is_empty() {
+            return 0;
+        }
+        dst[0] = IoSlice::new(self);
+        1
+    }
+
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        *self = &self[cnt..];
+    }
+}
+
+impl Sink for &mut [u8] {
+    fn remaining_mut(&self) -> usize {
+        self.len()
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        assert!(src.len() <= self.len(), "buffer overflow");
+        let (head, rest) = mem::take(self).split_at_mut(src.len());
+        head.copy_from_slice(src);
+        *self = rest;
+    }
+}
+
+impl Sink for Vec<u8> {
+    fn remaining_mut(&self) -> usize {
+        isize::MAX as usize - self.len()
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.extend_from_slice(src)
+    }
+}
+
+/// Adapts a buffer from version 0.5 of the `bytes` crate.
+#[cfg(feature = "bytes")]
+pub(crate) struct Bytes05<'a, B: ?Sized>(pub(crate) &'a mut B);
+
+#[cfg(feature = "bytes")]
+impl<'a, B: bytes::Buf + ?Sized> Source for Bytes05<'a, B> {
+    fn chunk(&self) -> &[u8] {
+        self.0.bytes()
+    }
+
+    fn chunks_vectored<'b>(&'b self, dst: &mut [IoSlice<'b>]) -> usize {
+        self.0.bytes_vectored(dst)
+    }
+
+    fn remaining(&self) -> usize {
+        self.0.remaining()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.0.advance(cnt)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'a, B: bytes::BufMut + ?Sized> Sink for Bytes05<'a, B> {
+    fn remaining_mut(&self) -> usize {
+        self.0.remaining_mut()
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.0.put_slice(src)
+    }
+}
+
+/// Implements every `Leb128Read` method of a `Source` with the shared logic
+/// in `read`. The `bytes` 0.5 blanket implementation can't use it, as its
+/// `peek_*` methods only borrow the buffer.
+#[allow(unused_macros)]
+macro_rules! forward_read {
+    () => {
+        #[cfg(not(feature = "unsigned-only"))]
+        fn read_signed(&mut self) -> Result<(i64, usize), $crate::read::Error> {
+            $crate::read::read_signed_from(self)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn read_unsigned(&mut self) -> Result<(u64, usize), $crate::read::Error> {
+            $crate::read::read_unsigned_from(self)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn read_unsigned_with_bit_limit(&mut self, bits: u32)
+                                        -> Result<(u64, usize), $crate::read::Error> {
+            $crate::read::read_unsigned_with_bit_limit_from(self, bits)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn read_unsigned_with(&mut self, config: &$crate::Config)
+                              -> Result<(u64, usize), $crate::read::Error> {
+            $crate::read::read_chunked(self, |bytes| config.decode_unsigned(bytes))
+        }
+
+        #[cfg(not(feature = "unsigned-only"))]
+        fn read_signed_with(&mut self, config: &$crate::Config)
+                            -> Result<(i64, usize), $crate::read::Error> {
+            $crate::read::read_chunked(self, |bytes| config.decode_signed(bytes))
+        }
+
+        #[cfg(not(feature = "unsigned-only"))]
+        fn peek_signed(&self) -> Result<(i64, usize), $crate::read::Error> {
+            $crate::read::peek_signed_from(self)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn peek_unsigned(&self) -> Result<(u64, usize), $crate::read::Error> {
+            $crate::read::peek_unsigned_from(self)
+        }
+
+        fn next_len(&self) -> Result<usize, $crate::read::Error> {
+            $crate::read::next_len_from(self)
+        }
+
+        #[cfg(not(feature = "unsigned-only"))]
+        fn skip_signed(&mut self) -> Result<usize, $crate::read::Error> {
+            $crate::read::skip_signed_from(self)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn skip_unsigned(&mut self) -> Result<usize, $crate::read::Error> {
+            $crate::read::skip_unsigned_from(self)
+        }
+
+        fn skip_n(&mut self, n: usize) -> Result<usize, $crate::read::Error> {
+            $crate::read::skip_n(self, n)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn read_option_unsigned(&mut self)
+                                -> Result<(Option<u64>, usize), $crate::read::Error> {
+            $crate::read::read_option_unsigned_from(self)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn read_unsigned_pair(&mut self) -> Result<((u64, u64), usize), $crate::read::Error> {
+            let ([first, second], len) = $crate::read::read_unsigned_group(self)?;
+            Ok(((first, second), len))
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn read_unsigned_triple(&mut self)
+                                -> Result<((u64, u64, u64), usize), $crate::read::Error> {
+            let ([first, second, third], len) = $crate::read::read_unsigned_group(self)?;
+            Ok(((first, second, third), len))
+        }
+
+        #[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+        fn read_bytes(&mut self, max_len: usize) -> Result<Vec<u8>, $crate::read::Error> {
+            $crate::read::read_bytes(self, max_len)
+        }
+    }
+}
+
+/// Implements every required `Leb128Write` method of a `Sink`, and those
+/// taking a `Config`, with the shared logic in `write`.
+#[allow(unused_macros)]
+macro_rules! forward_write {
+    () => {
+        #[cfg(not(feature = "unsigned-only"))]
+        fn write_signed(&mut self, val: i64) -> Result<usize, $crate::write::Error> {
+            $crate::write::write_signed_to(self, val)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn write_unsigned(&mut self, val: u64) -> Result<usize, $crate::write::Error> {
+            $crate::write::write_unsigned_to(self, val)
+        }
+
+        #[cfg(not(feature = "unsigned-only"))]
+        fn try_write_signed(&mut self, val: i64) -> Result<usize, $crate::write::Error> {
+            $crate::write::try_put_signed(self, val)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn try_write_unsigned(&mut self, val: u64) -> Result<usize, $crate::write::Error> {
+            $crate::write::try_put_unsigned(self, val)
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn write_bytes(&mut self, bytes: &[u8]) -> Result<usize, $crate::write::Error> {
+            Ok($crate::write::put_bytes(self, bytes))
+        }
+
+        #[cfg(not(feature = "signed-only"))]
+        fn write_unsigned_with(&mut self, val: u64, config: &$crate::Config)
+                               -> Result<usize, $crate::write::Error> {
+            $crate::write::put_unsigned_with(self, val, config)
+        }
+
+        #[cfg(not(feature = "unsigned-only"))]
+        fn write_signed_with(&mut self, val: i64, config: &$crate::Config)
+                             -> Result<usize, $crate::write::Error> {
+            $crate::write::put_signed_with(self, val, config)
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! The traits are implemented directly for every `Buf` and `BufMut` from
+//! version 0.5 of `bytes`. Rust doesn't allow a second set of blanket
+//! implementations for version 1, so its buffers are wrapped in
+//! [`Bytes1`](struct.Bytes1.html) instead; both share the same decoding and
+//! encoding logic.
+
+/// Version 1 of `bytes`, re-exported so downstream crates can name its types
+/// without depending on the same version themselves.
+pub use bytes_1 as bytes;
+
+use super::buf::{Sink, Source};
+use super::read::Leb128Read;
+#[cfg(not(feature = "signed-only"))]
+use super::read::{shared_prefix, Error, Leb128ReadShared};
+use super::write::Leb128Write;
+use bytes_1::{Buf, BufMut};
+#[cfg(not(feature = "signed-only"))]
+use bytes_1::{Bytes, BytesMut};
+use std::io::IoSlice;
+
+/// A wrapper giving a buffer from version 1 of `bytes` the `Leb128Read` and
+/// `Leb128Write` methods.
+///
+/// ```
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// use nt_leb128::bytes1::Bytes1;
+/// use nt_leb128::{Leb128Read, Leb128Write};
+///
+/// let mut writer = Bytes1::new(bytes_1::BytesMut::new());
+/// writer.write_unsigned(624485).unwrap();
+/// writer.write_signed(-2).unwrap();
+///
+/// let mut reader = Bytes1::new(writer.into_inner().freeze());
+/// assert_eq!(reader.read_unsigned().unwrap(), (624485, 3));
+/// assert_eq!(reader.read_signed().unwrap(), (-2, 1));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Bytes1<B> {
+    inner: B,
+}
+
+impl<B> Bytes1<B> {
+    /// Wrap `inner`. Pass `&mut buf` to keep using `buf` afterwards.
+    pub fn new(inner: B) -> Self {
+        Bytes1 { inner }
+    }
+
+    /// Get a reference to the inner buffer.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner buffer.
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner buffer.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Buf> Source for Bytes1<B> {
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.inner.advance(cnt)
+    }
+}
+
+impl<B: BufMut> Sink for Bytes1<B> {
+    fn remaining_mut(&self) -> usize {
+        self.inner.remaining_mut()
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.inner.put_slice(src)
+    }
+}
+
+impl<B: Buf> Leb128Read for Bytes1<B> {
+    forward_read!();
+}
+
+impl<B: BufMut> Leb128Write for Bytes1<B> {
+    forward_write!();
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl Leb128ReadShared for Bytes1<Bytes> {
+    type Shared = Bytes;
+
+    fn read_length_prefixed_bytes(&mut self, max_len: usize) -> Result<Bytes, Error> {
+        let (prefix, len) = shared_prefix(self, self.inner.len(), max_len)?;
+        self.inner.advance(prefix);
+        Ok(self.inner.split_to(len))
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl Leb128ReadShared for Bytes1<BytesMut> {
+    type Shared = Bytes;
+
+    fn read_length_prefixed_bytes(&mut self, max_len: usize) -> Result<Bytes, Error> {
+        let (prefix, len) = shared_prefix(self, self.inner.len(), max_len)?;
+        self.inner.advance(prefix);
+        Ok(self.inner.split_to(len).freeze())
+    }
+}
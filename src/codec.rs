@@ -0,0 +1,192 @@
+use super::read::{self, Leb128Read};
+use super::write::{self, Leb128Write, Measure};
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::encoded_len_unsigned;
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::encoded_len_signed;
+use std::convert::TryFrom;
+
+/// Types that can be encoded as a sequence of LEB128 numbers.
+///
+/// Unsigned integers are encoded as unsigned LEB128 and signed integers as
+/// signed LEB128. Slices, `Vec`s and `String`s are prefixed with their length
+/// as unsigned LEB128 (the `Vec`, `String` and `Box` implementations require
+/// the `alloc` feature). With the `derive` feature, `#[derive(Leb128Encode)]`
+/// encodes a struct's fields in declaration order.
+pub trait Leb128Encode {
+    /// Encode `self` into `buf`, returning the number of bytes written.
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error>;
+
+    /// The number of bytes `encode` would write, computed without writing
+    /// anything, so callers can reserve exact capacity or fill in length
+    /// prefixes before encoding.
+    ///
+    /// The default implementation encodes into a `write::Measure`, which
+    /// counts bytes and discards them; the implementations in this crate and
+    /// derived ones compute the length directly. If `encode` would fail, such
+    /// as on a number the default flavor can't represent, it's the length
+    /// written before the failure.
+    fn encoded_len(&self) -> usize {
+        let mut measure = Measure::new();
+        let _ = self.encode(&mut measure);
+        measure.len()
+    }
+}
+
+/// Types that can be decoded from a sequence of LEB128 numbers, as written by
+/// their `Leb128Encode` implementation.
+pub trait Leb128Decode: Sized {
+    /// Decode a value from the front of `buf`.
+    fn decode<R: Leb128Read>(buf: &mut R) -> Result<Self, read::Error>;
+}
+
+/// The most items a decoded `Vec` reserves room for before any are decoded.
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+pub(crate) const MAX_PREALLOC: usize = 4096;
+
+macro_rules! impl_codec {
+    ($wide:ty, $write:ident, $read:ident, $len:ident, $($ty:ty),*) => {
+        $(
+            impl Leb128Encode for $ty {
+                fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+                    buf.$write(*self as $wide)
+                }
+
+                fn encoded_len(&self) -> usize {
+                    $len(*self as $wide)
+                }
+            }
+
+            impl Leb128Decode for $ty {
+                fn decode<R: Leb128Read>(buf: &mut R) -> Result<Self, read::Error> {
+                    let (val, _) = buf.$read()?;
+                    <$ty>::try_from(val).map_err(|_| read::Error::Overflow)
+                }
+            }
+        )*
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl_codec!(u64, write_unsigned, read_unsigned, encoded_len_unsigned, u8, u16, u32, u64, usize);
+#[cfg(not(feature = "unsigned-only"))]
+impl_codec!(i64, write_signed, read_signed, encoded_len_signed, i8, i16, i32, i64, isize);
+
+#[cfg(not(feature = "signed-only"))]
+impl Leb128Encode for bool {
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+        buf.write_unsigned(*self as u64)
+    }
+
+    fn encoded_len(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl Leb128Decode for bool {
+    fn decode<R: Leb128Read>(buf: &mut R) -> Result<Self, read::Error> {
+        match buf.read_unsigned()?.0 {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(read::Error::Overflow),
+        }
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl<T: Leb128Encode> Leb128Encode for [T] {
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+        let mut written = buf.write_unsigned(self.len() as u64)?;
+        for item in self {
+            written += item.encode(buf)?;
+        }
+        Ok(written)
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_len_unsigned(self.len() as u64) + self.iter().map(T::encoded_len).sum::<usize>()
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+impl<T: Leb128Encode> Leb128Encode for Vec<T> {
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+        self[..].encode(buf)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self[..].encoded_len()
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+impl<T: Leb128Decode> Leb128Decode for Vec<T> {
+    fn decode<R: Leb128Read>(buf: &mut R) -> Result<Self, read::Error> {
+        let len = usize::decode(buf)?;
+        // Don't let a corrupt length allocate much up front; the `Vec` grows
+        // as items actually decode.
+        let mut items = Vec::with_capacity(len.min(MAX_PREALLOC));
+        for _ in 0..len {
+            items.push(T::decode(buf)?);
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl Leb128Encode for str {
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+        buf.write_str(self)
+    }
+
+    fn encoded_len(&self) -> usize {
+        encoded_len_unsigned(self.len() as u64) + self.len()
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+impl Leb128Encode for String {
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+        self.as_str().encode(buf)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.as_str().encoded_len()
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+impl Leb128Decode for String {
+    fn decode<R: Leb128Read>(buf: &mut R) -> Result<Self, read::Error> {
+        buf.read_string(usize::MAX)
+    }
+}
+
+impl<T: Leb128Encode + ?Sized> Leb128Encode for &T {
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+        (**self).encode(buf)
+    }
+
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Leb128Encode + ?Sized> Leb128Encode for Box<T> {
+    fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+        (**self).encode(buf)
+    }
+
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Leb128Decode> Leb128Decode for Box<T> {
+    fn decode<R: Leb128Read>(buf: &mut R) -> Result<Self, read::Error> {
+        T::decode(buf).map(Box::new)
+    }
+}
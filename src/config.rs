@@ -0,0 +1,393 @@
+//! ```
+//! # #[cfg(not(feature = "signed-only"))] {
+//! use nt_leb128::{Config, Flavor};
+//! use nt_leb128::prelude::*;
+//!
+//! // A 32 bit WebAssembly index may be padded, but to no more than 5 bytes.
+//! let config = Config::with_flavor(Flavor::Wasm);
+//! let mut buf = &[0x83, 0x80, 0x80, 0x80, 0x00][..];
+//! assert_eq!(buf.read_unsigned_with(&config).unwrap(), (3, 5));
+//!
+//! // The same rules with canonical encodings required.
+//! let strict = config.canonical(true);
+//! assert!(strict.decode_unsigned(&[0x83, 0x00]).is_err());
+//! # }
+//! ```
+
+use super::raw;
+use super::read::{not_enough_data, Error, Leb128Read};
+use super::slice::{is_canonical_signed, is_canonical_unsigned};
+use super::write::{self, Leb128Write};
+use nt_leb128_core::{encoded_len_unsigned, MAX_LEN_U64};
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::encoded_len_signed;
+
+#[cfg(any(all(feature = "default-flavor-dwarf", feature = "default-flavor-wasm"),
+          all(feature = "default-flavor-dwarf", feature = "default-flavor-protobuf"),
+          all(feature = "default-flavor-wasm", feature = "default-flavor-protobuf")))]
+compile_error!("at most one of the `default-flavor-*` features can be enabled");
+
+/// The flavor whose rules the plain `read_*` and `write_*` methods apply.
+const DEFAULT_FLAVOR: Flavor = if cfg!(feature = "default-flavor-dwarf") {
+    Flavor::Dwarf
+} else if cfg!(feature = "default-flavor-wasm") {
+    Flavor::Wasm
+} else if cfg!(feature = "default-flavor-protobuf") {
+    Flavor::Protobuf
+} else {
+    Flavor::Generic
+};
+
+/// Whether a `default-flavor-*` feature is enabled, so the plain methods must
+/// check `Config::DEFAULT` rather than take their fast paths.
+pub(crate) const DEFAULT_FLAVORED: bool = cfg!(any(feature = "default-flavor-dwarf",
+                                                   feature = "default-flavor-wasm",
+                                                   feature = "default-flavor-protobuf"));
+
+/// A set of encodings with their own validation rules, used as the starting
+/// point of a `Config`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Flavor {
+    /// 64 bit values in at most ten bytes, padding allowed, and an error for
+    /// anything out of range. These are the rules of the plain `read_*`
+    /// methods, unless a `default-flavor-*` feature chooses others.
+    Generic,
+    /// DWARF, which allows padded encodings. The rules are those of
+    /// `Generic`.
+    Dwarf,
+    /// WebAssembly, with the 32 bit width of its indices and lengths: at most
+    /// five bytes, padding allowed, and unused bits that must be zero (or
+    /// copies of the sign bit). Set `bits(64)` for `i64` constants.
+    Wasm,
+    /// Protocol Buffers varints, whose bits beyond 64 are discarded rather
+    /// than rejected. Signed numbers are sign extended to 64 bits, as in
+    /// `int32` and `int64` fields, so a negative one always takes ten bytes.
+    Protobuf,
+    /// Protocol Buffers `sint64` fields: the rules of `Protobuf`, with signed
+    /// numbers zigzag encoded.
+    ProtobufZigzag,
+    /// The multiformats `unsigned-varint`: 63 bit values in at most nine
+    /// bytes, canonical encodings only. The format defines no signed
+    /// numbers; they are read and written as signed LEB128 under the same
+    /// limits.
+    Multiformats,
+    /// Apache ORC's base 128 varints: the rules of `Generic`, with signed
+    /// numbers zigzag encoded, as ORC's run length encodings store them.
+    /// Unlike protobuf, bits beyond 64 are an error rather than discarded,
+    /// and negative numbers are never written in ten bytes of two's
+    /// complement. See `orc` for the 128 bit decimal encoding.
+    Orc,
+}
+
+impl Flavor {
+    /// The rules of this flavor, as `Config::with_flavor` gives them.
+    pub const fn config(self) -> Config {
+        Config::with_flavor(self)
+    }
+
+    /// Read an unsigned number from `buf` under the rules of this flavor,
+    /// for applications that choose a flavor at runtime.
+    ///
+    /// ```
+    /// use nt_leb128::Flavor;
+    ///
+    /// // Chosen by a handshake, say.
+    /// let flavor = Flavor::Multiformats;
+    /// let mut buf = Vec::new();
+    /// flavor.write_unsigned(&mut buf, 300).expect("Should write number");
+    /// assert_eq!(flavor.read_unsigned(&mut &buf[..]).unwrap(), (300, 2));
+    /// assert!(flavor.read_unsigned(&mut &[0xac, 0x82, 0x00][..]).is_err());
+    /// ```
+    #[cfg(not(feature = "signed-only"))]
+    pub fn read_unsigned<R: Leb128Read + ?Sized>(self, buf: &mut R) -> Result<(u64, usize), Error> {
+        buf.read_unsigned_with(&self.config())
+    }
+
+    /// Read a signed number from `buf` under the rules of this flavor.
+    #[cfg(not(feature = "unsigned-only"))]
+    pub fn read_signed<R: Leb128Read + ?Sized>(self, buf: &mut R) -> Result<(i64, usize), Error> {
+        buf.read_signed_with(&self.config())
+    }
+
+    /// Write an unsigned number to `buf` under the rules of this flavor,
+    /// returning the number of bytes written.
+    #[cfg(not(feature = "signed-only"))]
+    pub fn write_unsigned<W: Leb128Write + ?Sized>(self,
+                                                   buf: &mut W,
+                                                   val: u64)
+                                                   -> Result<usize, write::Error> {
+        buf.write_unsigned_with(val, &self.config())
+    }
+
+    /// Write a signed number to `buf` under the rules of this flavor,
+    /// returning the number of bytes written.
+    #[cfg(not(feature = "unsigned-only"))]
+    pub fn write_signed<W: Leb128Write + ?Sized>(self,
+                                                 buf: &mut W,
+                                                 val: i64)
+                                                 -> Result<usize, write::Error> {
+        buf.write_signed_with(val, &self.config())
+    }
+}
+
+/// How signed numbers are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SignedEncoding {
+    /// Signed LEB128, in two's complement with the sign in the highest
+    /// payload bit.
+    Sleb128,
+    /// Zigzag encoded, then written as unsigned LEB128. See
+    /// `raw::zigzag_encode`.
+    ZigZag,
+    /// Sign extended to 64 bits and written as unsigned LEB128, so that a
+    /// negative number takes ten bytes whatever `bits` allows.
+    SignExtended,
+}
+
+/// What to do with a value wider than a `Config` allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OverflowPolicy {
+    /// Fail, with `read::Error::Overflow` or `write::Error::OutOfRange`.
+    Error,
+    /// Keep only the lowest bits, as a cast between integer types would.
+    Truncate,
+}
+
+/// The rules for reading and writing LEB128, gathered in one place rather
+/// than spread across differently named methods.
+///
+/// Pass it to `Leb128Read::read_unsigned_with` and friends, or decode slices
+/// with it directly. Encodings are never longer than ten bytes, whatever the
+/// rules, and values are never wider than 64 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Config {
+    flavor: Flavor,
+    max_len: usize,
+    bits: u32,
+    canonical: bool,
+    overflow: OverflowPolicy,
+    signed: SignedEncoding,
+}
+
+impl Config {
+    /// The rules of the plain `read_*` and `write_*` methods, unless a
+    /// `default-flavor-*` feature chooses others. Those methods always encode
+    /// signed numbers as signed LEB128, so these rules do too, whatever the
+    /// flavor's.
+    ///
+    /// ```
+    /// # #[cfg(not(any(feature = "default-flavor-dwarf", feature = "default-flavor-wasm",
+    /// #               feature = "default-flavor-protobuf")))]
+    /// assert_eq!(nt_leb128::Config::DEFAULT, nt_leb128::Config::new());
+    /// ```
+    pub const DEFAULT: Config =
+        Config::with_flavor(DEFAULT_FLAVOR).signed_encoding(SignedEncoding::Sleb128);
+
+    /// The `Generic` rules.
+    pub const fn new() -> Self {
+        Config::with_flavor(Flavor::Generic)
+    }
+
+    /// The rules of `flavor`, which the other builder methods adjust.
+    pub const fn with_flavor(flavor: Flavor) -> Self {
+        let config = Config {
+            flavor,
+            max_len: MAX_LEN_U64,
+            bits: 64,
+            canonical: false,
+            overflow: OverflowPolicy::Error,
+            signed: SignedEncoding::Sleb128,
+        };
+        match flavor {
+            Flavor::Generic | Flavor::Dwarf => config,
+            Flavor::Wasm => config.bits(32),
+            Flavor::Protobuf => {
+                config.overflow(OverflowPolicy::Truncate)
+                    .signed_encoding(SignedEncoding::SignExtended)
+            }
+            Flavor::ProtobufZigzag => {
+                config.overflow(OverflowPolicy::Truncate).signed_encoding(SignedEncoding::ZigZag)
+            }
+            Flavor::Multiformats => config.max_len(9).bits(63).canonical(true),
+            Flavor::Orc => config.signed_encoding(SignedEncoding::ZigZag),
+        }
+    }
+
+    /// Limit encodings to `max_len` bytes. Values are also limited to as
+    /// many bytes as `bits` needs, whichever is fewer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_len` is 0 or greater than 10.
+    pub const fn max_len(mut self, max_len: usize) -> Self {
+        assert!(max_len >= 1 && max_len <= MAX_LEN_U64, "max_len must be between 1 and 10");
+        self.max_len = max_len;
+        self
+    }
+
+    /// Limit values to `bits` bits, so that they fit in a `u{bits}` when
+    /// unsigned or an `i{bits}` when signed, and their encodings to the
+    /// bytes those bits need.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is 0 or greater than 64.
+    pub const fn bits(mut self, bits: u32) -> Self {
+        assert!(bits >= 1 && bits <= 64, "bits must be between 1 and 64");
+        self.bits = bits;
+        self
+    }
+
+    /// Whether to reject encodings that are longer than necessary with
+    /// `read::Error::NonCanonical`.
+    pub const fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// What to do with values wider than `bits`.
+    pub const fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// How to encode signed numbers.
+    pub const fn signed_encoding(mut self, signed: SignedEncoding) -> Self {
+        self.signed = signed;
+        self
+    }
+
+    /// The flavor these rules started from.
+    pub const fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// How these rules encode signed numbers.
+    pub const fn signed(&self) -> SignedEncoding {
+        self.signed
+    }
+
+    /// Decode an unsigned number from the front of `bytes` under these rules,
+    /// returning it and the number of bytes it occupies.
+    pub fn decode_unsigned(&self, bytes: &[u8]) -> Result<(u64, usize), Error> {
+        let (raw, len) = self.gather(bytes)?;
+        if self.canonical && !is_canonical_unsigned(&bytes[..len]) {
+            return Err(Error::NonCanonical);
+        }
+        if raw >> self.bits != 0 && self.overflow == OverflowPolicy::Error {
+            return Err(Error::Overflow);
+        }
+        Ok(((raw & (u128::MAX >> (128 - self.bits))) as u64, len))
+    }
+
+    /// Decode a signed number from the front of `bytes` under these rules,
+    /// returning it and the number of bytes it occupies.
+    pub fn decode_signed(&self, bytes: &[u8]) -> Result<(i64, usize), Error> {
+        if self.signed == SignedEncoding::ZigZag {
+            // A zigzag encoded `i{bits}` fits in a `u{bits}`.
+            let (val, len) = self.decode_unsigned(bytes)?;
+            return Ok((raw::zigzag_decode(val), len));
+        }
+        if self.signed == SignedEncoding::SignExtended {
+            // Negative numbers use all 64 bits, whatever `bits` allows.
+            let (val, len) = self.bits(64).decode_unsigned(bytes)?;
+            let val = val as i64;
+            let unused = 64 - self.bits;
+            let truncated = (val << unused) >> unused;
+            if truncated != val && self.overflow == OverflowPolicy::Error {
+                return Err(Error::Overflow);
+            }
+            return Ok((truncated, len));
+        }
+        let (raw, len) = self.gather(bytes)?;
+        if self.canonical && !is_canonical_signed(&bytes[..len]) {
+            return Err(Error::NonCanonical);
+        }
+        let shift = raw::shift_of(len);
+        let mut val = raw as i128;
+        if bytes[len - 1] & raw::SIGN_BIT != 0 {
+            val |= !0 << shift;
+        }
+        // Sign extend from the highest allowed bit, then see if that changed
+        // anything.
+        let unused = 128 - self.bits;
+        let truncated = (val << unused) >> unused;
+        if truncated != val && self.overflow == OverflowPolicy::Error {
+            return Err(Error::Overflow);
+        }
+        Ok((truncated as i64, len))
+    }
+
+    /// Check `val` against these rules, returning what to encode.
+    #[cfg(not(feature = "signed-only"))]
+    pub(crate) fn encodable_unsigned(&self, val: u64) -> Result<u64, write::Error> {
+        let mask = u64::MAX >> (64 - self.bits);
+        if val & !mask != 0 && self.overflow == OverflowPolicy::Error {
+            return Err(write::Error::OutOfRange);
+        }
+        self.check_len(encoded_len_unsigned(val & mask))?;
+        Ok(val & mask)
+    }
+
+    /// Check `val` against these rules, returning what to encode.
+    #[cfg(not(feature = "unsigned-only"))]
+    pub(crate) fn encodable_signed(&self, val: i64) -> Result<i64, write::Error> {
+        let unused = 64 - self.bits;
+        let truncated = (val << unused) >> unused;
+        if truncated != val && self.overflow == OverflowPolicy::Error {
+            return Err(write::Error::OutOfRange);
+        }
+        self.check_len(self.encoded_len_signed(truncated))?;
+        Ok(truncated)
+    }
+
+    /// The length of the encoding of `val`, an encodable value, under these
+    /// rules.
+    #[cfg(not(feature = "unsigned-only"))]
+    pub(crate) fn encoded_len_signed(&self, val: i64) -> usize {
+        match self.signed {
+            SignedEncoding::Sleb128 => encoded_len_signed(val),
+            SignedEncoding::ZigZag => encoded_len_unsigned(raw::zigzag_encode(val)),
+            SignedEncoding::SignExtended => encoded_len_unsigned(val as u64),
+        }
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), write::Error> {
+        if len > self.max_len {
+            return Err(write::Error::LengthExceeded { len, max: self.max_len });
+        }
+        Ok(())
+    }
+
+    /// Collect the payload of the encoding at the front of `bytes`, at most
+    /// `max_len` bytes or as many as `bits` needs, without checking its range.
+    fn gather(&self, bytes: &[u8]) -> Result<(u128, usize), Error> {
+        let max_len = self.max_len.min(self.bits.div_ceil(7) as usize);
+        let mut raw = 0u128;
+        for (i, &byte) in bytes.iter().take(max_len).enumerate() {
+            raw |= (raw::low_bits_of_byte(byte) as u128) << raw::shift_of(i);
+            if raw::is_terminal(byte) {
+                return Ok((raw, i + 1));
+            }
+        }
+        if bytes.len() < max_len {
+            return Err(not_enough_data());
+        }
+        Err(Error::Overflow)
+    }
+}
+
+impl From<Flavor> for Config {
+    fn from(flavor: Flavor) -> Self {
+        Config::with_flavor(flavor)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
@@ -0,0 +1,161 @@
+use super::{CONTINUATION_BIT, SIGN_BIT, low_bits_of_byte};
+use super::read::{Error, MAX_LEN, signed_last_byte_fits, unsigned_last_byte_fits};
+use std::marker::PhantomData;
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u64 {}
+    impl Sealed for i64 {}
+}
+
+/// The integer types a [`Decoder`](struct.Decoder.html) can produce: `u64`
+/// for unsigned LEB128 and `i64` for signed.
+pub trait Integer: private::Sealed + Copy {
+    #[doc(hidden)]
+    fn last_byte_fits(byte: u8) -> bool;
+
+    #[doc(hidden)]
+    fn finish(raw: u64, len: usize, last: u8) -> Self;
+}
+
+impl Integer for u64 {
+    fn last_byte_fits(byte: u8) -> bool {
+        unsigned_last_byte_fits(byte)
+    }
+
+    fn finish(raw: u64, _len: usize, _last: u8) -> Self {
+        raw
+    }
+}
+
+impl Integer for i64 {
+    fn last_byte_fits(byte: u8) -> bool {
+        signed_last_byte_fits(byte)
+    }
+
+    fn finish(raw: u64, len: usize, last: u8) -> Self {
+        let mut result = raw as i64;
+        let shift = 7 * len;
+        if shift < 64 && (SIGN_BIT & last) == SIGN_BIT {
+            // Sign extend the result.
+            result |= !0 << shift;
+        }
+        result
+    }
+}
+
+/// The outcome of feeding a chunk of bytes to a [`Decoder`](struct.Decoder.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodeProgress<T> {
+    /// The whole chunk was consumed without finishing a value; the decoder
+    /// holds on to what it has seen until the next chunk arrives.
+    Incomplete,
+    /// A value was finished.
+    Complete {
+        /// The decoded number.
+        value: T,
+        /// The number of bytes its encoding occupied, across every chunk.
+        len: usize,
+        /// How many bytes of this chunk were consumed. Anything after them
+        /// belongs to the next value.
+        consumed: usize,
+    },
+}
+
+/// A decoder that is fed bytes as they arrive, in chunks split anywhere, and
+/// keeps the partial value between chunks.
+///
+/// Unlike the `Buf` based readers, a value split across chunks needs neither
+/// buffering nor a retry from its first byte.
+///
+/// ```
+/// # #[cfg(not(feature = "signed-only"))] {
+/// use nt_leb128::decoder::{DecodeProgress, Decoder};
+///
+/// let mut decoder = Decoder::unsigned();
+/// assert_eq!(decoder.push(&[0xe5, 0x8e]).unwrap(), DecodeProgress::Incomplete);
+/// assert_eq!(decoder.push(&[0x26, 0x01]).unwrap(),
+///            DecodeProgress::Complete { value: 624485, len: 3, consumed: 1 });
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Decoder<T> {
+    raw: u64,
+    len: usize,
+    marker: PhantomData<T>,
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl Decoder<u64> {
+    /// Create a decoder for unsigned LEB128.
+    pub fn unsigned() -> Self {
+        Decoder::new()
+    }
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+impl Decoder<i64> {
+    /// Create a decoder for signed LEB128.
+    pub fn signed() -> Self {
+        Decoder::new()
+    }
+}
+
+impl<T: Integer> Decoder<T> {
+    /// Create a decoder for values of type `T`.
+    pub fn new() -> Self {
+        Decoder {
+            raw: 0,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Feed the next chunk of input, consuming it up to the end of the first
+    /// value that finishes within it.
+    ///
+    /// On error the partial value is discarded, so the decoder is ready for a
+    /// new value, though the stream itself is unlikely to be recoverable.
+    pub fn push(&mut self, bytes: &[u8]) -> Result<DecodeProgress<T>, Error> {
+        for (i, &byte) in bytes.iter().enumerate() {
+            if self.len == MAX_LEN - 1 && !T::last_byte_fits(byte) {
+                self.reset();
+                return Err(Error::Overflow);
+            }
+            self.raw |= (low_bits_of_byte(byte) as u64) << (7 * self.len);
+            self.len += 1;
+
+            if byte & CONTINUATION_BIT == 0 {
+                let value = T::finish(self.raw, self.len, byte);
+                let len = self.len;
+                self.reset();
+                return Ok(DecodeProgress::Complete {
+                    value,
+                    len,
+                    consumed: i + 1,
+                });
+            }
+        }
+        Ok(DecodeProgress::Incomplete)
+    }
+
+    /// The number of bytes of an unfinished value seen so far. This is zero
+    /// between values, so a nonzero count at the end of a stream means the
+    /// last value was truncated.
+    pub fn pending(&self) -> usize {
+        self.len
+    }
+
+    /// Discard any partial value.
+    pub fn reset(&mut self) {
+        self.raw = 0;
+        self.len = 0;
+    }
+}
+
+impl<T: Integer> Default for Decoder<T> {
+    fn default() -> Self {
+        Decoder::new()
+    }
+}
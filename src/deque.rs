@@ -0,0 +1,88 @@
+//! A `VecDeque<u8>` holds its bytes in two slices once it has wrapped around
+//! its ring buffer. [`DequeReader`](struct.DequeReader.html) reads across
+//! that split, peeks included, without first making the bytes contiguous.
+
+use super::buf::Source;
+use super::read::Leb128Read;
+use std::borrow::BorrowMut;
+use std::collections::VecDeque;
+use std::io::IoSlice;
+
+/// A wrapper giving a `VecDeque<u8>` the `Leb128Read` methods, consuming from
+/// its front.
+///
+/// ```
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// use nt_leb128::deque::DequeReader;
+/// use nt_leb128::Leb128Read;
+/// use std::collections::VecDeque;
+///
+/// let mut deque = VecDeque::new();
+/// deque.extend(&[0xe5, 0x8e, 0x26, 0x7e]);
+///
+/// let mut reader = DequeReader::new(&mut deque);
+/// assert_eq!(reader.peek_unsigned().unwrap(), (624485, 3));
+/// assert_eq!(reader.read_unsigned().unwrap(), (624485, 3));
+/// assert_eq!(reader.read_signed().unwrap(), (-2, 1));
+/// assert!(deque.is_empty());
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DequeReader<D> {
+    inner: D,
+}
+
+impl<D> DequeReader<D> {
+    /// Wrap `inner`. Pass `&mut deque` to keep using `deque` afterwards.
+    pub fn new(inner: D) -> Self {
+        DequeReader { inner }
+    }
+
+    /// Get a reference to the inner deque.
+    pub fn get_ref(&self) -> &D {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner deque.
+    pub fn get_mut(&mut self) -> &mut D {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner deque.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D: BorrowMut<VecDeque<u8>>> Source for DequeReader<D> {
+    fn chunk(&self) -> &[u8] {
+        match self.inner.borrow().as_slices() {
+            (&[], back) => back,
+            (front, _) => front,
+        }
+    }
+
+    fn chunks_vectored<'b>(&'b self, dst: &mut [IoSlice<'b>]) -> usize {
+        let (front, back) = self.inner.borrow().as_slices();
+        let mut n = 0;
+        for slice in [front, back] {
+            if n < dst.len() && !slice.is_empty() {
+                dst[n] = IoSlice::new(slice);
+                n += 1;
+            }
+        }
+        n
+    }
+
+    fn remaining(&self) -> usize {
+        self.inner.borrow().len()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.inner.borrow_mut().drain(..cnt);
+    }
+}
+
+impl<D: BorrowMut<VecDeque<u8>>> Leb128Read for DequeReader<D> {
+    forward_read!();
+}
@@ -0,0 +1,143 @@
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::{decode_unsigned_const, encode_unsigned_const};
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::{decode_signed_const, encode_signed_const};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Deref;
+
+macro_rules! encoded_type {
+    ($name:ident, $int:ty, $encode:ident, $decode:ident, $doc:expr, $example:expr) => {
+        #[doc = $doc]
+        ///
+        /// The encoding is held inline, so no allocation is needed to get
+        /// at "the bytes of this one value".
+        ///
+        /// ```
+        #[doc = $example]
+        /// ```
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name {
+            bytes: [u8; 10],
+            len: u8,
+        }
+
+        impl $name {
+            /// Encode `val`.
+            pub const fn new(val: $int) -> Self {
+                let (bytes, len) = $encode::<10>(val);
+                $name { bytes, len: len as u8 }
+            }
+
+            /// The encoded bytes.
+            pub fn as_slice(&self) -> &[u8] {
+                &self.bytes[..self.len as usize]
+            }
+
+            /// The number of encoded bytes, between 1 and 10.
+            #[allow(clippy::len_without_is_empty)]
+            pub const fn len(&self) -> usize {
+                self.len as usize
+            }
+
+            /// The encoded value.
+            pub const fn value(&self) -> $int {
+                match $decode(&self.bytes, 0) {
+                    Ok((val, _)) => val,
+                    Err(_) => panic!("always holds a valid encoding"),
+                }
+            }
+        }
+
+        impl From<$int> for $name {
+            fn from(val: $int) -> Self {
+                $name::new(val)
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(encoded: $name) -> Self {
+                encoded.value()
+            }
+        }
+
+        impl Deref for $name {
+            type Target = [u8];
+
+            fn deref(&self) -> &[u8] {
+                self.as_slice()
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                self.as_slice()
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                f.debug_struct(stringify!($name))
+                    .field("value", &self.value())
+                    .field("bytes", &self.as_slice())
+                    .finish()
+            }
+        }
+
+        impl PartialEq<$int> for $name {
+            fn eq(&self, other: &$int) -> bool {
+                self.value() == *other
+            }
+        }
+
+        impl PartialEq<$name> for $int {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.value()
+            }
+        }
+
+        impl PartialOrd<$int> for $name {
+            fn partial_cmp(&self, other: &$int) -> Option<Ordering> {
+                self.value().partial_cmp(other)
+            }
+        }
+
+        impl PartialOrd<$name> for $int {
+            fn partial_cmp(&self, other: &$name) -> Option<Ordering> {
+                self.partial_cmp(&other.value())
+            }
+        }
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $name {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                <$int>::arbitrary(u).map($name::new)
+            }
+
+            fn size_hint(depth: usize) -> (usize, Option<usize>) {
+                <$int as arbitrary::Arbitrary>::size_hint(depth)
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+encoded_type!(EncodedU64,
+              u64,
+              encode_unsigned_const,
+              decode_unsigned_const,
+              "The unsigned LEB128 encoding of a single `u64`.",
+              "let encoded = nt_leb128::EncodedU64::from(624485);
+assert_eq!(&*encoded, &[0xe5, 0x8e, 0x26]);
+assert_eq!(encoded.len(), 3);
+assert_eq!(encoded, 624485);");
+
+#[cfg(not(feature = "unsigned-only"))]
+encoded_type!(EncodedI64,
+              i64,
+              encode_signed_const,
+              decode_signed_const,
+              "The signed LEB128 encoding of a single `i64`.",
+              "let encoded = nt_leb128::EncodedI64::from(-129);
+assert_eq!(encoded.as_slice(), &[0xff, 0x7e]);
+assert!(encoded < 0);");
@@ -0,0 +1,85 @@
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::encode_unsigned_const;
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::encode_signed_const;
+use super::read::MAX_LEN;
+
+/// The outcome of letting an [`Encoder`](struct.Encoder.html) fill an output
+/// window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodeProgress {
+    /// How many bytes were written to the window, from its start.
+    pub written: usize,
+    /// Whether the encoding has now been written in full.
+    pub complete: bool,
+}
+
+/// An encoder that writes a single value into output windows of whatever size
+/// is available, resuming where it left off when given the next one.
+///
+/// This suits fixed-size packets and ring buffers, where a value may have to
+/// straddle the boundary between two windows.
+///
+/// ```
+/// # #[cfg(not(feature = "signed-only"))] {
+/// use nt_leb128::encoder::{EncodeProgress, Encoder};
+///
+/// let mut encoder = Encoder::unsigned(624485);
+/// let mut first = [0; 2];
+/// let mut second = [0; 2];
+/// assert_eq!(encoder.emit(&mut first), EncodeProgress { written: 2, complete: false });
+/// assert_eq!(encoder.emit(&mut second), EncodeProgress { written: 1, complete: true });
+/// assert_eq!((first, second[0]), ([0xe5, 0x8e], 0x26));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    bytes: [u8; MAX_LEN],
+    len: usize,
+    written: usize,
+}
+
+impl Encoder {
+    /// Create an encoder for `val` as unsigned LEB128.
+    #[cfg(not(feature = "signed-only"))]
+    pub fn unsigned(val: u64) -> Self {
+        let (bytes, len) = encode_unsigned_const(val);
+        Encoder { bytes, len, written: 0 }
+    }
+
+    /// Create an encoder for `val` as signed LEB128.
+    #[cfg(not(feature = "unsigned-only"))]
+    pub fn signed(val: i64) -> Self {
+        let (bytes, len) = encode_signed_const(val);
+        Encoder { bytes, len, written: 0 }
+    }
+
+    /// Write as much of the rest of the encoding as fits at the start of
+    /// `out`. Once the encoding is complete, further calls write nothing.
+    pub fn emit(&mut self, out: &mut [u8]) -> EncodeProgress {
+        let rest = &self.bytes[self.written..self.len];
+        let n = rest.len().min(out.len());
+        out[..n].copy_from_slice(&rest[..n]);
+        self.written += n;
+        EncodeProgress {
+            written: n,
+            complete: self.is_complete(),
+        }
+    }
+
+    /// The number of bytes of the encoding still to be written.
+    pub fn remaining(&self) -> usize {
+        self.len - self.written
+    }
+
+    /// Whether the whole encoding has been written.
+    pub fn is_complete(&self) -> bool {
+        self.written == self.len
+    }
+
+    /// The total length of the encoding, between 1 and 10.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
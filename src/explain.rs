@@ -0,0 +1,157 @@
+use super::{CONTINUATION_BIT, SIGN_BIT, low_bits_of_byte};
+use std::fmt;
+
+/// The breakdown of a single byte of an LEB128 encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExplainedByte {
+    /// The offset of this byte from the start of the encoding.
+    pub offset: usize,
+    /// The raw byte.
+    pub byte: u8,
+    /// Whether the continuation bit is set, i.e. more bytes follow.
+    pub continuation: bool,
+    /// The seven payload bits of the byte.
+    pub payload: u8,
+    /// The shift applied to `payload` before it is or'd into the value.
+    pub shift: u32,
+    /// The unsigned value decoded so far, including this byte.
+    pub running: u64,
+}
+
+/// A byte by byte account of how an LEB128 encoding decodes.
+///
+/// Produced by [`explain`](fn.explain.html). The `Display` implementation
+/// prints one line per byte followed by the unsigned and signed
+/// interpretations of the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// Every byte examined, in order.
+    pub bytes: Vec<ExplainedByte>,
+    /// Whether a byte without the continuation bit was found.
+    pub complete: bool,
+}
+
+impl Explanation {
+    /// The number of bytes the encoding occupies (or the number examined, if
+    /// the encoding is incomplete).
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether no bytes were examined at all.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The value interpreted as unsigned LEB128, if the encoding is complete
+    /// and fits in a `u64`.
+    pub fn unsigned(&self) -> Option<u64> {
+        let last = self.last_in_range()?;
+        if last.shift == 63 && last.byte != 0x00 && last.byte != 0x01 {
+            return None;
+        }
+        Some(last.running)
+    }
+
+    /// The value interpreted as signed LEB128, if the encoding is complete
+    /// and fits in an `i64`.
+    pub fn signed(&self) -> Option<i64> {
+        let last = self.last_in_range()?;
+        if last.shift == 63 && last.byte != 0x00 && last.byte != 0x7f {
+            return None;
+        }
+        let mut result = last.running as i64;
+        let shift = last.shift + 7;
+        if shift < 64 && (SIGN_BIT & last.byte) == SIGN_BIT {
+            // Sign extend the result.
+            result |= !0 << shift;
+        }
+        Some(result)
+    }
+
+    fn last_in_range(&self) -> Option<&ExplainedByte> {
+        if !self.complete {
+            return None;
+        }
+        self.bytes.last().filter(|b| b.shift <= 63)
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        writeln!(f, "offset  byte  bits      cont  payload  shift  running")?;
+        for b in &self.bytes {
+            writeln!(f,
+                     "{:>6}  0x{:02x}  {}_{:07b}  {:>4}  {:>7}  {:>5}  {}",
+                     b.offset,
+                     b.byte,
+                     b.byte >> 7,
+                     b.payload,
+                     b.continuation as u8,
+                     b.payload,
+                     b.shift,
+                     b.running)?;
+        }
+        if !self.complete {
+            writeln!(f, "incomplete: no terminating byte")?;
+        }
+        match self.unsigned() {
+            Some(v) => writeln!(f, "unsigned: {}", v)?,
+            None => writeln!(f, "unsigned: -")?,
+        }
+        match self.signed() {
+            Some(v) => write!(f, "signed: {}", v),
+            None => write!(f, "signed: -"),
+        }
+    }
+}
+
+/// Decode the LEB128 value at the start of `bytes` and report, for every byte,
+/// the continuation bit, the payload bits, the cumulative shift and the
+/// running value.
+///
+/// Decoding stops at the first byte without the continuation bit; any trailing
+/// bytes are ignored. Unlike the readers, this never fails: a truncated
+/// encoding is reported through `complete`, and a value too large for 64 bits
+/// through `unsigned()` or `signed()` returning `None`.
+///
+/// ```
+/// let explanation = nt_leb128::explain(&[0xe5, 0x8e, 0x26]);
+/// assert_eq!(explanation.unsigned(), Some(624485));
+/// println!("{}", explanation);
+/// ```
+pub fn explain<B: AsRef<[u8]>>(bytes: B) -> Explanation {
+    let bytes = bytes.as_ref();
+    let mut explained = Vec::new();
+    let mut running = 0u64;
+    let mut shift = 0u32;
+    let mut complete = false;
+
+    for (offset, &byte) in bytes.iter().enumerate() {
+        let payload = low_bits_of_byte(byte);
+        if shift < 64 {
+            running |= (payload as u64) << shift;
+        }
+
+        let continuation = byte & CONTINUATION_BIT != 0;
+        explained.push(ExplainedByte {
+            offset,
+            byte,
+            continuation,
+            payload,
+            shift,
+            running,
+        });
+
+        if !continuation {
+            complete = true;
+            break;
+        }
+        shift += 7;
+    }
+
+    Explanation {
+        bytes: explained,
+        complete,
+    }
+}
@@ -0,0 +1,169 @@
+//! Every function reports its outcome through one of the `LEB128_*` status
+//! codes and writes its results through out-parameters, which may be null
+//! when the caller isn't interested in them. Buffers are passed as a pointer
+//! and a length; a null pointer is only accepted when the length is zero.
+//!
+//! To build a shared library and its header:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --lib --crate-type cdylib
+//! cbindgen --config cbindgen.toml --output nt_leb128.h
+//! ```
+
+use super::{encode_signed_const, encode_unsigned_const, encoded_len_signed, encoded_len_unsigned};
+use super::read::{self, decode_signed, decode_unsigned, MAX_LEN};
+use std::slice;
+
+/// The call succeeded.
+pub const LEB128_OK: i32 = 0;
+/// A required pointer was null.
+pub const LEB128_ERR_NULL: i32 = -1;
+/// The input ended before the end of the encoding.
+pub const LEB128_ERR_EOF: i32 = -2;
+/// The encoded value doesn't fit in 64 bits.
+pub const LEB128_ERR_OVERFLOW: i32 = -3;
+/// The output buffer is too small for the encoding.
+pub const LEB128_ERR_BUFFER_TOO_SMALL: i32 = -4;
+
+fn status(err: &read::Error) -> i32 {
+    match *err {
+        read::Error::Overflow => LEB128_ERR_OVERFLOW,
+        _ => LEB128_ERR_EOF,
+    }
+}
+
+unsafe fn input<'a>(buf: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if buf.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(buf, len))
+    }
+}
+
+unsafe fn store<T>(out: *mut T, val: T) {
+    if !out.is_null() {
+        *out = val;
+    }
+}
+
+unsafe fn emit(buf: *mut u8, cap: usize, out_len: *mut usize, encoded: ([u8; MAX_LEN], usize))
+               -> i32 {
+    let (scratch, len) = encoded;
+    store(out_len, len);
+    if len > cap {
+        return LEB128_ERR_BUFFER_TOO_SMALL;
+    }
+    if buf.is_null() {
+        return LEB128_ERR_NULL;
+    }
+    slice::from_raw_parts_mut(buf, cap)[..len].copy_from_slice(&scratch[..len]);
+    LEB128_OK
+}
+
+/// Decode the unsigned LEB128 value at the start of the `len` bytes at `buf`,
+/// storing it in `*out_value` and the number of bytes it occupied in
+/// `*out_len`.
+///
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes, and each out-parameter must
+/// be null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn leb128_read_u64(buf: *const u8,
+                                         len: usize,
+                                         out_value: *mut u64,
+                                         out_len: *mut usize)
+                                         -> i32 {
+    let bytes = match input(buf, len) {
+        Some(bytes) => bytes,
+        None => return LEB128_ERR_NULL,
+    };
+    match decode_unsigned(bytes.iter().cloned()) {
+        Ok((val, read)) => {
+            store(out_value, val);
+            store(out_len, read);
+            LEB128_OK
+        }
+        Err(e) => status(&e),
+    }
+}
+
+/// Decode the signed LEB128 value at the start of the `len` bytes at `buf`,
+/// storing it in `*out_value` and the number of bytes it occupied in
+/// `*out_len`.
+///
+/// # Safety
+///
+/// `buf` must be valid for reads of `len` bytes, and each out-parameter must
+/// be null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn leb128_read_i64(buf: *const u8,
+                                         len: usize,
+                                         out_value: *mut i64,
+                                         out_len: *mut usize)
+                                         -> i32 {
+    let bytes = match input(buf, len) {
+        Some(bytes) => bytes,
+        None => return LEB128_ERR_NULL,
+    };
+    match decode_signed(bytes.iter().cloned()) {
+        Ok((val, read)) => {
+            store(out_value, val);
+            store(out_len, read);
+            LEB128_OK
+        }
+        Err(e) => status(&e),
+    }
+}
+
+/// Encode `val` as unsigned LEB128 into the `cap` bytes at `buf`, storing
+/// the number of bytes written in `*out_len`.
+///
+/// If the encoding doesn't fit, nothing is written, `*out_len` still
+/// receives the length needed and `LEB128_ERR_BUFFER_TOO_SMALL` is returned.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `cap` bytes, and `out_len` must be null
+/// or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn leb128_write_u64(val: u64,
+                                          buf: *mut u8,
+                                          cap: usize,
+                                          out_len: *mut usize)
+                                          -> i32 {
+    emit(buf, cap, out_len, encode_unsigned_const::<MAX_LEN>(val))
+}
+
+/// Encode `val` as signed LEB128 into the `cap` bytes at `buf`, storing the
+/// number of bytes written in `*out_len`.
+///
+/// If the encoding doesn't fit, nothing is written, `*out_len` still
+/// receives the length needed and `LEB128_ERR_BUFFER_TOO_SMALL` is returned.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `cap` bytes, and `out_len` must be null
+/// or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn leb128_write_i64(val: i64,
+                                          buf: *mut u8,
+                                          cap: usize,
+                                          out_len: *mut usize)
+                                          -> i32 {
+    emit(buf, cap, out_len, encode_signed_const::<MAX_LEN>(val))
+}
+
+/// The number of bytes needed to encode `val` as unsigned LEB128.
+#[no_mangle]
+pub extern "C" fn leb128_encoded_len_u64(val: u64) -> usize {
+    encoded_len_unsigned(val)
+}
+
+/// The number of bytes needed to encode `val` as signed LEB128.
+#[no_mangle]
+pub extern "C" fn leb128_encoded_len_i64(val: i64) -> usize {
+    encoded_len_signed(val)
+}
@@ -0,0 +1,263 @@
+use super::config::Config;
+use super::read::{decode_unsigned, not_enough_data, Error, Leb128Read, MAX_LEN};
+use super::scratch;
+use super::write::{self, Leb128Write};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// A writer of frames: payloads prefixed with their length as unsigned
+/// LEB128.
+///
+/// ```
+/// use nt_leb128::frame::{FrameReader, FrameWriter};
+///
+/// let mut writer = FrameWriter::new(Vec::new(), 1024);
+/// writer.write_frame(b"hello").unwrap();
+/// writer.write_frame(b"").unwrap();
+/// let wire = writer.into_inner();
+/// assert_eq!(&wire[..], b"\x05hello\x00");
+///
+/// let mut reader = FrameReader::new(1024);
+/// let mut readable = &wire[..];
+/// assert_eq!(&reader.read_frame(&mut readable).unwrap().unwrap()[..], b"hello");
+/// assert_eq!(&reader.read_frame(&mut readable).unwrap().unwrap()[..], b"");
+/// assert_eq!(reader.read_frame(&mut readable).unwrap(), None);
+/// ```
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    inner: W,
+    max_frame: usize,
+}
+
+impl<W: BufMut> FrameWriter<W> {
+    /// Wrap `inner`, refusing payloads longer than `max_frame` bytes.
+    pub fn new(inner: W, max_frame: usize) -> Self {
+        FrameWriter { inner, max_frame }
+    }
+
+    /// Write `payload` as one frame, returning the number of bytes written
+    /// including the length prefix.
+    ///
+    /// Fails with `write::Error::LengthExceeded`, writing nothing, if the
+    /// payload is longer than the maximum frame size.
+    pub fn write_frame(&mut self, payload: &[u8]) -> Result<usize, write::Error> {
+        if payload.len() > self.max_frame {
+            return Err(write::Error::LengthExceeded {
+                len: payload.len(),
+                max: self.max_frame,
+            });
+        }
+        self.inner.write_bytes(payload)
+    }
+
+    /// The largest payload this writer accepts.
+    pub fn max_frame(&self) -> usize {
+        self.max_frame
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// A reader of frames written by [`FrameWriter`](struct.FrameWriter.html).
+///
+/// Input may arrive in pieces: whatever part of a frame is available is kept
+/// until the rest arrives, either from a `Buf` passed to
+/// [`read_frame`](#method.read_frame) or from slices given to
+/// [`push`](#method.push).
+#[derive(Debug)]
+pub struct FrameReader {
+    buffered: BytesMut,
+    max_frame: usize,
+}
+
+impl FrameReader {
+    /// Create a reader that rejects frames longer than `max_frame` bytes.
+    pub fn new(max_frame: usize) -> Self {
+        FrameReader {
+            buffered: BytesMut::new(),
+            max_frame,
+        }
+    }
+
+    /// Read the next frame, taking only as many bytes from `src` as it needs
+    /// (and at most ten bytes beyond the end of the frame while looking for
+    /// a length prefix).
+    ///
+    /// Returns `Ok(None)` if `src` ran out before the frame was complete; the
+    /// partial frame is kept, and reading resumes with the next call.
+    pub fn read_frame<B: Buf>(&mut self, src: &mut B) -> Result<Option<Bytes>, Error> {
+        loop {
+            if let Some(frame) = self.next_frame()? {
+                return Ok(Some(frame));
+            }
+            if !src.has_remaining() {
+                return Ok(None);
+            }
+            let want = match self.header()? {
+                Some((prefix, payload)) => prefix + payload - self.buffered.len(),
+                None => MAX_LEN - self.buffered.len(),
+            };
+            let chunk = src.bytes();
+            let n = want.min(chunk.len());
+            self.buffered.extend_from_slice(&chunk[..n]);
+            src.advance(n);
+        }
+    }
+
+    /// Append `bytes` to the buffered input.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffered.extend_from_slice(bytes);
+    }
+
+    /// Take the next complete frame from the buffered input, if there is one.
+    ///
+    /// Fails with `Error::LengthExceeded` as soon as a length prefix larger
+    /// than the maximum frame size has been buffered, before its payload is.
+    pub fn next_frame(&mut self) -> Result<Option<Bytes>, Error> {
+        match self.header()? {
+            Some((prefix, payload)) if prefix + payload <= self.buffered.len() => {
+                self.buffered.advance(prefix);
+                Ok(Some(self.buffered.split_to(payload).freeze()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The number of bytes buffered towards frames that haven't been
+    /// returned yet.
+    pub fn buffered(&self) -> usize {
+        self.buffered.len()
+    }
+
+    /// The largest payload this reader accepts.
+    pub fn max_frame(&self) -> usize {
+        self.max_frame
+    }
+
+    /// The lengths of the next frame's prefix and payload, once the prefix
+    /// is buffered.
+    fn header(&self) -> Result<Option<(usize, usize)>, Error> {
+        match decode_unsigned(self.buffered.iter().copied()) {
+            Ok((len, prefix)) => {
+                if len > self.max_frame as u64 {
+                    return Err(Error::LengthExceeded {
+                        len,
+                        max: self.max_frame,
+                    });
+                }
+                Ok(Some((prefix, len as usize)))
+            }
+            Err(Error::Incomplete) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Write `msg` in the varint-delimited form of protobuf's `writeDelimitedTo`,
+/// returning the number of bytes written including the length prefix.
+pub fn write_delimited<W: BufMut>(buf: &mut W, msg: &[u8]) -> Result<usize, write::Error> {
+    buf.write_bytes(msg)
+}
+
+/// Read the next message written in protobuf's `writeDelimitedTo` form,
+/// returning its payload, or `None` if `buf` is already at its end (as
+/// `parseDelimitedFrom` returns `null`).
+///
+/// Fails with `Error::LengthExceeded`, before reading the payload, if the
+/// message is longer than `max_len`, and with `Error::Incomplete` if the
+/// stream ends partway through a message.
+pub fn read_delimited<B: Buf>(buf: &mut B, max_len: usize) -> Result<Option<Bytes>, Error> {
+    if !buf.has_remaining() {
+        return Ok(None);
+    }
+    let (len, _) = buf.read_unsigned_with(&Config::new())?;
+    if len > max_len as u64 {
+        return Err(Error::LengthExceeded { len, max: max_len });
+    }
+    let len = len as usize;
+    if len > buf.remaining() {
+        return Err(not_enough_data());
+    }
+    let mut payload = scratch::zeroed_bytes(len);
+    buf.copy_to_slice(&mut payload);
+    Ok(Some(payload.freeze()))
+}
+
+/// An iterator over the payloads of a stream of varint-delimited protobuf
+/// messages held in `Bytes`, sharing its storage rather than copying.
+///
+/// Created by [`delimited_messages`](fn.delimited_messages.html). After an
+/// error, it yields nothing more.
+#[derive(Debug, Clone)]
+pub struct DelimitedMessages {
+    data: Bytes,
+    max_len: usize,
+    failed: bool,
+}
+
+/// Iterate over the messages in `data`, a stream written with protobuf's
+/// `writeDelimitedTo`, such as a log file produced by a Java service.
+///
+/// ```
+/// use bytes::Bytes;
+/// use nt_leb128::frame::delimited_messages;
+///
+/// let data = Bytes::from_static(b"\x02hi\x00\x03abc");
+/// let messages: Result<Vec<_>, _> = delimited_messages(data, 1 << 20).collect();
+/// assert_eq!(messages.unwrap(), vec!["hi", "", "abc"]);
+/// ```
+pub fn delimited_messages(data: Bytes, max_len: usize) -> DelimitedMessages {
+    DelimitedMessages {
+        data,
+        max_len,
+        failed: false,
+    }
+}
+
+impl DelimitedMessages {
+    /// The bytes not yet consumed.
+    pub fn remaining(&self) -> &Bytes {
+        &self.data
+    }
+
+    fn next_message(&mut self) -> Result<Bytes, Error> {
+        let (len, prefix) = decode_unsigned(self.data.iter().copied())?;
+        if len > self.max_len as u64 {
+            return Err(Error::LengthExceeded {
+                len,
+                max: self.max_len,
+            });
+        }
+        let len = len as usize;
+        if len > self.data.len() - prefix {
+            return Err(not_enough_data());
+        }
+        self.data.advance(prefix);
+        Ok(self.data.split_to(len))
+    }
+}
+
+impl Iterator for DelimitedMessages {
+    type Item = Result<Bytes, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.data.is_empty() {
+            return None;
+        }
+        let result = self.next_message();
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
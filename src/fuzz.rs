@@ -0,0 +1,141 @@
+//! Uniformly random bytes almost never form a long encoding, so a fuzzer fed
+//! them spends its time on one and two byte values. [`EdgeCase`] instead picks
+//! one of the shapes the decoder treats specially and builds an input of that
+//! shape:
+//!
+//! ```
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use nt_leb128::fuzz::EdgeCase;
+//!
+//! let mut u = Unstructured::new(&[0x03, 0x01, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa]);
+//! let case = EdgeCase::arbitrary(&mut u).unwrap();
+//! println!("{:?} {:02x?}", case.edge, case.bytes);
+//! ```
+
+use super::{encoded_len_signed, encoded_len_unsigned};
+use super::read::MAX_LEN;
+use super::slice::pad_encoding;
+use super::write::{put_signed, put_unsigned};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+/// The shape of an input produced by [`edge_case`](fn.edge_case.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// The shortest encoding of a value.
+    Canonical,
+    /// A value padded with redundant continuation bytes, still at most ten
+    /// bytes long.
+    Overlong,
+    /// An encoding cut off before its final byte.
+    Truncated,
+    /// A ten byte encoding of a value that needs all 64 bits.
+    Maximal,
+    /// A ten byte encoding whose final byte carries bits beyond 64.
+    Overflowing,
+}
+
+impl Edge {
+    /// Every shape, in declaration order.
+    pub const ALL: [Edge; 5] = [Edge::Canonical,
+                                Edge::Overlong,
+                                Edge::Truncated,
+                                Edge::Maximal,
+                                Edge::Overflowing];
+
+    /// Whether a decoder should accept inputs of this shape (ignoring
+    /// canonicality checks).
+    pub fn is_valid(self) -> bool {
+        match self {
+            Edge::Canonical | Edge::Overlong | Edge::Maximal => true,
+            Edge::Truncated | Edge::Overflowing => false,
+        }
+    }
+}
+
+impl<'a> Arbitrary<'a> for Edge {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&Edge::ALL).copied()
+    }
+}
+
+/// An input of a given shape, along with the shape and signedness it was
+/// generated for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EdgeCase {
+    /// The shape of `bytes`.
+    pub edge: Edge,
+    /// Whether `bytes` is meant for the signed decoder.
+    pub signed: bool,
+    /// The input itself.
+    pub bytes: Vec<u8>,
+}
+
+impl<'a> Arbitrary<'a> for EdgeCase {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let edge = Edge::arbitrary(u)?;
+        let signed = bool::arbitrary(u)?;
+        let bytes = edge_case(u, edge, signed)?;
+        Ok(EdgeCase { edge, signed, bytes })
+    }
+}
+
+/// Build an input of shape `edge` around a value drawn from `u`, for the
+/// signed decoder if `signed` and the unsigned one otherwise.
+pub fn edge_case(u: &mut Unstructured, edge: Edge, signed: bool) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(MAX_LEN);
+    if signed {
+        let mut val = i64::arbitrary(u)?;
+        match edge {
+            Edge::Overlong => {
+                while encoded_len_signed(val) == MAX_LEN {
+                    val >>= 7;
+                }
+            }
+            Edge::Maximal | Edge::Overflowing => {
+                // Bit 62 differing from the sign bit forces all ten bytes.
+                val = if val < 0 { val & !(1 << 62) } else { val | (1 << 62) };
+            }
+            _ => {}
+        }
+        put_signed(&mut bytes, val);
+        if edge == Edge::Overlong {
+            let pad = u.int_in_range(1..=MAX_LEN - bytes.len())?;
+            pad_encoding(&mut bytes, pad, val < 0);
+        }
+    } else {
+        let mut val = u64::arbitrary(u)?;
+        match edge {
+            Edge::Overlong => {
+                while encoded_len_unsigned(val) == MAX_LEN {
+                    val >>= 7;
+                }
+            }
+            Edge::Maximal | Edge::Overflowing => val |= 1 << 63,
+            _ => {}
+        }
+        put_unsigned(&mut bytes, val);
+        if edge == Edge::Overlong {
+            let pad = u.int_in_range(1..=MAX_LEN - bytes.len())?;
+            pad_encoding(&mut bytes, pad, false);
+        }
+    }
+
+    match edge {
+        Edge::Truncated => {
+            let keep = u.int_in_range(0..=bytes.len() - 1)?;
+            bytes.truncate(keep);
+        }
+        Edge::Overflowing => {
+            // The final byte may only hold the 64th bit (and, when signed,
+            // its sign extension); anything else overflows.
+            let last = if signed {
+                u.int_in_range(0x01..=0x7e)?
+            } else {
+                u.int_in_range(0x02..=0x7f)?
+            };
+            bytes[MAX_LEN - 1] = last;
+        }
+        _ => {}
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,130 @@
+//! An [`AdjacencyList`](struct.AdjacencyList.html) is a graph in compressed
+//! sparse row form: one buffer holding every node's neighbors, sorted, as the
+//! unsigned LEB128 difference from the previous neighbor (the first from
+//! zero), and a table of where each node's neighbors start.
+//!
+//! ```
+//! use nt_leb128::graph::AdjacencyList;
+//!
+//! let graph = AdjacencyList::from_edges(vec![(0, 1_000_000), (0, 3), (2, 0), (0, 1_000_001)]);
+//! assert_eq!(graph.node_count(), 1_000_002);
+//! assert_eq!(graph.edge_count(), 4);
+//! assert_eq!(graph.neighbors(0).collect::<Vec<_>>(), [3, 1_000_000, 1_000_001]);
+//! assert_eq!(graph.neighbors(1).count(), 0);
+//! assert_eq!(graph.neighbors(2).collect::<Vec<_>>(), [0]);
+//! ```
+
+use super::slice;
+use super::write::put_unsigned;
+use std::iter::FromIterator;
+
+/// The most nodes a graph can have. The table of where each node's neighbors
+/// start has an entry for every node up to the largest, however few edges
+/// there are, so node ids are kept to those of a `u32`.
+pub const MAX_NODES: u64 = u32::MAX as u64;
+
+/// A directed graph storing each node's neighbors as delta encoded LEB128.
+///
+/// Nodes are numbered from zero up to the largest that appears in an edge,
+/// which must be below `MAX_NODES`.
+/// Parallel edges are kept, and a node's neighbors are iterated in ascending
+/// order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdjacencyList {
+    offsets: Vec<usize>,
+    data: Vec<u8>,
+    edge_count: usize,
+}
+
+impl AdjacencyList {
+    /// Build a graph from `edges`, given as `(from, to)` pairs in any order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a node in `edges` is `MAX_NODES` or more.
+    pub fn from_edges<I: IntoIterator<Item = (u64, u64)>>(edges: I) -> Self {
+        let mut edges: Vec<_> = edges.into_iter().collect();
+        edges.sort_unstable();
+        let largest = edges.iter().map(|&(from, to)| from.max(to)).max();
+        let node_count = largest.map_or(Some(0), |node| node.checked_add(1))
+            .filter(|&count| count <= MAX_NODES)
+            .expect("a graph has fewer than u32::MAX nodes");
+
+        let mut graph = AdjacencyList {
+            offsets: Vec::with_capacity(node_count as usize + 1),
+            data: Vec::new(),
+            edge_count: edges.len(),
+        };
+        let mut edges = edges.into_iter().peekable();
+        for node in 0..node_count {
+            graph.offsets.push(graph.data.len());
+            let mut prev = 0;
+            while let Some((_, to)) = edges.next_if(|&(from, _)| from == node) {
+                put_unsigned(&mut graph.data, to - prev);
+                prev = to;
+            }
+        }
+        graph.offsets.push(graph.data.len());
+        graph
+    }
+
+    /// The number of nodes, one more than the largest node in any edge.
+    pub fn node_count(&self) -> u64 {
+        self.offsets.len().saturating_sub(1) as u64
+    }
+
+    /// The number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// The neighbors of `node` in ascending order; none if `node` isn't in
+    /// the graph.
+    pub fn neighbors(&self, node: u64) -> Neighbors<'_> {
+        let node = node as usize;
+        let bytes = match self.offsets.get(node..node.saturating_add(2)) {
+            Some(&[start, end]) => &self.data[start..end],
+            _ => &[],
+        };
+        Neighbors { bytes, prev: 0 }
+    }
+
+    /// The number of neighbors of `node`, counted without decoding them.
+    pub fn degree(&self, node: u64) -> usize {
+        slice::count_values(self.neighbors(node).bytes).expect("encoded by from_edges")
+    }
+
+    /// The bytes of heap the graph occupies.
+    pub fn heap_size(&self) -> usize {
+        self.offsets.capacity() * std::mem::size_of::<usize>() + self.data.capacity()
+    }
+}
+
+impl FromIterator<(u64, u64)> for AdjacencyList {
+    fn from_iter<I: IntoIterator<Item = (u64, u64)>>(edges: I) -> Self {
+        AdjacencyList::from_edges(edges)
+    }
+}
+
+/// An iterator over the neighbors of a node, decoding them as it goes.
+///
+/// Created by [`AdjacencyList::neighbors`](struct.AdjacencyList.html#method.neighbors).
+#[derive(Debug, Clone)]
+pub struct Neighbors<'a> {
+    bytes: &'a [u8],
+    prev: u64,
+}
+
+impl<'a> Iterator for Neighbors<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let (delta, rest) = slice::decode_unsigned(self.bytes).expect("encoded by from_edges");
+        self.bytes = rest;
+        self.prev += delta;
+        Some(self.prev)
+    }
+}
@@ -0,0 +1,103 @@
+//! Encodings are written as two lowercase hex digits per byte with no
+//! separators, and read back with or without whitespace or commas between
+//! bytes and an optional `0x` prefix, the forms the `leb128` command line
+//! tool prints and accepts.
+//!
+//! ```
+//! # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+//! use nt_leb128::hex;
+//!
+//! assert_eq!(hex::to_hex(624485), "e58e26");
+//! assert_eq!(hex::from_hex("e5 8e 26").unwrap(), (624485, 3));
+//! assert_eq!(hex::from_hex_signed(&hex::to_hex_signed(-123456)).unwrap(), (-123456, 3));
+//! # }
+//! ```
+
+use super::read::{Error, MAX_LEN};
+#[cfg(not(feature = "signed-only"))]
+use super::read::decode_unsigned;
+#[cfg(not(feature = "unsigned-only"))]
+use super::read::decode_signed;
+use super::io_error;
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::encode_unsigned_const;
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::encode_signed_const;
+use std::fmt::Write;
+use std::io;
+
+/// The unsigned LEB128 encoding of `val`, in hex.
+#[cfg(not(feature = "signed-only"))]
+pub fn to_hex(val: u64) -> String {
+    let (bytes, len) = encode_unsigned_const::<MAX_LEN>(val);
+    format_hex(&bytes[..len])
+}
+
+/// The signed LEB128 encoding of `val`, in hex.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn to_hex_signed(val: i64) -> String {
+    let (bytes, len) = encode_signed_const::<MAX_LEN>(val);
+    format_hex(&bytes[..len])
+}
+
+/// Decode the unsigned LEB128 encoding written in hex in `s`, returning the
+/// number and the length of its encoding in bytes.
+///
+/// Fails with an `InvalidData` error if `s` isn't hex bytes or holds more
+/// than one encoding, and as `read::decode_unsigned` does if the encoding
+/// is malformed.
+#[cfg(not(feature = "signed-only"))]
+pub fn from_hex(s: &str) -> Result<(u64, usize), Error> {
+    let bytes = parse_hex(s)?;
+    whole(&bytes, decode_unsigned(bytes.iter().copied())?)
+}
+
+/// Like `from_hex`, for a signed LEB128 encoding.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn from_hex_signed(s: &str) -> Result<(i64, usize), Error> {
+    let bytes = parse_hex(s)?;
+    whole(&bytes, decode_signed(bytes.iter().copied())?)
+}
+
+fn format_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(s, "{:02x}", byte).expect("String should have room");
+    }
+    s
+}
+
+/// Parse the hex bytes in `s`, in any of the forms `from_hex` accepts,
+/// without decoding them.
+///
+/// Fails with an `InvalidData` error if a token isn't a whole number of hex
+/// bytes.
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    for token in s.split(|c: char| c.is_whitespace() || c == ',') {
+        let digits = token.strip_prefix("0x").unwrap_or(token);
+        if digits.len() % 2 != 0 {
+            return Err(invalid_data("Odd number of hex digits"));
+        }
+        for i in (0..digits.len()).step_by(2) {
+            // `from_str_radix` alone would take a sign, as in `+f`.
+            let byte = digits.get(i..i + 2)
+                .filter(|pair| pair.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| invalid_data("Invalid hex digit"))?;
+            bytes.push(byte);
+        }
+    }
+    Ok(bytes)
+}
+
+fn whole<T>(bytes: &[u8], decoded: (T, usize)) -> Result<(T, usize), Error> {
+    if decoded.1 != bytes.len() {
+        return Err(invalid_data("Trailing bytes after number"));
+    }
+    Ok(decoded)
+}
+
+fn invalid_data(msg: &'static str) -> Error {
+    Error::IoError(io_error(io::ErrorKind::InvalidData, msg))
+}
@@ -0,0 +1,248 @@
+//! A [`StreamIndex`](struct.StreamIndex.html) records the byte offset of every
+//! `stride`-th number in a stream of unsigned LEB128 numbers, so the stream
+//! itself can stay plain LEB128. The offsets are stored Elias–Fano encoded, in
+//! about `2 + log2(stream length / samples)` bits each.
+//!
+//! ```
+//! use nt_leb128::index::StreamIndex;
+//! use nt_leb128::write::Leb128Write;
+//!
+//! let mut stream = Vec::new();
+//! for val in (0..1000).map(|i| i * i) {
+//!     stream.write_unsigned(val).unwrap();
+//! }
+//!
+//! let index = StreamIndex::build(&stream, 16).unwrap();
+//! assert_eq!(index.len(), 1000);
+//! assert_eq!(index.get(&stream, 500).unwrap(), Some(250000));
+//! assert_eq!(index.get(&stream, 1000).unwrap(), None);
+//! // The stream is sorted, so it can be searched.
+//! assert_eq!(index.seek_to_value(&stream, 250001).unwrap(), Some((501, 251001)));
+//! ```
+
+use super::read::{not_enough_data, Error};
+use super::slice::{self, boundaries, decode_unsigned_at, validate_unsigned};
+
+/// An index of the positions of the numbers in an unsigned LEB128 stream,
+/// for random access without decoding from the start.
+///
+/// The index doesn't hold the stream; its methods take the stream that was
+/// passed to [`build`](#method.build), and fail or return nonsense for any
+/// other.
+#[derive(Debug, Clone)]
+pub struct StreamIndex {
+    stride: usize,
+    len: usize,
+    offsets: EliasFano,
+}
+
+impl StreamIndex {
+    /// Index `stream`, recording the offset of every `stride`-th number.
+    /// A larger stride makes the index smaller and lookups slower.
+    ///
+    /// Fails as `validate_unsigned` would if any encoding in `stream` is
+    /// malformed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is 0.
+    pub fn build<B: AsRef<[u8]>>(stream: B, stride: usize) -> Result<Self, Error> {
+        assert!(stride > 0, "stride must be at least 1");
+        let mut samples = Vec::new();
+        let mut len = 0;
+        for range in boundaries(stream.as_ref()) {
+            let range = range?;
+            if len % stride == 0 {
+                samples.push(range.start as u64);
+            }
+            len += 1;
+        }
+        Ok(StreamIndex {
+            stride,
+            len,
+            offsets: EliasFano::new(&samples),
+        })
+    }
+
+    /// The number of numbers in the stream.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the stream is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many numbers apart the recorded offsets are.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// The bytes of heap the index occupies.
+    pub fn heap_size(&self) -> usize {
+        self.offsets.heap_size()
+    }
+
+    /// The byte offset in the stream of the number at `index`, or `None` if
+    /// there are only `index` numbers or fewer.
+    pub fn offset_of<B: AsRef<[u8]>>(&self, stream: B, index: usize)
+                                     -> Result<Option<usize>, Error> {
+        if index >= self.len {
+            return Ok(None);
+        }
+        let stream = stream.as_ref();
+        let mut offset = self.offsets.get(index / self.stride) as usize;
+        for _ in 0..index % self.stride {
+            offset += validate_unsigned(stream.get(offset..).ok_or_else(not_enough_data)?)?;
+        }
+        Ok(Some(offset))
+    }
+
+    /// The number at `index`, or `None` if there are only `index` numbers or
+    /// fewer. Decodes at most `stride` numbers.
+    pub fn get<B: AsRef<[u8]>>(&self, stream: B, index: usize) -> Result<Option<u64>, Error> {
+        let stream = stream.as_ref();
+        match self.offset_of(stream, index)? {
+            Some(offset) => Ok(Some(decode_unsigned_at(stream, offset)?.0)),
+            None => Ok(None),
+        }
+    }
+
+    /// The index and value of the first number that is at least `val`, or
+    /// `None` if there is none, for a stream sorted in ascending order.
+    /// Binary searches the recorded offsets, then decodes at most `stride`
+    /// numbers.
+    pub fn seek_to_value<B: AsRef<[u8]>>(&self, stream: B, val: u64)
+                                         -> Result<Option<(usize, u64)>, Error> {
+        let stream = stream.as_ref();
+        // Find the first sample that isn't less than `val`; the number
+        // sought is at most a stride before it.
+        let (mut lo, mut hi) = (0, self.offsets.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if decode_unsigned_at(stream, self.offsets.get(mid) as usize)?.0 < val {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        let start = lo.saturating_sub(1);
+        let mut rest = match self.offsets.len() {
+            0 => return Ok(None),
+            _ => stream.get(self.offsets.get(start) as usize..).ok_or_else(not_enough_data)?,
+        };
+        for index in start * self.stride..self.len {
+            let (found, next) = slice::decode_unsigned(rest)?;
+            if found >= val {
+                return Ok(Some((index, found)));
+            }
+            rest = next;
+        }
+        Ok(None)
+    }
+}
+
+/// How many set bits of `upper` apart the positions in the select directory
+/// are.
+const SELECT_SAMPLE: usize = 256;
+
+/// A non-decreasing sequence of numbers in Elias–Fano form: the low bits of
+/// each packed at a fixed width, and the high bits as unary coded gaps, with
+/// a directory of where every `SELECT_SAMPLE`th gap ends.
+#[derive(Debug, Clone)]
+struct EliasFano {
+    len: usize,
+    low_bits: u32,
+    lower: Vec<u64>,
+    upper: Vec<u64>,
+    select_samples: Vec<usize>,
+}
+
+impl EliasFano {
+    fn new(vals: &[u64]) -> Self {
+        let len = vals.len();
+        let universe = vals.last().copied().unwrap_or(0);
+        let low_bits = match universe / (len.max(1) as u64) {
+            0 => 0,
+            ratio => 63 - ratio.leading_zeros(),
+        };
+        let upper_len = len + (universe >> low_bits) as usize + 1;
+        let mut ef = EliasFano {
+            len,
+            low_bits,
+            lower: vec![0; (len * low_bits as usize).div_ceil(64)],
+            upper: vec![0; upper_len.div_ceil(64)],
+            select_samples: Vec::with_capacity(len.div_ceil(SELECT_SAMPLE)),
+        };
+        for (i, &val) in vals.iter().enumerate() {
+            ef.set_low(i, val & ((1 << low_bits) - 1));
+            let pos = (val >> low_bits) as usize + i;
+            ef.upper[pos / 64] |= 1 << (pos % 64);
+            if i % SELECT_SAMPLE == 0 {
+                ef.select_samples.push(pos);
+            }
+        }
+        ef
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn heap_size(&self) -> usize {
+        (self.lower.capacity() + self.upper.capacity()) * 8
+            + self.select_samples.capacity() * std::mem::size_of::<usize>()
+    }
+
+    fn get(&self, i: usize) -> u64 {
+        let high = (self.select(i) - i) as u64;
+        (high << self.low_bits) | self.low(i)
+    }
+
+    /// The position of the `i`th set bit of `upper`, found by scanning on
+    /// from the nearest sampled position before it. With about as many
+    /// clear bits as set ones, that is a few words on average.
+    fn select(&self, i: usize) -> usize {
+        let sampled = self.select_samples[i / SELECT_SAMPLE];
+        let mut rank = i % SELECT_SAMPLE;
+        let mut w = sampled / 64;
+        let mut word = self.upper[w] & (u64::MAX << (sampled % 64));
+        loop {
+            let ones = word.count_ones() as usize;
+            if rank < ones {
+                for _ in 0..rank {
+                    word &= word - 1;
+                }
+                return w * 64 + word.trailing_zeros() as usize;
+            }
+            rank -= ones;
+            w += 1;
+            word = self.upper[w];
+        }
+    }
+
+    fn set_low(&mut self, i: usize, low: u64) {
+        if self.low_bits == 0 {
+            return;
+        }
+        let pos = i * self.low_bits as usize;
+        self.lower[pos / 64] |= low << (pos % 64);
+        if pos % 64 + self.low_bits as usize > 64 {
+            self.lower[pos / 64 + 1] |= low >> (64 - pos % 64);
+        }
+    }
+
+    fn low(&self, i: usize) -> u64 {
+        if self.low_bits == 0 {
+            return 0;
+        }
+        let pos = i * self.low_bits as usize;
+        let mut low = self.lower[pos / 64] >> (pos % 64);
+        if pos % 64 + self.low_bits as usize > 64 {
+            low |= self.lower[pos / 64 + 1] << (64 - pos % 64);
+        }
+        low & ((1 << self.low_bits) - 1)
+    }
+}
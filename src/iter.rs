@@ -0,0 +1,97 @@
+use super::config::Config;
+use super::read::{Error, Leb128Read};
+use bytes::Buf;
+
+/// An iterator over the unsigned LEB128 numbers in a `Buf`.
+///
+/// Created by [`iter_unsigned`](fn.iter_unsigned.html).
+#[cfg(not(feature = "signed-only"))]
+#[derive(Debug)]
+pub struct IterUnsigned<B> {
+    buf: B,
+    failed: bool,
+}
+
+/// An iterator over the signed LEB128 numbers in a `Buf`.
+///
+/// Created by [`iter_signed`](fn.iter_signed.html).
+#[cfg(not(feature = "unsigned-only"))]
+#[derive(Debug)]
+pub struct IterSigned<B> {
+    buf: B,
+    failed: bool,
+}
+
+/// Iterate over the unsigned LEB128 numbers in `buf`.
+///
+/// The iterator ends cleanly when `buf` is exhausted at a value boundary. If a
+/// value is truncated or overflows, the error is yielded once and the iterator
+/// then ends.
+///
+/// ```
+/// let values: Result<Vec<u64>, _> = nt_leb128::iter_unsigned(&[2u8, 0x80, 1][..]).collect();
+/// assert_eq!(values.unwrap(), [2, 128]);
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn iter_unsigned<B: Buf>(buf: B) -> IterUnsigned<B> {
+    IterUnsigned { buf, failed: false }
+}
+
+/// Iterate over the signed LEB128 numbers in `buf`.
+///
+/// The iterator ends cleanly when `buf` is exhausted at a value boundary. If a
+/// value is truncated or overflows, the error is yielded once and the iterator
+/// then ends.
+///
+/// ```
+/// let values: Result<Vec<i64>, _> = nt_leb128::iter_signed(&[2u8, 0x7e][..]).collect();
+/// assert_eq!(values.unwrap(), [2, -2]);
+/// ```
+#[cfg(not(feature = "unsigned-only"))]
+pub fn iter_signed<B: Buf>(buf: B) -> IterSigned<B> {
+    IterSigned { buf, failed: false }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl<B> IterUnsigned<B> {
+    /// Unwrap the underlying buffer, positioned after the last value read.
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+impl<B> IterSigned<B> {
+    /// Unwrap the underlying buffer, positioned after the last value read.
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl<B: Buf> Iterator for IterUnsigned<B> {
+    type Item = Result<u64, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || !self.buf.has_remaining() {
+            return None;
+        }
+        let result = self.buf.read_unsigned_with(&Config::new()).map(|(val, _)| val);
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+impl<B: Buf> Iterator for IterSigned<B> {
+    type Item = Result<i64, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || !self.buf.has_remaining() {
+            return None;
+        }
+        let result = self.buf.read_signed_with(&Config::new()).map(|(val, _)| val);
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
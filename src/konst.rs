@@ -0,0 +1,56 @@
+//! Everything but the `leb128!` macro is defined in `nt-leb128-core` and
+//! re-exported here, where `ConstDecodeError` also gains its conversion into
+//! `read::Error`.
+
+use super::read;
+
+pub use nt_leb128_core::{ConstDecodeError, MaxEncodedLen, max_encoded_len};
+#[cfg(not(feature = "unsigned-only"))]
+pub use nt_leb128_core::{decode_signed_const, encode_signed_const, encoded_len_signed,
+                         MAX_LEN_I16, MAX_LEN_I32, MAX_LEN_I64, MAX_LEN_I8, MAX_LEN_ISIZE};
+#[cfg(not(feature = "signed-only"))]
+pub use nt_leb128_core::{decode_unsigned_const, encode_unsigned_const, encoded_len_unsigned,
+                         MAX_LEN_U16, MAX_LEN_U32, MAX_LEN_U64, MAX_LEN_U8, MAX_LEN_USIZE};
+
+/// Expand to the LEB128 encoding of a constant as a byte array of exactly the
+/// encoded length, computed at compile time.
+///
+/// The value is encoded as unsigned LEB128, or as signed LEB128 when prefixed
+/// with `signed`. It must be a constant expression, and its half of the
+/// encoding must not be left out.
+///
+/// ```
+/// # #[macro_use] extern crate nt_leb128;
+/// # fn main() {
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// static TABLE: [[u8; 3]; 2] = [leb128!(624485u64), leb128!(signed -123456)];
+/// assert_eq!(TABLE[0], [0xe5, 0x8e, 0x26]);
+/// assert_eq!(TABLE[1], [0xc0, 0xbb, 0x78]);
+/// assert_eq!(leb128!(2), [2]);
+/// # }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! leb128 {
+    (signed $val:expr) => {{
+        const VALUE: i64 = $val;
+        const LEN: usize = $crate::encoded_len_signed(VALUE);
+        const ENCODED: [u8; LEN] = $crate::encode_signed_const::<LEN>(VALUE).0;
+        ENCODED
+    }};
+    ($val:expr) => {{
+        const VALUE: u64 = $val;
+        const LEN: usize = $crate::encoded_len_unsigned(VALUE);
+        const ENCODED: [u8; LEN] = $crate::encode_unsigned_const::<LEN>(VALUE).0;
+        ENCODED
+    }};
+}
+
+impl From<ConstDecodeError> for read::Error {
+    fn from(e: ConstDecodeError) -> Self {
+        match e {
+            ConstDecodeError::UnexpectedEof => read::not_enough_data(),
+            ConstDecodeError::Overflow => read::Error::Overflow,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::{CONTINUATION_BIT, encoded_len_unsigned, low_bits_of_u64};
+use super::write::put_unsigned;
+use bytes::Buf;
+
+/// A `Buf` that encodes a stream of unsigned values as LEB128 on the fly.
+///
+/// Only the encoding of the value currently being consumed is ever held in
+/// memory, so arbitrarily long streams can be handed to APIs that take an
+/// `impl Buf` without materializing the whole encoding.
+///
+/// `Buf::remaining` must be exact, so the iterator is cloned once up front to
+/// total the encoded length. This only sums lengths; nothing is encoded twice.
+///
+/// ```
+/// use bytes::Buf;
+/// use nt_leb128::lazy::EncodeBuf;
+///
+/// let mut buf = EncodeBuf::new(vec![2u64, 128, 624485].into_iter());
+/// assert_eq!(buf.remaining(), 6);
+/// assert_eq!(buf.to_bytes(), &[2, 0x80, 1, 0xe5, 0x8e, 0x26][..]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncodeBuf<I> {
+    values: I,
+    scratch: [u8; 10],
+    pos: usize,
+    len: usize,
+    remaining: usize,
+}
+
+impl<I> EncodeBuf<I>
+    where I: Iterator<Item = u64> + Clone
+{
+    /// Create a `Buf` producing the unsigned LEB128 encoding of every value
+    /// yielded by `values`, back to back.
+    pub fn new(values: I) -> Self {
+        let remaining = values.clone().map(encoded_len_unsigned).sum();
+        let mut buf = EncodeBuf {
+            values,
+            scratch: [0; 10],
+            pos: 0,
+            len: 0,
+            remaining,
+        };
+        buf.refill();
+        buf
+    }
+}
+
+impl<I> EncodeBuf<I>
+    where I: Iterator<Item = u64>
+{
+    fn refill(&mut self) {
+        while self.pos == self.len {
+            match self.values.next() {
+                Some(val) => {
+                    let mut writable = &mut self.scratch[..];
+                    self.len = put_unsigned(&mut writable, val);
+                    self.pos = 0;
+                }
+                None => return,
+            }
+        }
+    }
+}
+
+impl<I> Buf for EncodeBuf<I>
+    where I: Iterator<Item = u64>
+{
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.scratch[self.pos..self.len]
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.remaining, "cannot advance past the end of an EncodeBuf");
+        self.remaining -= cnt;
+        while cnt > 0 {
+            let step = ::std::cmp::min(cnt, self.len - self.pos);
+            self.pos += step;
+            cnt -= step;
+            self.refill();
+        }
+    }
+}
+
+/// A `Buf` producing the unsigned LEB128 encoding of a single value, one byte
+/// at a time as it is consumed.
+///
+/// This lets a varint be chained between other buffers for a vectored write
+/// without encoding it into an intermediate buffer first.
+///
+/// ```
+/// use bytes::Buf;
+/// use bytes::buf::BufExt;
+/// use nt_leb128::lazy::LazyUleb;
+///
+/// let body = &b"hello"[..];
+/// let mut frame = (&b"\x01"[..]).chain(LazyUleb::new(body.len() as u64)).chain(body);
+/// assert_eq!(frame.to_bytes(), &b"\x01\x05hello"[..]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LazyUleb {
+    // The bits not yet emitted, shifted down so the next byte's are lowest.
+    val: u64,
+    current: [u8; 1],
+    remaining: usize,
+}
+
+impl LazyUleb {
+    /// Create a `Buf` producing the unsigned LEB128 encoding of `val`.
+    pub fn new(val: u64) -> Self {
+        LazyUleb {
+            val,
+            current: [next_byte(val)],
+            remaining: encoded_len_unsigned(val),
+        }
+    }
+}
+
+impl From<u64> for LazyUleb {
+    fn from(val: u64) -> Self {
+        LazyUleb::new(val)
+    }
+}
+
+fn next_byte(val: u64) -> u8 {
+    let byte = low_bits_of_u64(val);
+    if val >> 7 != 0 {
+        // More bytes to come, so set the continuation bit.
+        byte | CONTINUATION_BIT
+    } else {
+        byte
+    }
+}
+
+impl Buf for LazyUleb {
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn bytes(&self) -> &[u8] {
+        if self.remaining == 0 {
+            &[]
+        } else {
+            &self.current
+        }
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.remaining, "cannot advance past the end of a LazyUleb");
+        self.remaining -= cnt;
+        for _ in 0..cnt {
+            self.val >>= 7;
+        }
+        self.current = [next_byte(self.val)];
+    }
+}
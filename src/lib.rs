@@ -7,28 +7,31 @@
 //! Read and write signed integers:
 //!
 //! ```
-//! use leb128::write::LEB128Write;
-//! use leb128::read::LEB128Read;
+//! # #[cfg(not(feature = "unsigned-only"))] {
+//! use nt_leb128::write::Leb128Write;
+//! use nt_leb128::read::Leb128Read;
 //!
 //! let mut buf = [0; 1024];
 //!
-//! // Write to anything that implements `std::io::Write`.
+//! // Write to anything that implements `bytes::BufMut`.
 //! {
 //!     let mut writable = &mut buf[..];
 //!     writable.write_signed(-12345).expect("Should write number");
 //! }
 //!
-//! // Read from anything that implements `std::io::Read`.
+//! // Read from anything that implements `bytes::Buf`.
 //! let mut readable = &buf[..];
-//! let val = readable.read_signed().expect("Should read number");
+//! let (val, _len) = readable.read_signed().expect("Should read number");
 //! assert_eq!(val, -12345);
+//! # }
 //! ```
 //!
 //! Or read and write unsigned integers:
 //!
 //! ```
-//! use leb128::write::LEB128Write;
-//! use leb128::read::LEB128Read;
+//! # #[cfg(not(feature = "signed-only"))] {
+//! use nt_leb128::write::Leb128Write;
+//! use nt_leb128::read::Leb128Read;
 //!
 //! let mut buf = [0; 1024];
 //!
@@ -38,29 +41,124 @@
 //! }
 //!
 //! let mut readable = &buf[..];
-//! let val = readable.read_signed().expect("Should read number");
+//! let (val, _len) = readable.read_unsigned().expect("Should read number");
 //! assert_eq!(val, 98765);
+//! # }
+//! ```
+//!
+//! ## Features
+//!
+//! * `alloc` (default): the APIs that allocate, such as
+//!   [`explain`](explain/fn.explain.html), `Leb128Read::read_bytes`,
+//!   `write::Leb128Writer`, the `log` module and the `Vec`/`String` codec
+//!   implementations. Without it, nothing in this crate
+//!   touches the heap.
+//! * `bytes` (default): the traits for every `Buf` and `BufMut` from version
+//!   0.5 of `bytes`, and the modules built on them, such as
+//!   [`frame`](frame/index.html) and [`lazy`](lazy/index.html). Without it,
+//!   the traits are implemented for `&[u8]`, `&mut [u8]` and `Vec<u8>`, and
+//!   the crate depends only on `nt-leb128-core`.
+//! * `derive`: `#[derive(Leb128Encode, Leb128Decode)]`.
+//! * `bytes-1`: reading from and writing to buffers from version 1 of the
+//!   `bytes` crate, through [`Bytes1`](bytes1/struct.Bytes1.html). Buffers
+//!   from version 0.5 implement the traits directly.
+//! * `time`: reading and writing `Duration`s and `SystemTime`s in
+//!   [`time`](time/index.html).
+//! * `arrow`: encoding Apache Arrow `UInt64Array`s, nulls included, in
+//!   [`arrow`](arrow/index.html).
+//! * `metrics`: process wide counters of the numbers encoded and decoded and
+//!   of rejected input, in [`metrics`](metrics/index.html).
+//! * `opt-size`: leaves out the fast paths that decode straight from a
+//!   buffer's current chunk, so every read gathers its bytes and shares one
+//!   copy of the decoder. For targets where code size matters more than
+//!   speed; `nt-leb128-core` alone is smaller still.
+//! * `portable-simd` (nightly only): bulk kernels, such as the scan behind
+//!   [`count_values`](fn.count_values.html), written against `core::simd`
+//!   so that any target with vector registers gets them.
+//! * `default-flavor-dwarf`, `default-flavor-wasm`, `default-flavor-protobuf`:
+//!   the [`Flavor`](config/enum.Flavor.html) whose rules the `Leb128Read`
+//!   and `Leb128Write` methods that take no `Config`, the free functions at
+//!   the crate root and the `Leb128Encode` and `Leb128Decode` implementations
+//!   apply. At most one can be enabled; without any, they apply the `Generic`
+//!   rules. Everything else, from the helper modules and language bindings
+//!   to the command line tools, reads and writes plain LEB128 whatever the
+//!   default flavor.
+//! * `signed-only`, `unsigned-only`: leave out the other half of the
+//!   encoding, for firmware where every kilobyte counts: its `read_*` and
+//!   `write_*` methods, free functions, wrapper types and codec
+//!   implementations, and the modules built on it. Length prefixes, tags and
+//!   counts are unsigned LEB128, so they go with `signed-only`, and modules
+//!   needing both halves, such as [`time`](time/index.html), go with either.
+//!   At most one can be enabled.
+//! * `tokio`: cancel safe asynchronous reads in
+//!   [`async_io`](async_io/index.html).
+//! * `ffi`: the C API in [`ffi`](ffi/index.html), for building as a
+//!   `cdylib` or `staticlib`.
+//! * `wasm`: JavaScript bindings in [`wasm`](wasm/index.html), for building
+//!   with `wasm-pack`.
+//! * `python`: a Python extension module in [`python`](python/index.html).
+//! * `arbitrary`: `Arbitrary` implementations for the wrapper types, and the
+//!   edge case generators in [`fuzz`](fuzz/index.html).
+//! * `proptest`: strategies for generating encodings in
+//!   [`strategy`](strategy/index.html).
+//! * `verification`: Kani proofs of round-tripping, panic freedom and length
+//!   bounds for the core routines, run with `cargo kani --features
+//!   verification`.
+//! * `cli`: the `leb128` command line tool, e.g.
+//!   `leb128 encode --signed -- -123456` or `leb128 decode e5 8e 26`.
+//! * `test-util`: the `assert_leb_roundtrip!` and `assert_leb_eq!` macros.
+//! * `test-vectors`: the specification examples and boundary values in
+//!   [`test_vectors`](test_vectors/index.html).
+//!
+//! The bit manipulation in [`raw`](raw/index.html), the encoded lengths and
+//! the const encoders and decoders live in the dependency free, `no_std`
+//! `nt-leb128-core` crate and are re-exported here; depend on it directly for
+//! just the encoding.
+//!
+//! For one-off uses, the free functions avoid importing the traits:
+//!
+//! ```
+//! # #[cfg(not(feature = "signed-only"))] {
+//! let mut buf = Vec::new();
+//! nt_leb128::write_unsigned(&mut buf, 624485).expect("Should write number");
+//!
+//! let mut readable = &buf[..];
+//! let (val, len) = nt_leb128::read_unsigned(&mut readable).expect("Should read number");
+//! assert_eq!((val, len), (624485, 3));
+//! # }
 //! ```
 
 #![deny(missing_docs)]
+#![cfg_attr(feature = "portable-simd", feature(portable_simd))]
 
-#[doc(hidden)]
-pub const CONTINUATION_BIT: u8 = 1 << 7;
-#[doc(hidden)]
-pub const SIGN_BIT: u8 = 1 << 6;
+#[cfg(all(feature = "signed-only", feature = "unsigned-only"))]
+compile_error!("at most one of `signed-only` and `unsigned-only` can be enabled");
 
-#[doc(hidden)]
-#[inline]
-pub fn low_bits_of_byte(byte: u8) -> u8 {
-    byte & !CONTINUATION_BIT
-}
+// Lets the derive macros' `::nt_leb128` paths resolve within this crate's tests.
+#[cfg(test)]
+extern crate self as nt_leb128;
+
+#[cfg(feature = "derive")]
+pub use nt_leb128_derive::{Leb128Decode, Leb128Encode};
+
+/// The version of `bytes` whose `Buf` and `BufMut` the traits are
+/// implemented for, so downstream crates can name its types without depending
+/// on the same version themselves.
+#[cfg(feature = "bytes")]
+pub use bytes;
 
 #[doc(hidden)]
-#[inline]
-pub fn low_bits_of_u64(val: u64) -> u8 {
-    let byte = val & (std::u8::MAX as u64);
-    low_bits_of_byte(byte as u8)
-}
+pub use self::raw::{CONTINUATION_BIT, SIGN_BIT, low_bits_of_byte, low_bits_of_u64};
+
+#[macro_use]
+mod buf;
+mod konst;
+#[cfg(feature = "alloc")]
+mod scratch;
+#[cfg(feature = "portable-simd")]
+mod simd;
+
+pub use nt_leb128_core::raw;
 
 /// A module for reading signed and unsigned integers that have been LEB128
 /// encoded.
@@ -69,9 +167,311 @@ pub mod read;
 /// A module for writing integers encoded as LEB128.
 pub mod write;
 
+/// A module for reading runs of differently typed LEB128 numbers, as in
+/// hand-written parsers of binary formats.
+pub mod seq;
+
+/// A module for `Config`, which sets the rules for reading and writing in one
+/// place.
+pub mod config;
+
+/// A module for explaining, byte by byte, how an LEB128 encoding decodes.
+#[cfg(feature = "alloc")]
+pub mod explain;
+
+/// A module for the LEB128 run headers of Parquet's RLE/bit-packed hybrid
+/// encoding.
+#[cfg(not(feature = "signed-only"))]
+pub mod parquet;
+
+/// A module for the 128 bit decimals of Apache ORC; its other integers use
+/// `Flavor::Orc`.
+pub mod orc;
+
+/// A module for snapshots of changed fields, sent as deltas from a baseline.
+#[cfg(not(feature = "signed-only"))]
+pub mod snapshot;
+
+/// A module for reading and writing records whose layout is described at
+/// runtime, for generic inspection tools.
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod schema;
+
+/// A module for writing LEB128 encodings as hex strings and reading them
+/// back, for configuration files, logs and test fixtures.
+#[cfg(feature = "alloc")]
+pub mod hex;
+
+/// A module for the Base64 VLQ encoding of JavaScript source maps, a sibling
+/// of signed LEB128.
+#[cfg(feature = "alloc")]
+pub mod vlq;
+
+/// A module for blocks of values stored in whichever of LEB128, frame of
+/// reference or bit packing suits them best.
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+pub mod block;
+
+/// A module for graphs stored as adjacency lists of delta encoded LEB128.
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+pub mod graph;
+
+/// A module for measuring how a set of values would encode, to choose a
+/// storage layout.
+pub mod analyze;
+
+/// A module for values held in their encoded form.
+pub mod encoded;
+
+/// A module for compact indexes giving random access into streams of
+/// LEB128 numbers.
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+pub mod index;
+
+/// A module for reading from `VecDeque<u8>` ring buffers.
+pub mod deque;
+
+/// A module for iterating over the LEB128 numbers in a buffer.
+#[cfg(feature = "bytes")]
+pub mod iter;
+
+/// A module for `Buf`s that produce LEB128 encodings lazily as they are
+/// consumed.
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+pub mod lazy;
+
+/// A module for converting streams of LEB128 numbers from one flavor's rules
+/// to another's, as when migrating between storage formats.
+#[cfg(feature = "bytes")]
+pub mod transcode;
+
+/// A module for encoding structured values as sequences of LEB128 numbers.
+pub mod codec;
+
+/// The traits and wrapper types most code needs, for glob importing with
+/// `use nt_leb128::prelude::*`.
+pub mod prelude;
+
+/// A module for working with LEB128 encodings directly in byte slices.
+pub mod slice;
+
+/// A module for integer newtypes that are encoded as LEB128.
+pub mod types;
+
+/// A module for decoding LEB128 from input that arrives in arbitrary chunks.
+pub mod decoder;
+
+/// A module for encoding LEB128 into output windows of limited size.
+pub mod encoder;
+
+/// A module for encoding into and decoding from buffers passed by value, for
+/// completion based I/O.
+#[cfg(feature = "bytes")]
+pub mod owned;
+
+/// A module for frames: payloads prefixed with their length as LEB128, as in
+/// protobuf's delimited message streams.
+#[cfg(all(feature = "alloc", feature = "bytes", not(feature = "signed-only")))]
+pub mod frame;
+
+/// A module for append-only segments of length prefixed records, recoverable
+/// after a torn write, as in write-ahead logs.
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+pub mod log;
+
+/// `extern "C"` functions for reading and writing LEB128 from C and C++.
+#[cfg(all(feature = "ffi", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod ffi;
+
+/// Support for buffers from version 1 of the `bytes` crate.
+#[cfg(feature = "bytes-1")]
+pub mod bytes1;
+
+/// A module for encoding `Duration`s and `SystemTime`s as LEB128.
+#[cfg(all(feature = "time", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod time;
+
+/// A module for encoding Apache Arrow arrays as LEB128.
+#[cfg(all(feature = "arrow", not(feature = "signed-only")))]
+pub mod arrow;
+
+/// Counters of the numbers encoded and decoded, and of rejected input.
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(not(feature = "metrics"))]
+mod metrics;
+
+/// A module for reading LEB128 from tokio's asynchronous I/O traits.
+#[cfg(feature = "tokio")]
+pub mod async_io;
+
+/// JavaScript bindings, through `wasm-bindgen`.
+#[cfg(all(feature = "wasm", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod wasm;
+
+/// A Python extension module, through PyO3.
+#[cfg(all(feature = "python", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod python;
+
+/// Encodings from the DWARF and WebAssembly specifications, paired with
+/// their values, for checking interoperability.
+#[cfg(all(feature = "test-vectors", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod test_vectors;
+
+/// Generators of structurally interesting inputs for fuzzing decoders.
+#[cfg(all(feature = "arbitrary", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod fuzz;
+
+/// Proptest strategies for valid and adversarial encodings.
+#[cfg(all(feature = "proptest", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod strategy;
+
+/// Assertion macros and helpers for testing code built on this crate.
+#[cfg(all(feature = "test-util", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub mod test_util;
+
+pub use self::read::{Decoded, Leb128Read};
+pub use self::write::Leb128Write;
+#[allow(deprecated)]
+#[deprecated(note = "renamed to `Leb128Read`")]
 pub use self::read::LEB128Read;
+#[allow(deprecated)]
+#[deprecated(note = "renamed to `Leb128Write`")]
 pub use self::write::LEB128Write;
+pub use self::codec::{Leb128Decode, Leb128Encode};
+pub use self::config::{Config, Flavor, OverflowPolicy, SignedEncoding};
+pub use self::analyze::{analyze, SizeReport};
+#[cfg(not(feature = "unsigned-only"))]
+pub use self::encoded::EncodedI64;
+#[cfg(not(feature = "signed-only"))]
+pub use self::encoded::EncodedU64;
+#[cfg(feature = "alloc")]
+pub use self::explain::{explain, Explanation};
+#[cfg(all(feature = "bytes", not(feature = "unsigned-only")))]
+pub use self::iter::iter_signed;
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+pub use self::iter::iter_unsigned;
+pub use self::slice::count_values;
+#[cfg(not(feature = "unsigned-only"))]
+pub use self::slice::{boundaries_signed, validate_signed, validate_signed_canonical};
+#[cfg(not(feature = "signed-only"))]
+pub use self::slice::{boundaries, cmp_encoded, validate_unsigned, validate_unsigned_canonical};
+#[cfg(not(feature = "unsigned-only"))]
+pub use self::types::Sleb128;
+#[cfg(not(feature = "signed-only"))]
+pub use self::types::Uleb128;
+pub use self::decoder::{DecodeProgress, Decoder};
+pub use self::encoder::{EncodeProgress, Encoder};
+#[cfg(feature = "alloc")]
+pub use self::slice::split_at_boundaries;
+#[cfg(feature = "alloc")]
+pub use self::scratch::with_scratch_capacity;
+pub use self::konst::{ConstDecodeError, max_encoded_len, MaxEncodedLen};
+#[cfg(not(feature = "unsigned-only"))]
+pub use self::konst::{decode_signed_const, encode_signed_const, encoded_len_signed, MAX_LEN_I16,
+                      MAX_LEN_I32, MAX_LEN_I64, MAX_LEN_I8, MAX_LEN_ISIZE};
+#[cfg(not(feature = "signed-only"))]
+pub use self::konst::{decode_unsigned_const, encode_unsigned_const, encoded_len_unsigned,
+                      MAX_LEN_U16, MAX_LEN_U32, MAX_LEN_U64, MAX_LEN_U8, MAX_LEN_USIZE};
 
-#[cfg(test)]
+use std::io;
+
+/// Create an `io::Error` of the given kind. With the `alloc` feature, it
+/// carries `msg`; without it, only the kind, since a message would be boxed.
+pub(crate) fn io_error(kind: io::ErrorKind, msg: &'static str) -> io::Error {
+    if cfg!(feature = "alloc") {
+        io::Error::new(kind, msg)
+    } else {
+        kind.into()
+    }
+}
+
+/// Trait for types that can both read and write LEB128 encoded numbers.
+///
+/// This is implemented automatically for everything that implements both
+/// `Leb128Read` and `Leb128Write`, so generic code that needs both directions
+/// can take a single bound.
+pub trait Leb128Codec: Leb128Read + Leb128Write {}
+
+impl<T> Leb128Codec for T
+    where T: Leb128Read + Leb128Write + ?Sized
+{
+}
+
+/// The old name of [`Leb128Codec`](trait.Leb128Codec.html), kept for one
+/// release.
+#[deprecated(note = "renamed to `Leb128Codec`")]
+pub use self::Leb128Codec as LEB128Codec;
+
+/// Read an unsigned LEB128 number from `buf`, returning it along with the
+/// number of bytes read.
+///
+/// Equivalent to `Leb128Read::read_unsigned`, without importing the trait.
+#[cfg(not(feature = "signed-only"))]
+pub fn read_unsigned<R: Leb128Read>(buf: &mut R) -> Result<(u64, usize), read::Error> {
+    buf.read_unsigned()
+}
+
+/// Read a signed LEB128 number from `buf`, returning it along with the number
+/// of bytes read.
+///
+/// Equivalent to `Leb128Read::read_signed`, without importing the trait.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn read_signed<R: Leb128Read>(buf: &mut R) -> Result<(i64, usize), read::Error> {
+    buf.read_signed()
+}
+
+/// Write `val` to `buf` as unsigned LEB128, returning the number of bytes
+/// written.
+///
+/// Equivalent to `Leb128Write::write_unsigned`, without importing the trait.
+#[cfg(not(feature = "signed-only"))]
+pub fn write_unsigned<W: Leb128Write>(buf: &mut W, val: u64) -> Result<usize, write::Error> {
+    buf.write_unsigned(val)
+}
+
+/// Write `val` to `buf` as signed LEB128, returning the number of bytes
+/// written.
+///
+/// Equivalent to `Leb128Write::write_signed`, without importing the trait.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn write_signed<W: Leb128Write>(buf: &mut W, val: i64) -> Result<usize, write::Error> {
+    buf.write_signed(val)
+}
+
+/// The total number of bytes needed to encode every number in `vals` as
+/// unsigned LEB128, computed without encoding any of them.
+///
+/// ```
+/// assert_eq!(nt_leb128::encoded_len_unsigned_iter(vec![1, 128, u64::MAX]), 13);
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn encoded_len_unsigned_iter<I>(vals: I) -> usize
+    where I: IntoIterator<Item = u64>
+{
+    vals.into_iter().map(encoded_len_unsigned).sum()
+}
+
+/// The total number of bytes needed to encode every number in `vals` as
+/// signed LEB128, computed without encoding any of them.
+///
+/// ```
+/// assert_eq!(nt_leb128::encoded_len_signed_iter(-65..65), 2 + 128 + 2);
+/// ```
+#[cfg(not(feature = "unsigned-only"))]
+pub fn encoded_len_signed_iter<I>(vals: I) -> usize
+    where I: IntoIterator<Item = i64>
+{
+    vals.into_iter().map(encoded_len_signed).sum()
+}
+
+#[cfg(all(test, feature = "bytes"))]
 mod tests_bytes;
 
+#[cfg(test)]
+mod tests_slice;
+
+#[cfg(all(kani, feature = "verification", not(any(feature = "signed-only",
+                                                  feature = "unsigned-only"))))]
+mod verification;
+
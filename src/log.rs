@@ -0,0 +1,212 @@
+//! A segment is a four byte header followed by records, each a payload
+//! prefixed with its length as unsigned LEB128. Records are only ever
+//! appended, so a crash can at worst leave the last record cut short; opening
+//! the segment again with [`SegmentWriter::open`](struct.SegmentWriter.html#method.open)
+//! truncates it back to its last whole record.
+//!
+//! ```
+//! use nt_leb128::log::{SegmentReader, SegmentWriter};
+//!
+//! let mut writer = SegmentWriter::new(Vec::new()).unwrap();
+//! writer.append(b"first").unwrap();
+//! writer.append(b"second").unwrap();
+//! let mut segment = writer.into_inner();
+//!
+//! // A torn write leaves part of a third record behind.
+//! segment.extend_from_slice(b"\x05thi");
+//! let records: Vec<_> = SegmentReader::new(&segment).unwrap().collect();
+//! assert_eq!(records[0].as_deref().unwrap(), b"first");
+//! assert_eq!(records[1].as_deref().unwrap(), b"second");
+//! assert!(records[2].as_ref().unwrap_err().is_incomplete());
+//!
+//! let valid = nt_leb128::log::recover(&segment[..]).unwrap();
+//! assert_eq!(valid as usize, segment.len() - 4);
+//! ```
+
+use super::{encode_unsigned_const, io_error, slice};
+use super::read::{not_enough_data, try_decode_unsigned, Error, MAX_LEN};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+/// The bytes every segment starts with: a magic number and a format
+/// version.
+pub const HEADER: [u8; 4] = *b"LEB\x01";
+
+/// A writer that appends records to a segment.
+#[derive(Debug)]
+pub struct SegmentWriter<W> {
+    inner: W,
+    size: u64,
+}
+
+impl<W: Write> SegmentWriter<W> {
+    /// Start a new segment in `inner`, writing its header.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(&HEADER)?;
+        Ok(SegmentWriter {
+            inner,
+            size: HEADER.len() as u64,
+        })
+    }
+
+    /// Append `record`, returning the offset of its length prefix in the
+    /// segment.
+    ///
+    /// Nothing is flushed; call [`flush`](#method.flush), and sync the file,
+    /// before relying on the record being durable.
+    pub fn append(&mut self, record: &[u8]) -> io::Result<u64> {
+        let (prefix, prefix_len) = encode_unsigned_const::<MAX_LEN>(record.len() as u64);
+        self.inner.write_all(&prefix[..prefix_len])?;
+        self.inner.write_all(record)?;
+        let offset = self.size;
+        self.size += (prefix_len + record.len()) as u64;
+        Ok(offset)
+    }
+
+    /// Flush the inner writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// The size of the segment in bytes, header included.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl SegmentWriter<File> {
+    /// Open the segment in `file`, which must be readable and writable, for
+    /// appending. An empty file becomes a new segment.
+    ///
+    /// A torn final record, or a torn header, is truncated away first, as
+    /// found by [`recover`](fn.recover.html). Fails with an `InvalidData`
+    /// error if the file isn't a segment.
+    pub fn open(mut file: File) -> io::Result<Self> {
+        file.seek(SeekFrom::Start(0))?;
+        let size = recover(BufReader::new(&file))?;
+        file.set_len(size)?;
+        file.seek(SeekFrom::Start(size))?;
+        if size == 0 {
+            return SegmentWriter::new(file);
+        }
+        Ok(SegmentWriter { inner: file, size })
+    }
+}
+
+/// Scan the segment read from `reader`, returning the size of its header and
+/// whole records: where appending should resume. A final record that was cut
+/// short is left out, and a segment too short to hold its header has a size
+/// of zero. Length prefixes are read a byte at a time, so `reader` should be
+/// buffered.
+///
+/// Fails with an `InvalidData` error if the header is wrong or a length
+/// prefix is malformed.
+pub fn recover<R: BufRead>(mut reader: R) -> io::Result<u64> {
+    let mut header = [0; HEADER.len()];
+    let mut header_len = 0;
+    for byte in (&mut reader).bytes().take(HEADER.len()) {
+        header[header_len] = byte?;
+        header_len += 1;
+    }
+    if header[..header_len] != HEADER[..header_len] {
+        return Err(io_error(io::ErrorKind::InvalidData, "Not a log segment"));
+    }
+    if header_len < HEADER.len() {
+        return Ok(0);
+    }
+
+    let mut size = HEADER.len() as u64;
+    loop {
+        let (len, prefix_len) = match try_decode_unsigned::<_, io::Error>((&mut reader).bytes()) {
+            Ok(decoded) => decoded,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(size),
+            Err(e) => return Err(e),
+        };
+        if io::copy(&mut (&mut reader).take(len), &mut io::sink())? < len {
+            return Ok(size);
+        }
+        size += prefix_len as u64 + len;
+    }
+}
+
+/// An iterator over the records of a segment held in memory.
+///
+/// A record cut short at the end of the segment is yielded as
+/// `Error::Incomplete`; after any error, the iterator yields nothing more.
+#[derive(Debug, Clone)]
+pub struct SegmentReader<'a> {
+    segment: &'a [u8],
+    offset: usize,
+    failed: bool,
+}
+
+impl<'a> SegmentReader<'a> {
+    /// Iterate over the records of `segment`.
+    ///
+    /// Fails with `Error::Incomplete` if `segment` is too short to hold the
+    /// header, and with an `InvalidData` error if the header is wrong.
+    pub fn new(segment: &'a [u8]) -> Result<Self, Error> {
+        if segment.len() < HEADER.len() {
+            return Err(not_enough_data());
+        }
+        if segment[..HEADER.len()] != HEADER {
+            return Err(Error::IoError(io_error(io::ErrorKind::InvalidData,
+                                               "Not a log segment")));
+        }
+        Ok(SegmentReader {
+            segment,
+            offset: HEADER.len(),
+            failed: false,
+        })
+    }
+
+    /// The offset of the next record's length prefix in the segment.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn next_record(&self) -> Result<(&'a [u8], usize), Error> {
+        let rest = &self.segment[self.offset..];
+        let (len, payload) = slice::decode_unsigned(rest)?;
+        if len > payload.len() as u64 {
+            return Err(not_enough_data());
+        }
+        let len = len as usize;
+        Ok((&payload[..len], rest.len() - payload.len() + len))
+    }
+}
+
+impl<'a> Iterator for SegmentReader<'a> {
+    type Item = Result<&'a [u8], Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.offset == self.segment.len() {
+            return None;
+        }
+        match self.next_record() {
+            Ok((record, len)) => {
+                self.offset += len;
+                Some(Ok(record))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
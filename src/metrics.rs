@@ -0,0 +1,121 @@
+//! Process wide counters of the numbers this crate encodes and decodes, and of
+//! the malformed input it rejects, so services can alert on rates of bad
+//! input without wrapping every call.
+//!
+//! The counters are relaxed atomics, bumped by the `Leb128Read` and
+//! `Leb128Write` implementations in this crate and by the canonical
+//! validators in `slice`. Without the `metrics` feature they compile away.
+
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicU64, Ordering};
+use super::read::Error;
+
+/// A snapshot of the counters, as returned by [`stats`](fn.stats.html).
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The numbers encoded.
+    pub values_encoded: u64,
+    /// The bytes those encodings occupied.
+    pub bytes_encoded: u64,
+    /// The numbers decoded successfully.
+    pub values_decoded: u64,
+    /// The bytes those encodings occupied.
+    pub bytes_decoded: u64,
+    /// The decodes that failed with `read::Error::Overflow`.
+    pub overflows: u64,
+    /// The encodings rejected with `read::Error::NonCanonical`.
+    pub non_canonical: u64,
+}
+
+#[cfg(feature = "metrics")]
+static VALUES_ENCODED: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "metrics")]
+static BYTES_ENCODED: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "metrics")]
+static VALUES_DECODED: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "metrics")]
+static BYTES_DECODED: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "metrics")]
+static OVERFLOWS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "metrics")]
+static NON_CANONICAL: AtomicU64 = AtomicU64::new(0);
+
+/// A snapshot of the counters since the process started or they were last
+/// reset. Each counter is read separately, so a snapshot taken while other
+/// threads encode or decode may be slightly inconsistent.
+///
+/// ```
+/// # #[cfg(not(feature = "signed-only"))] {
+/// use nt_leb128::prelude::*;
+///
+/// let before = nt_leb128::metrics::stats();
+/// let mut buf = Vec::new();
+/// buf.write_unsigned(300).expect("Should write number");
+/// let after = nt_leb128::metrics::stats();
+/// assert!(after.values_encoded > before.values_encoded);
+/// assert!(after.bytes_encoded >= before.bytes_encoded + 2);
+/// # }
+/// ```
+#[cfg(feature = "metrics")]
+pub fn stats() -> Stats {
+    Stats {
+        values_encoded: VALUES_ENCODED.load(Ordering::Relaxed),
+        bytes_encoded: BYTES_ENCODED.load(Ordering::Relaxed),
+        values_decoded: VALUES_DECODED.load(Ordering::Relaxed),
+        bytes_decoded: BYTES_DECODED.load(Ordering::Relaxed),
+        overflows: OVERFLOWS.load(Ordering::Relaxed),
+        non_canonical: NON_CANONICAL.load(Ordering::Relaxed),
+    }
+}
+
+/// Reset every counter to zero.
+#[cfg(feature = "metrics")]
+pub fn reset() {
+    for counter in &[&VALUES_ENCODED, &BYTES_ENCODED, &VALUES_DECODED, &BYTES_DECODED,
+                     &OVERFLOWS, &NON_CANONICAL] {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Count an encoding of `len` bytes.
+#[inline]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_encoded(len: usize) {
+    #[cfg(feature = "metrics")]
+    {
+        VALUES_ENCODED.fetch_add(1, Ordering::Relaxed);
+        BYTES_ENCODED.fetch_add(len as u64, Ordering::Relaxed);
+    }
+}
+
+/// Count the outcome of a decode.
+#[inline]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_decoded<T>(result: &Result<(T, usize), Error>) {
+    #[cfg(feature = "metrics")]
+    match *result {
+        Ok((_, len)) => {
+            VALUES_DECODED.fetch_add(1, Ordering::Relaxed);
+            BYTES_DECODED.fetch_add(len as u64, Ordering::Relaxed);
+        }
+        Err(ref e) => record_error(e),
+    }
+}
+
+/// Count a rejected encoding.
+#[inline]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_error(error: &Error) {
+    #[cfg(feature = "metrics")]
+    match *error {
+        Error::Overflow => {
+            OVERFLOWS.fetch_add(1, Ordering::Relaxed);
+        }
+        Error::NonCanonical => {
+            NON_CANONICAL.fetch_add(1, Ordering::Relaxed);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,80 @@
+//! ORC's integers are read and written with `Flavor::Orc`. Decimals are the
+//! exception: their unscaled values are up to 128 bits wide, zigzag encoded
+//! and written as unsigned base 128 varints of up to 19 bytes, which these
+//! functions handle.
+//!
+//! ```
+//! # #[cfg(not(feature = "unsigned-only"))] {
+//! use nt_leb128::orc::{decode_decimal, encode_decimal};
+//! use nt_leb128::Flavor;
+//!
+//! let mut buf = [0; 20];
+//! let rest = encode_decimal(&mut buf, -(1 << 100)).unwrap();
+//! let len = 20 - rest.len();
+//! assert_eq!(decode_decimal(&buf[..len]).unwrap(), (-(1 << 100), &[][..]));
+//!
+//! // Signed integers are zigzag encoded, so -1 is the single byte 0x01.
+//! let mut readable = &[0x01][..];
+//! assert_eq!(Flavor::Orc.read_signed(&mut readable).unwrap(), (-1, 1));
+//! # }
+//! ```
+
+use super::raw;
+use super::read::{not_enough_data, Error};
+use super::write;
+
+/// The longest encoding of a decimal: 128 bits, seven to a byte.
+pub const MAX_LEN_DECIMAL: usize = 19;
+
+/// Decode the decimal at the start of `input`, returning its unscaled value
+/// along with the rest of `input`.
+///
+/// Fails with `Error::Overflow` if the value is wider than 128 bits.
+pub fn decode_decimal(input: &[u8]) -> Result<(i128, &[u8]), Error> {
+    let mut val = 0u128;
+    for (i, &byte) in input.iter().take(MAX_LEN_DECIMAL).enumerate() {
+        let low_bits = raw::low_bits_of_byte(byte) as u128;
+        let shift = raw::shift_of(i);
+        if shift > 128 - raw::PAYLOAD_BITS && low_bits >> (128 - shift) != 0 {
+            return Err(Error::Overflow);
+        }
+        val |= low_bits << shift;
+        if raw::is_terminal(byte) {
+            let val = (val >> 1) as i128 ^ -((val & 1) as i128);
+            return Ok((val, &input[i + 1..]));
+        }
+    }
+    if input.len() < MAX_LEN_DECIMAL {
+        return Err(not_enough_data());
+    }
+    Err(Error::Overflow)
+}
+
+/// Encode `val`, the unscaled value of a decimal, at the front of `buf`,
+/// returning the part of `buf` after the encoding.
+///
+/// Fails with `write::Error::InsufficientSpace`, leaving `buf` untouched, if
+/// the encoding doesn't fit.
+pub fn encode_decimal(buf: &mut [u8], val: i128) -> Result<&mut [u8], write::Error> {
+    let mut zigzag = ((val << 1) ^ (val >> 127)) as u128;
+    let mut bytes = [0u8; MAX_LEN_DECIMAL];
+    let mut len = 0;
+    loop {
+        bytes[len] = raw::low_bits_of_byte(zigzag as u8);
+        zigzag >>= raw::PAYLOAD_BITS;
+        len += 1;
+        if zigzag == 0 {
+            break;
+        }
+        bytes[len - 1] |= raw::CONTINUATION_BIT;
+    }
+    if len > buf.len() {
+        return Err(write::Error::InsufficientSpace {
+            needed: len,
+            available: buf.len(),
+        });
+    }
+    let (head, rest) = buf.split_at_mut(len);
+    head.copy_from_slice(&bytes[..len]);
+    Ok(rest)
+}
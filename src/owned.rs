@@ -0,0 +1,85 @@
+//! Completion based runtimes, such as those built on io_uring, take
+//! ownership of a buffer for the duration of an operation. These functions
+//! follow the same convention: they take the buffer by value and hand it
+//! back alongside the result, so it can be submitted again without copying.
+//!
+//! ```
+//! # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+//! use nt_leb128::owned;
+//!
+//! let (written, buf) = owned::write_unsigned(Vec::new(), 624485);
+//! assert_eq!(written.unwrap(), 3);
+//! let (written, buf) = owned::write_signed(buf, -2);
+//! assert_eq!(written.unwrap(), 1);
+//!
+//! let (read, buf) = owned::read_unsigned(buf, 0);
+//! assert_eq!(read.unwrap(), (624485, 3));
+//! let (read, _buf) = owned::read_signed(buf, 3);
+//! assert_eq!(read.unwrap(), (-2, 1));
+//! # }
+//! ```
+
+use super::config::Config;
+use super::read::Error;
+#[cfg(not(feature = "signed-only"))]
+use super::slice::decode_unsigned_at;
+#[cfg(not(feature = "unsigned-only"))]
+use super::slice::decode_signed_at;
+use super::write::{self, Leb128Write};
+use bytes::BufMut;
+
+/// Append `val` to `buf` as signed LEB128, returning the number of bytes
+/// written along with the buffer.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn write_signed<B: BufMut>(mut buf: B, val: i64) -> (Result<usize, write::Error>, B) {
+    let result = buf.write_signed_with(val, &Config::new());
+    (result, buf)
+}
+
+/// Append `val` to `buf` as unsigned LEB128, returning the number of bytes
+/// written along with the buffer.
+#[cfg(not(feature = "signed-only"))]
+pub fn write_unsigned<B: BufMut>(mut buf: B, val: u64) -> (Result<usize, write::Error>, B) {
+    let result = buf.write_unsigned_with(val, &Config::new());
+    (result, buf)
+}
+
+/// Append every value in `vals` to `buf` as signed LEB128, returning the
+/// total number of bytes written along with the buffer.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn write_signed_iter<B, I>(mut buf: B, vals: I) -> (Result<usize, write::Error>, B)
+    where B: BufMut,
+          I: IntoIterator<Item = i64>
+{
+    let result = vals.into_iter()
+        .try_fold(0, |total, val| Ok(total + buf.write_signed_with(val, &Config::new())?));
+    (result, buf)
+}
+
+/// Append every value in `vals` to `buf` as unsigned LEB128, returning the
+/// total number of bytes written along with the buffer.
+#[cfg(not(feature = "signed-only"))]
+pub fn write_unsigned_iter<B, I>(mut buf: B, vals: I) -> (Result<usize, write::Error>, B)
+    where B: BufMut,
+          I: IntoIterator<Item = u64>
+{
+    let result = vals.into_iter()
+        .try_fold(0, |total, val| Ok(total + buf.write_unsigned_with(val, &Config::new())?));
+    (result, buf)
+}
+
+/// Decode the signed LEB128 number at `offset` in `buf`, returning it and its
+/// length along with the untouched buffer.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn read_signed<B: AsRef<[u8]>>(buf: B, offset: usize) -> (Result<(i64, usize), Error>, B) {
+    let result = decode_signed_at(buf.as_ref(), offset);
+    (result, buf)
+}
+
+/// Decode the unsigned LEB128 number at `offset` in `buf`, returning it and
+/// its length along with the untouched buffer.
+#[cfg(not(feature = "signed-only"))]
+pub fn read_unsigned<B: AsRef<[u8]>>(buf: B, offset: usize) -> (Result<(u64, usize), Error>, B) {
+    let result = decode_unsigned_at(buf.as_ref(), offset);
+    (result, buf)
+}
@@ -0,0 +1,117 @@
+//! Parquet's RLE/bit-packed hybrid encoding, used for repetition and
+//! definition levels and for dictionary indices, is a series of runs, each
+//! introduced by an unsigned LEB128 header. Its lowest bit says whether the
+//! run repeats one value (`0`) or bit packs groups of eight values (`1`),
+//! and the rest counts the values or the groups. Dictionary indices are
+//! preceded by a single byte giving their bit width.
+//!
+//! These helpers parse and write the headers, leaving the values themselves
+//! to the Parquet reader.
+//!
+//! ```
+//! use nt_leb128::parquet::{decode_bit_width, decode_run_header, RunHeader};
+//!
+//! // A bit width of 3, then a run of 100 copies of 5, then 16 packed values.
+//! let page = [0x03, 0xc8, 0x01, 0x05, 0x05, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+//! let (bit_width, rest) = decode_bit_width(&page).unwrap();
+//! let (header, rest) = decode_run_header(rest).unwrap();
+//! assert_eq!(header, RunHeader::Rle { len: 100 });
+//! assert_eq!(header.payload_len(bit_width), 1);
+//! let (header, rest) = decode_run_header(&rest[1..]).unwrap();
+//! assert_eq!(header, RunHeader::BitPacked { groups: 2 });
+//! assert_eq!((header.len(), header.payload_len(bit_width)), (16, 6));
+//! assert_eq!(rest.len(), 6);
+//! ```
+
+use super::config::Config;
+use super::read::Error;
+use super::slice::encode_unsigned;
+use super::write;
+
+/// The header of one run of the hybrid encoding. Headers are limited to 32
+/// bits, as Parquet's writers produce them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunHeader {
+    /// A run of `len` copies of one value, which follows the header in
+    /// little endian order, in as many bytes as its bit width needs.
+    Rle {
+        /// The number of values in the run.
+        len: u32,
+    },
+    /// `groups` groups of eight values, bit packed into `groups * bit_width`
+    /// bytes that follow the header.
+    BitPacked {
+        /// The number of groups of eight values.
+        groups: u32,
+    },
+}
+
+impl RunHeader {
+    /// The number of values the run holds. A bit packed run at the end of
+    /// the data may be padded beyond the values actually present.
+    pub fn len(&self) -> usize {
+        match *self {
+            RunHeader::Rle { len } => len as usize,
+            RunHeader::BitPacked { groups } => groups as usize * 8,
+        }
+    }
+
+    /// Whether the run holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of bytes after the header that the run occupies, for
+    /// values `bit_width` bits wide.
+    pub fn payload_len(&self, bit_width: u8) -> usize {
+        match *self {
+            RunHeader::Rle { .. } => (bit_width as usize).div_ceil(8),
+            RunHeader::BitPacked { groups } => groups as usize * bit_width as usize,
+        }
+    }
+}
+
+/// The rules for run headers: unsigned, at most 32 bits.
+const HEADER: Config = Config::new().bits(32);
+
+/// Decode the run header at the start of `input`, returning it along with the
+/// rest of `input`.
+///
+/// Fails with `Error::Overflow` if the header is wider than 32 bits.
+pub fn decode_run_header(input: &[u8]) -> Result<(RunHeader, &[u8]), Error> {
+    let (indicator, len) = HEADER.decode_unsigned(input)?;
+    let count = (indicator >> 1) as u32;
+    let header = if indicator & 1 == 0 {
+        RunHeader::Rle { len: count }
+    } else {
+        RunHeader::BitPacked { groups: count }
+    };
+    Ok((header, &input[len..]))
+}
+
+/// Encode `header` at the front of `buf`, returning the part of `buf` after
+/// it.
+///
+/// Fails with `write::Error::OutOfRange` if the count doesn't fit in the 31
+/// bits a header has for it, and with `write::Error::InsufficientSpace`,
+/// leaving `buf` untouched, if the header doesn't fit.
+pub fn encode_run_header(buf: &mut [u8], header: RunHeader) -> Result<&mut [u8], write::Error> {
+    let indicator = match header {
+        RunHeader::Rle { len } => (len as u64) << 1,
+        RunHeader::BitPacked { groups } => (groups as u64) << 1 | 1,
+    };
+    encode_unsigned(buf, HEADER.encodable_unsigned(indicator)?)
+}
+
+/// Decode the single byte bit width that precedes dictionary indices,
+/// returning it along with the rest of `input`.
+///
+/// Fails with `Error::Overflow` if the width is greater than 32, the widest
+/// index Parquet allows.
+pub fn decode_bit_width(input: &[u8]) -> Result<(u8, &[u8]), Error> {
+    match input.split_first() {
+        Some((&width, _)) if width > 32 => Err(Error::Overflow),
+        Some((&width, rest)) => Ok((width, rest)),
+        None => Err(Error::Incomplete),
+    }
+}
@@ -0,0 +1,28 @@
+//! ```
+//! # #[cfg(not(feature = "signed-only"))] {
+//! use nt_leb128::prelude::*;
+//!
+//! let mut buf = Vec::new();
+//! buf.write_unsigned(624485).expect("Should write number");
+//! let mut readable = &buf[..];
+//! assert_eq!(readable.read_unsigned_decoded().expect("Should read number").value, 624485);
+//! # }
+//! ```
+
+pub use super::read::{Decoded, Leb128Read};
+#[cfg(feature = "bytes")]
+pub use super::read::Take;
+#[cfg(all(any(feature = "bytes", feature = "bytes-1"), not(feature = "signed-only")))]
+pub use super::read::Leb128ReadShared;
+pub use super::write::{BudgetedWriter, CountingWriter, Leb128Write};
+#[cfg(feature = "alloc")]
+pub use super::write::Leb128Writer;
+pub use super::Leb128Codec;
+pub use super::{Leb128Decode, Leb128Encode};
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+pub use super::lazy::{EncodeBuf, LazyUleb};
+#[cfg(not(feature = "unsigned-only"))]
+pub use super::types::Sleb128;
+#[cfg(not(feature = "signed-only"))]
+pub use super::types::Uleb128;
+pub use super::deque::DequeReader;
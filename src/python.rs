@@ -0,0 +1,107 @@
+//! The module is importable as `nt_leb128` once the library is built as an
+//! extension module:
+//!
+//! ```text
+//! cargo rustc --release --features python,pyo3/extension-module --lib --crate-type cdylib
+//! cp target/release/libnt_leb128.so nt_leb128.so
+//! ```
+//!
+//! ```text
+//! >>> import nt_leb128
+//! >>> nt_leb128.encode_unsigned(624485)
+//! b'\xe5\x8e&'
+//! >>> nt_leb128.decode_unsigned_all(b'\x02\x7f\x80\x01')
+//! [2, 127, 128]
+//! ```
+//!
+//! Decoding failures are raised as `ValueError`s carrying the message of the
+//! underlying [`read::Error`](../read/enum.Error.html).
+
+use super::read;
+use super::scratch;
+use super::slice::{self, decode_all};
+use super::write::{put_signed, put_unsigned};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+fn value_error(err: read::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// Encode `val` as unsigned LEB128.
+#[pyfunction]
+pub fn encode_unsigned(py: Python<'_>, val: u64) -> Bound<'_, PyBytes> {
+    encode_unsigned_all(py, vec![val])
+}
+
+/// Encode `val` as signed LEB128.
+#[pyfunction]
+pub fn encode_signed(py: Python<'_>, val: i64) -> Bound<'_, PyBytes> {
+    encode_signed_all(py, vec![val])
+}
+
+/// Decode the unsigned LEB128 value at the start of `data`, returning it
+/// along with the number of bytes it occupied.
+#[pyfunction]
+pub fn decode_unsigned(data: &[u8]) -> PyResult<(u64, usize)> {
+    read::decode_unsigned(data.iter().copied()).map_err(value_error)
+}
+
+/// Decode the signed LEB128 value at the start of `data`, returning it along
+/// with the number of bytes it occupied.
+#[pyfunction]
+pub fn decode_signed(data: &[u8]) -> PyResult<(i64, usize)> {
+    read::decode_signed(data.iter().copied()).map_err(value_error)
+}
+
+/// Encode every value in `vals` as unsigned LEB128, back to back.
+#[pyfunction]
+pub fn encode_unsigned_all(py: Python<'_>, vals: Vec<u64>) -> Bound<'_, PyBytes> {
+    scratch::with_vec(|out| {
+        for val in vals {
+            put_unsigned(out, val);
+        }
+        PyBytes::new(py, out)
+    })
+}
+
+/// Encode every value in `vals` as signed LEB128, back to back.
+#[pyfunction]
+pub fn encode_signed_all(py: Python<'_>, vals: Vec<i64>) -> Bound<'_, PyBytes> {
+    scratch::with_vec(|out| {
+        for val in vals {
+            put_signed(out, val);
+        }
+        PyBytes::new(py, out)
+    })
+}
+
+/// Decode `data` as a sequence of unsigned LEB128 values, all of which must
+/// be complete.
+#[pyfunction]
+pub fn decode_unsigned_all(data: &[u8]) -> PyResult<Vec<u64>> {
+    decode_all(data, slice::decode_unsigned).map_err(value_error)
+}
+
+/// Decode `data` as a sequence of signed LEB128 values, all of which must be
+/// complete.
+#[pyfunction]
+pub fn decode_signed_all(data: &[u8]) -> PyResult<Vec<i64>> {
+    decode_all(data, slice::decode_signed).map_err(value_error)
+}
+
+/// The `nt_leb128` Python module.
+#[pymodule]
+#[pyo3(name = "nt_leb128")]
+pub fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(encode_unsigned, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_signed, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_unsigned, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_signed, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_unsigned_all, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_signed_all, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_unsigned_all, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_signed_all, m)?)?;
+    Ok(())
+}
@@ -1,17 +1,315 @@
-use super::{CONTINUATION_BIT, SIGN_BIT, low_bits_of_byte};
+use super::{CONTINUATION_BIT, io_error, low_bits_of_byte, metrics, raw};
+use super::buf::Source;
+use super::seq::Seq;
+use super::config::{Config, DEFAULT_FLAVORED};
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+use super::codec::MAX_PREALLOC;
+#[cfg(feature = "bytes")]
+use super::buf::Bytes05;
+#[cfg(not(feature = "signed-only"))]
+use std::convert::TryFrom;
 use std::fmt;
-use std::io;
+use std::io::{self, IoSlice};
+#[cfg(feature = "bytes")]
 use bytes::buf::Buf;
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+use bytes::{Bytes, BytesMut};
+
+/// The old name of [`Leb128Read`](trait.Leb128Read.html), kept for one
+/// release.
+#[deprecated(note = "renamed to `Leb128Read`")]
+pub use self::Leb128Read as LEB128Read;
 
 /// Trait for reading signed and unsigned LEB128 encoded numbers
-pub trait LEB128Read {
+pub trait Leb128Read {
     /// Read a signed LEB128 number from the given `std::io::Read`able and
     /// return it or an error if reading failed.
+    #[cfg(not(feature = "unsigned-only"))]
     fn read_signed(&mut self) -> Result<(i64, usize), Error>;
 
     /// Read an unsigned LEB128 number from the given `std::io::Read`able and
     /// return it or an error if reading failed.
+    #[cfg(not(feature = "signed-only"))]
     fn read_unsigned(&mut self) -> Result<(u64, usize), Error>;
+
+    /// Read an unsigned LEB128 number that must fit in `bits` bits, such as a
+    /// 20 bit DWARF index or a 33 bit WebAssembly value.
+    ///
+    /// Fails with `Error::Overflow` at the first byte carrying a bit beyond
+    /// the limit, including an encoding padded with more bytes than `bits`
+    /// needs, rather than only at 64 bits. An overflowing encoding is
+    /// consumed up to its terminating byte, as with `read_unsigned`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is 0 or greater than 64.
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_with_bit_limit(&mut self, bits: u32) -> Result<(u64, usize), Error>;
+
+    /// Read an unsigned LEB128 number under the rules of `config`.
+    ///
+    /// An encoding that breaks them is consumed up to its terminating byte,
+    /// as with `read_unsigned`.
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_with(&mut self, config: &Config) -> Result<(u64, usize), Error>;
+
+    /// Read a signed LEB128 number under the rules of `config`.
+    ///
+    /// An encoding that breaks them is consumed up to its terminating byte,
+    /// as with `read_signed`.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn read_signed_with(&mut self, config: &Config) -> Result<(i64, usize), Error>;
+
+    /// Decode the next signed LEB128 number without consuming it, returning
+    /// it along with the number of bytes it occupies.
+    ///
+    /// This only sees the bytes the buffer exposes through
+    /// `Buf::bytes_vectored`, which for most buffers is everything remaining.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn peek_signed(&self) -> Result<(i64, usize), Error>;
+
+    /// Decode the next unsigned LEB128 number without consuming it, returning
+    /// it along with the number of bytes it occupies.
+    ///
+    /// This only sees the bytes the buffer exposes through
+    /// `Buf::bytes_vectored`, which for most buffers is everything remaining.
+    #[cfg(not(feature = "signed-only"))]
+    fn peek_unsigned(&self) -> Result<(u64, usize), Error>;
+
+    /// The number of bytes the next LEB128 number occupies, found by scanning
+    /// for its terminating byte without decoding or consuming it.
+    ///
+    /// Like `skip_n`, this only enforces the ten byte length limit, so it
+    /// measures signed and unsigned numbers alike. It sees the same bytes as
+    /// `peek_unsigned`.
+    fn next_len(&self) -> Result<usize, Error>;
+
+    /// Read an optional unsigned number written by
+    /// `Leb128Write::write_option_unsigned`: a one byte presence tag, then
+    /// the number if the tag is `0x01`. Returns the number and the total
+    /// number of bytes read, including the tag.
+    ///
+    /// Fails with an `InvalidData` error if the tag is neither `0x00` nor
+    /// `0x01`, including when it has its continuation bit set. Nothing is
+    /// consumed when that happens.
+    #[cfg(not(feature = "signed-only"))]
+    fn read_option_unsigned(&mut self) -> Result<(Option<u64>, usize), Error>;
+
+    /// Read a decimal written by `Leb128Write::write_decimal`: its mantissa
+    /// and then its base 10 exponent, both as signed LEB128, so the value is
+    /// `mantissa * 10^exponent`. Returns both and the total number of bytes
+    /// read.
+    ///
+    /// Fails with `Error::Overflow` if the exponent doesn't fit in an `i8`.
+    ///
+    /// ```
+    /// use nt_leb128::read::Leb128Read;
+    ///
+    /// // 12.34 as 1234e-2.
+    /// let mut readable = &[0xd2, 0x09, 0x7e][..];
+    /// assert_eq!(readable.read_decimal().unwrap(), ((1234, -2), 3));
+    /// assert!(matches!((&[0x01, 0x80, 0x01][..]).read_decimal(),
+    ///                  Err(nt_leb128::read::Error::Overflow)));
+    /// ```
+    #[cfg(not(feature = "unsigned-only"))]
+    fn read_decimal(&mut self) -> Result<((i64, i8), usize), Error> {
+        let (mantissa, mantissa_len) = self.read_signed()?;
+        let (exponent, exponent_len) = self.read_signed_with(&Config::new().bits(8))?;
+        Ok(((mantissa, exponent as i8), mantissa_len + exponent_len))
+    }
+
+    /// Advance past the next signed LEB128 number without decoding it,
+    /// returning the number of bytes skipped.
+    ///
+    /// This accepts exactly the encodings `read_signed` accepts, but only
+    /// scans for the terminating byte rather than computing the value.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn skip_signed(&mut self) -> Result<usize, Error>;
+
+    /// Advance past the next unsigned LEB128 number without decoding it,
+    /// returning the number of bytes skipped.
+    ///
+    /// This accepts exactly the encodings `read_unsigned` accepts, but only
+    /// scans for the terminating byte rather than computing the value.
+    #[cfg(not(feature = "signed-only"))]
+    fn skip_unsigned(&mut self) -> Result<usize, Error>;
+
+    /// Advance past the next `n` LEB128 numbers without decoding them,
+    /// returning the number of bytes skipped.
+    ///
+    /// This scans each chunk of the buffer for terminating bytes, so it only
+    /// enforces the ten byte length limit (like `count_values`) and skips
+    /// signed and unsigned numbers alike.
+    fn skip_n(&mut self, n: usize) -> Result<usize, Error>;
+
+    /// Read two consecutive unsigned LEB128 numbers, such as the code and tag
+    /// that open a DWARF abbreviation.
+    #[cfg(not(feature = "signed-only"))]
+    #[deprecated(note = "use `read_unsigned_pair`, which also returns the length")]
+    fn read_uleb_pair(&mut self) -> Result<(u64, u64), Error> {
+        self.read_unsigned_pair().map(|(pair, _)| pair)
+    }
+
+    /// Read two consecutive unsigned LEB128 numbers, such as the code and tag
+    /// that open a DWARF abbreviation, returning them along with the number
+    /// of bytes they occupied together.
+    ///
+    /// When both are in the buffer's current chunk, they are decoded in a
+    /// single pass over it. Otherwise they are read one at a time, so if the
+    /// second fails to decode, the first has still been consumed.
+    ///
+    /// ```
+    /// use nt_leb128::read::Leb128Read;
+    ///
+    /// let mut readable = &[0x02, 0xe5, 0x8e, 0x26, 0x7f][..];
+    /// assert_eq!(readable.read_unsigned_pair().unwrap(), ((2, 624485), 4));
+    /// ```
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_pair(&mut self) -> Result<((u64, u64), usize), Error>;
+
+    /// Like `read_unsigned_pair`, but reading three numbers, such as the
+    /// operands of a DWARF line program opcode.
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_triple(&mut self) -> Result<((u64, u64, u64), usize), Error>;
+
+    /// Read a byte string prefixed with its length as unsigned LEB128.
+    ///
+    /// Requires the `alloc` feature.
+    ///
+    /// Fails with `Error::LengthExceeded`, before reading or allocating for
+    /// the payload, if the length is greater than `max_len`.
+    #[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+    fn read_bytes(&mut self, max_len: usize) -> Result<Vec<u8>, Error>;
+
+    /// Read a UTF-8 string prefixed with its length in bytes as unsigned
+    /// LEB128.
+    ///
+    /// Requires the `alloc` feature.
+    ///
+    /// Fails with `Error::LengthExceeded`, before reading or allocating for
+    /// the payload, if the length is greater than `max_len`, and with an
+    /// `InvalidData` error if the payload isn't valid UTF-8.
+    #[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+    fn read_string(&mut self, max_len: usize) -> Result<String, Error> {
+        let bytes = self.read_bytes(max_len)?;
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+    }
+
+    /// Read an enum discriminant written by `Leb128Write::write_enum`,
+    /// converting it with `T`'s `TryFrom<u64>` implementation.
+    ///
+    /// Fails with `Error::UnknownDiscriminant` if the conversion does.
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use nt_leb128::prelude::*;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// #[repr(u64)]
+    /// enum Op { Nop = 0, Push = 300 }
+    ///
+    /// impl TryFrom<u64> for Op {
+    ///     type Error = ();
+    ///     fn try_from(val: u64) -> Result<Self, ()> {
+    ///         match val {
+    ///             0 => Ok(Op::Nop),
+    ///             300 => Ok(Op::Push),
+    ///             _ => Err(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut readable = &[0xac, 0x02, 0x01][..];
+    /// assert_eq!(readable.read_enum::<Op>().unwrap(), Op::Push);
+    /// assert!(readable.read_enum::<Op>().is_err());
+    /// ```
+    #[cfg(not(feature = "signed-only"))]
+    fn read_enum<T: TryFrom<u64>>(&mut self) -> Result<T, Error>
+        where Self: Sized
+    {
+        let (val, _) = self.read_unsigned()?;
+        T::try_from(val).map_err(|_| Error::UnknownDiscriminant(val))
+    }
+
+    /// Read signed numbers prefixed with their count as unsigned LEB128, as
+    /// written by `Leb128Write::write_vec_signed`.
+    ///
+    /// Requires the `alloc` feature.
+    ///
+    /// Fails with `Error::LengthExceeded`, before reading or allocating for
+    /// the numbers, if the count is greater than `max_len`.
+    #[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+    fn read_vec_signed(&mut self, max_len: usize) -> Result<Vec<i64>, Error> {
+        let len = read_count(self, max_len)?;
+        let mut vals = Vec::with_capacity(len.min(MAX_PREALLOC));
+        for _ in 0..len {
+            vals.push(self.read_signed()?.0);
+        }
+        Ok(vals)
+    }
+
+    /// Read unsigned numbers prefixed with their count as unsigned LEB128, as
+    /// written by `Leb128Write::write_vec_unsigned`.
+    ///
+    /// Requires the `alloc` feature.
+    ///
+    /// Fails with `Error::LengthExceeded`, before reading or allocating for
+    /// the numbers, if the count is greater than `max_len`.
+    #[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+    fn read_vec_unsigned(&mut self, max_len: usize) -> Result<Vec<u64>, Error> {
+        let len = read_count(self, max_len)?;
+        let mut vals = Vec::with_capacity(len.min(MAX_PREALLOC));
+        for _ in 0..len {
+            vals.push(self.read_unsigned()?.0);
+        }
+        Ok(vals)
+    }
+
+    /// Like `read_signed`, but returns the value and its length as a named
+    /// `Decoded` rather than a tuple.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn read_signed_decoded(&mut self) -> Result<Decoded<i64>, Error> {
+        self.read_signed().map(Decoded::from)
+    }
+
+    /// Like `read_unsigned`, but returns the value and its length as a named
+    /// `Decoded` rather than a tuple.
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_decoded(&mut self) -> Result<Decoded<u64>, Error> {
+        self.read_unsigned().map(Decoded::from)
+    }
+
+    /// Start reading a run of differently typed numbers, such as the fields
+    /// of a header, with one `?` per field. See `Seq`.
+    fn seq(&mut self) -> Seq<'_, Self, ()> {
+        Seq::new(self)
+    }
+}
+
+/// A decoded number along with the number of bytes its encoding occupied.
+///
+/// More fields may be added in the future, so this can't be constructed or
+/// destructured exhaustively outside of this crate; convert from and to
+/// `(value, len)` tuples instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Decoded<T> {
+    /// The decoded number.
+    pub value: T,
+    /// The number of bytes the encoding occupied.
+    pub len: usize,
+}
+
+impl<T> From<(T, usize)> for Decoded<T> {
+    fn from((value, len): (T, usize)) -> Self {
+        Decoded { value, len }
+    }
+}
+
+impl<T> From<Decoded<T>> for (T, usize) {
+    fn from(decoded: Decoded<T>) -> Self {
+        (decoded.value, decoded.len)
+    }
 }
 
 /// An enumeration of the possible errors that can occur when reading a
@@ -20,8 +318,43 @@ pub trait LEB128Read {
 pub enum Error {
     /// There was an underlying IO error.
     IoError(io::Error),
+    /// The input ended before the number did. Unlike the other errors, this
+    /// one may go away if more input arrives; see `is_incomplete`.
+    Incomplete,
     /// The number being read is larger than can be represented.
     Overflow,
+    /// The encoding is longer than necessary, and only the shortest encoding
+    /// of each number was allowed.
+    NonCanonical,
+    /// A length prefix is larger than the maximum allowed.
+    LengthExceeded {
+        /// The length that was read.
+        len: u64,
+        /// The maximum length allowed.
+        max: usize,
+    },
+    /// A number read as an enum discriminant matches none of the enum's
+    /// variants.
+    UnknownDiscriminant(u64),
+}
+
+impl Error {
+    /// Whether the input ended partway through a number (or before it
+    /// began), so a streaming caller can wait for more bytes and retry, rather
+    /// than the input being malformed. An `IoError` from the underlying
+    /// source is never incomplete, even an `UnexpectedEof` one.
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "signed-only"))] {
+    /// use nt_leb128::read::Leb128Read;
+    ///
+    /// assert!((&[0x80, 0x80][..]).read_unsigned().unwrap_err().is_incomplete());
+    /// assert!(!(&[0xff; 11][..]).read_unsigned().unwrap_err().is_incomplete());
+    /// # }
+    /// ```
+    pub fn is_incomplete(&self) -> bool {
+        matches!(*self, Error::Incomplete)
+    }
 }
 
 impl From<io::Error> for Error {
@@ -30,93 +363,775 @@ impl From<io::Error> for Error {
     }
 }
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f,
-               "leb128::read::Error: {}",
-               ::std::error::Error::description(self))
+/// An `IoError` converts back to the `io::Error` it holds and `Incomplete`
+/// becomes an `UnexpectedEof` error; anything else becomes an `InvalidData`
+/// error, carrying the original error with the `alloc` feature.
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::IoError(e) => e,
+            Error::Incomplete => io_error(io::ErrorKind::UnexpectedEof, "Not enough data"),
+            e if cfg!(feature = "alloc") => io::Error::new(io::ErrorKind::InvalidData, e),
+            _ => io::ErrorKind::InvalidData.into(),
+        }
     }
 }
 
-impl ::std::error::Error for Error {
-    fn description(&self) -> &str {
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match *self {
-            Error::IoError(ref e) => e.description(),
-            Error::Overflow => "The number being read is larger than can be represented",
+            Error::IoError(ref e) => write!(f, "leb128::read::Error: {}", e),
+            Error::Incomplete => {
+                write!(f, "leb128::read::Error: The input ended before the number did")
+            }
+            Error::Overflow => {
+                write!(f,
+                       "leb128::read::Error: The number being read is larger than can be represented")
+            }
+            Error::NonCanonical => {
+                write!(f,
+                       "leb128::read::Error: The encoding is longer than necessary")
+            }
+            Error::LengthExceeded { len, max } => {
+                write!(f,
+                       "leb128::read::Error: The length {} is larger than the maximum of {}",
+                       len,
+                       max)
+            }
+            Error::UnknownDiscriminant(val) => {
+                write!(f, "leb128::read::Error: {} is not a known discriminant", val)
+            }
         }
     }
+}
 
-    fn cause(&self) -> Option<&::std::error::Error> {
+impl ::std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
         match *self {
             Error::IoError(ref e) => Some(e),
-            Error::Overflow => None,
+            Error::Incomplete |
+            Error::Overflow |
+            Error::NonCanonical |
+            Error::LengthExceeded { .. } |
+            Error::UnknownDiscriminant(_) => None,
         }
     }
 }
 
-impl<R> LEB128Read for R
+#[cfg(feature = "bytes")]
+impl<R> Leb128Read for R
     where R: Buf
 {
+    #[cfg(not(feature = "unsigned-only"))]
     fn read_signed(&mut self) -> Result<(i64, usize), Error> {
-        let mut result = 0;
-        let mut shift = 0;
-        let size = 64;
-        let mut byte;
-        let mut bytes_read = 0;
-
-        loop {
-            if !self.has_remaining() {
-                return Err(Error::IoError(io::Error::new(io::ErrorKind::UnexpectedEof, "Not enough data")))
-            }
+        read_signed_from(&mut Bytes05(self))
+    }
 
-            byte = self.get_u8();
-            bytes_read += 1;
-            if shift == 63 && byte != 0x00 && byte != 0x7f {
-                return Err(Error::Overflow);
-            }
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned(&mut self) -> Result<(u64, usize), Error> {
+        read_unsigned_from(&mut Bytes05(self))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_with_bit_limit(&mut self, bits: u32) -> Result<(u64, usize), Error> {
+        read_unsigned_with_bit_limit_from(&mut Bytes05(self), bits)
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_with(&mut self, config: &Config) -> Result<(u64, usize), Error> {
+        read_chunked(&mut Bytes05(self), |bytes| config.decode_unsigned(bytes))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn read_signed_with(&mut self, config: &Config) -> Result<(i64, usize), Error> {
+        read_chunked(&mut Bytes05(self), |bytes| config.decode_signed(bytes))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn peek_signed(&self) -> Result<(i64, usize), Error> {
+        let mut chunks = [IoSlice::new(&[]); 10];
+        let n = self.bytes_vectored(&mut chunks);
+        peek_signed_in(&chunks[..n])
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn peek_unsigned(&self) -> Result<(u64, usize), Error> {
+        let mut chunks = [IoSlice::new(&[]); 10];
+        let n = self.bytes_vectored(&mut chunks);
+        peek_unsigned_in(&chunks[..n])
+    }
+
+    fn next_len(&self) -> Result<usize, Error> {
+        let mut chunks = [IoSlice::new(&[]); 10];
+        let n = self.bytes_vectored(&mut chunks);
+        scan(chunks[..n].iter().flat_map(|c| c.iter().copied()), raw::is_terminal)
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn skip_signed(&mut self) -> Result<usize, Error> {
+        skip_signed_from(&mut Bytes05(self))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn skip_unsigned(&mut self) -> Result<usize, Error> {
+        skip_unsigned_from(&mut Bytes05(self))
+    }
+
+    fn skip_n(&mut self, n: usize) -> Result<usize, Error> {
+        skip_n(&mut Bytes05(self), n)
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn read_option_unsigned(&mut self) -> Result<(Option<u64>, usize), Error> {
+        read_option_unsigned_from(&mut Bytes05(self))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_pair(&mut self) -> Result<((u64, u64), usize), Error> {
+        let ([first, second], len) = read_unsigned_group(&mut Bytes05(self))?;
+        Ok(((first, second), len))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn read_unsigned_triple(&mut self) -> Result<((u64, u64, u64), usize), Error> {
+        let ([first, second, third], len) = read_unsigned_group(&mut Bytes05(self))?;
+        Ok(((first, second, third), len))
+    }
+
+    #[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+    fn read_bytes(&mut self, max_len: usize) -> Result<Vec<u8>, Error> {
+        read_bytes(&mut Bytes05(self), max_len)
+    }
+}
+
+/// Readers backed by reference counted storage, which can hand out parts of
+/// it without copying.
+#[cfg(all(any(feature = "bytes", feature = "bytes-1"), not(feature = "signed-only")))]
+pub trait Leb128ReadShared: Leb128Read {
+    /// The type of the parts handed out.
+    type Shared;
 
-            let low_bits = low_bits_of_byte(byte) as i64;
-            result |= low_bits << shift;
-            shift += 7;
+    /// Read a byte string prefixed with its length as unsigned LEB128,
+    /// returning it as a view of the reader's storage rather than a copy.
+    ///
+    /// Fails with `Error::LengthExceeded` if the length is greater than
+    /// `max_len`, and with `Error::Incomplete` if the byte string doesn't
+    /// fit in what remains. Nothing is consumed when either happens.
+    ///
+    /// ```
+    /// # #[cfg(feature = "bytes")] {
+    /// use nt_leb128::read::Leb128ReadShared;
+    ///
+    /// let mut buf = bytes::Bytes::from(&b"\x02hi\x01!"[..]);
+    /// assert_eq!(buf.read_length_prefixed_bytes(16).unwrap(), &b"hi"[..]);
+    /// assert_eq!(buf, &b"\x01!"[..]);
+    /// # }
+    /// ```
+    fn read_length_prefixed_bytes(&mut self, max_len: usize) -> Result<Self::Shared, Error>;
+}
+
+/// Check the length prefix at the front of `reader`, which has `remaining`
+/// bytes, returning the lengths of the prefix and of the byte string.
+#[cfg(all(any(feature = "bytes", feature = "bytes-1"), not(feature = "signed-only")))]
+pub(crate) fn shared_prefix<R: Leb128Read + ?Sized>(reader: &R,
+                                                    remaining: usize,
+                                                    max_len: usize)
+                                                    -> Result<(usize, usize), Error> {
+    let (len, prefix) = reader.peek_unsigned()?;
+    if len > max_len as u64 {
+        return Err(Error::LengthExceeded { len, max: max_len });
+    }
+    if len > (remaining - prefix) as u64 {
+        return Err(Error::Incomplete);
+    }
+    Ok((prefix, len as usize))
+}
+
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+impl Leb128ReadShared for Bytes {
+    type Shared = Bytes;
+
+    fn read_length_prefixed_bytes(&mut self, max_len: usize) -> Result<Bytes, Error> {
+        let (prefix, len) = shared_prefix(self, self.len(), max_len)?;
+        self.advance(prefix);
+        Ok(self.split_to(len))
+    }
+}
+
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+impl Leb128ReadShared for BytesMut {
+    type Shared = Bytes;
+
+    fn read_length_prefixed_bytes(&mut self, max_len: usize) -> Result<Bytes, Error> {
+        let (prefix, len) = shared_prefix(self, self.len(), max_len)?;
+        self.advance(prefix);
+        Ok(self.split_to(len).freeze())
+    }
+}
+
+/// Without the `bytes` feature, byte slices are read directly.
+#[cfg(not(feature = "bytes"))]
+impl Leb128Read for &[u8] {
+    forward_read!();
+}
+
+/// The maximum number of bytes a 64 bit number occupies.
+pub(crate) const MAX_LEN: usize = nt_leb128_core::MAX_LEN_U64;
+
+/// Whether the tenth byte of a signed encoding carries no bits beyond 64.
+pub(crate) fn signed_last_byte_fits(byte: u8) -> bool {
+    byte == 0x00 || byte == 0x7f
+}
+
+/// Whether the tenth byte of an unsigned encoding carries no bits beyond 64.
+pub(crate) fn unsigned_last_byte_fits(byte: u8) -> bool {
+    byte == 0x00 || byte == 0x01
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) fn read_signed_from<S: Source>(src: &mut S) -> Result<(i64, usize), Error> {
+    if DEFAULT_FLAVORED {
+        return read_chunked(src, |bytes| Config::DEFAULT.decode_signed(bytes));
+    }
+    read_chunked(src, |bytes| decode_signed(bytes.iter().copied()))
+}
+
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn read_unsigned_from<S: Source>(src: &mut S) -> Result<(u64, usize), Error> {
+    if DEFAULT_FLAVORED {
+        return read_chunked(src, |bytes| Config::DEFAULT.decode_unsigned(bytes));
+    }
+    read_chunked(src, |bytes| decode_unsigned(bytes.iter().copied()))
+}
+
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn read_unsigned_with_bit_limit_from<S: Source>(src: &mut S,
+                                                           bits: u32)
+                                                           -> Result<(u64, usize), Error> {
+    assert!((1..=64).contains(&bits), "bit limit must be between 1 and 64");
+    read_chunked(src, |bytes| decode_unsigned_with_bit_limit(bytes.iter().copied(), bits))
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+#[cfg_attr(all(feature = "bytes", not(feature = "bytes-1")), allow(dead_code))]
+pub(crate) fn peek_signed_from<S: Source>(src: &S) -> Result<(i64, usize), Error> {
+    let mut chunks = [IoSlice::new(&[]); MAX_LEN];
+    let n = src.chunks_vectored(&mut chunks);
+    peek_signed_in(&chunks[..n])
+}
 
+#[cfg(not(feature = "signed-only"))]
+#[cfg_attr(all(feature = "bytes", not(feature = "bytes-1")), allow(dead_code))]
+pub(crate) fn peek_unsigned_from<S: Source>(src: &S) -> Result<(u64, usize), Error> {
+    let mut chunks = [IoSlice::new(&[]); MAX_LEN];
+    let n = src.chunks_vectored(&mut chunks);
+    peek_unsigned_in(&chunks[..n])
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+fn peek_signed_in(chunks: &[IoSlice<'_>]) -> Result<(i64, usize), Error> {
+    let bytes = chunks.iter().flat_map(|c| c.iter().copied());
+    if DEFAULT_FLAVORED {
+        let (window, n) = peek_window(bytes);
+        return Config::DEFAULT.decode_signed(&window[..n]);
+    }
+    decode_signed(bytes)
+}
+
+#[cfg(not(feature = "signed-only"))]
+fn peek_unsigned_in(chunks: &[IoSlice<'_>]) -> Result<(u64, usize), Error> {
+    let bytes = chunks.iter().flat_map(|c| c.iter().copied());
+    if DEFAULT_FLAVORED {
+        let (window, n) = peek_window(bytes);
+        return Config::DEFAULT.decode_unsigned(&window[..n]);
+    }
+    decode_unsigned(bytes)
+}
+
+/// Copy up to the first ten of `bytes`, the most an encoding can take, so
+/// that a `Config` can decode them.
+fn peek_window<I: Iterator<Item = u8>>(bytes: I) -> ([u8; MAX_LEN], usize) {
+    let mut window = [0; MAX_LEN];
+    let n = window.iter_mut().zip(bytes).map(|(slot, byte)| *slot = byte).count();
+    (window, n)
+}
+
+#[cfg_attr(all(feature = "bytes", not(feature = "bytes-1")), allow(dead_code))]
+pub(crate) fn next_len_from<S: Source>(src: &S) -> Result<usize, Error> {
+    let mut chunks = [IoSlice::new(&[]); MAX_LEN];
+    let n = src.chunks_vectored(&mut chunks);
+    scan(chunks[..n].iter().flat_map(|c| c.iter().copied()), raw::is_terminal)
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) fn skip_signed_from<S: Source>(src: &mut S) -> Result<usize, Error> {
+    if DEFAULT_FLAVORED {
+        return read_chunked(src, |bytes| Config::DEFAULT.decode_signed(bytes)).map(|(_, len)| len);
+    }
+    skip(src, signed_last_byte_fits)
+}
+
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn skip_unsigned_from<S: Source>(src: &mut S) -> Result<usize, Error> {
+    if DEFAULT_FLAVORED {
+        return read_chunked(src, |bytes| Config::DEFAULT.decode_unsigned(bytes))
+            .map(|(_, len)| len);
+    }
+    skip(src, unsigned_last_byte_fits)
+}
+
+fn skip<S: Source>(src: &mut S, last_byte_fits: fn(u8) -> bool) -> Result<usize, Error> {
+    // Fast path: the whole encoding is in the current chunk.
+    let chunk = src.chunk();
+    let window = &chunk[..chunk.len().min(MAX_LEN)];
+    let end = if OPT_SIZE { None } else { window.iter().position(|b| b & CONTINUATION_BIT == 0) };
+    if let Some(pos) = end {
+        if pos == MAX_LEN - 1 && !last_byte_fits(window[pos]) {
+            src.advance(MAX_LEN);
+            return Err(Error::Overflow);
+        }
+        src.advance(pos + 1);
+        return Ok(pos + 1);
+    }
+
+    let mut scratch = [0; MAX_LEN];
+    let n = gather(src, &mut scratch);
+    scan(scratch[..n].iter().copied(), last_byte_fits)
+}
+
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn read_option_unsigned_from<S: Source>(src: &mut S)
+                                                   -> Result<(Option<u64>, usize), Error> {
+    let present = match src.chunk().first() {
+        None => return Err(not_enough_data()),
+        Some(0x00) => false,
+        Some(0x01) => true,
+        Some(_) => return Err(Error::IoError(io_error(io::ErrorKind::InvalidData,
+                                                      "Invalid presence tag"))),
+    };
+    src.advance(1);
+    if !present {
+        return Ok((None, 1));
+    }
+    let (val, len) = read_unsigned_from(src)?;
+    Ok((Some(val), 1 + len))
+}
+
+pub(crate) fn skip_n<S: Source>(src: &mut S, mut n: usize) -> Result<usize, Error> {
+    let mut skipped = 0;
+    let mut run = 0;
+    while n > 0 {
+        let chunk = src.chunk();
+        if chunk.is_empty() {
+            return Err(not_enough_data());
+        }
+
+        let mut used = 0;
+        for &byte in chunk {
+            used += 1;
             if byte & CONTINUATION_BIT == 0 {
-                break;
+                run = 0;
+                n -= 1;
+                if n == 0 {
+                    break;
+                }
+            } else {
+                run += 1;
+                if run == MAX_LEN {
+                    src.advance(used);
+                    return Err(Error::Overflow);
+                }
             }
         }
+        src.advance(used);
+        skipped += used;
+    }
+    Ok(skipped)
+}
 
-        if shift < size && (SIGN_BIT & byte) == SIGN_BIT {
-            // Sign extend the result.
-            result |= !0 << shift;
+/// Read `N` consecutive unsigned numbers, returning them and their total
+/// length. Numbers before one that fails to decode stay consumed, whichever
+/// path is taken.
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn read_unsigned_group<S: Source, const N: usize>(src: &mut S)
+                                                             -> Result<([u64; N], usize), Error> {
+    let mut vals = [0; N];
+    let mut total = 0;
+
+    // Fast path: every number is in the current chunk, and there are no
+    // flavor rules to apply.
+    let chunk = src.chunk();
+    if !(DEFAULT_FLAVORED || OPT_SIZE) {
+        let mut lens = [0; N];
+        let whole = vals.iter_mut().zip(&mut lens).all(|(val, len)| {
+            match decode_unsigned(chunk[total..].iter().copied()) {
+                Ok((decoded, decoded_len)) => {
+                    *val = decoded;
+                    *len = decoded_len;
+                    total += decoded_len;
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+        if whole {
+            for &len in &lens {
+                metrics::record_decoded(&Ok(((), len)));
+            }
+            src.advance(total);
+            return Ok((vals, total));
         }
+        total = 0;
+    }
 
-        Ok((result, bytes_read))
+    for val in &mut vals {
+        let (decoded, len) = read_unsigned_from(src)?;
+        *val = decoded;
+        total += len;
     }
+    Ok((vals, total))
+}
 
-    fn read_unsigned(&mut self) -> Result<(u64, usize), Error> {
-        let mut result = 0;
-        let mut shift = 0;
-        let mut bytes_read = 0;
+/// Read a count prefix, checking it against `max_len`.
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+fn read_count<R: Leb128Read + ?Sized>(buf: &mut R, max_len: usize) -> Result<usize, Error> {
+    let (len, _) = buf.read_unsigned()?;
+    if len > max_len as u64 {
+        return Err(Error::LengthExceeded { len, max: max_len });
+    }
+    Ok(len as usize)
+}
 
-        loop {
-            if !self.has_remaining() {
-                return Err(Error::IoError(io::Error::new(io::ErrorKind::UnexpectedEof, "Not enough data")))
-            }
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+pub(crate) fn read_bytes<S: Source>(src: &mut S, max_len: usize) -> Result<Vec<u8>, Error> {
+    let (len, _) = read_unsigned_from(src)?;
+    if len > max_len as u64 {
+        return Err(Error::LengthExceeded { len, max: max_len });
+    }
+    let len = len as usize;
+    if len > src.remaining() {
+        return Err(not_enough_data());
+    }
+    let mut bytes = Vec::with_capacity(len);
+    while bytes.len() < len {
+        let chunk = src.chunk();
+        let n = chunk.len().min(len - bytes.len());
+        bytes.extend_from_slice(&chunk[..n]);
+        src.advance(n);
+    }
+    Ok(bytes)
+}
 
-            let byte = self.get_u8();
-            bytes_read += 1;
+/// Whether the `opt-size` feature asks for the fast paths to be left out, so
+/// that every read goes through `gather` and a single copy of the decoder.
+const OPT_SIZE: bool = cfg!(feature = "opt-size");
 
-            if shift == 63 && byte != 0x00 && byte != 0x01 {
-                return Err(Error::Overflow);
-            }
+pub(crate) fn read_chunked<S, T, F>(src: &mut S, decode: F) -> Result<(T, usize), Error>
+    where S: Source,
+          F: Fn(&[u8]) -> Result<(T, usize), Error>
+{
+    // Fast path: the whole encoding, or its overflowing tenth byte, is in the
+    // current chunk.
+    let chunk = src.chunk();
+    let window = &chunk[..chunk.len().min(MAX_LEN)];
+    let end = window.iter().position(|&b| raw::is_terminal(b)).map(|pos| pos + 1);
+    if !OPT_SIZE && (window.len() == MAX_LEN || end.is_some()) {
+        let result = decode(window);
+        // A failed decode consumes up to the terminating byte or the tenth
+        // byte, just like `gather` below.
+        src.advance(result.as_ref().map_or(end.unwrap_or(MAX_LEN), |&(_, len)| len));
+        metrics::record_decoded(&result);
+        return result;
+    }
 
-            let low_bits = low_bits_of_byte(byte) as u64;
-            result |= low_bits << shift;
+    let mut scratch = [0; MAX_LEN];
+    let n = gather(src, &mut scratch);
+    let result = decode(&scratch[..n]);
+    metrics::record_decoded(&result);
+    result
+}
 
-            if byte & CONTINUATION_BIT == 0 {
-                return Ok((result, bytes_read));
-            }
+/// Copy the encoding at the front of `src` into `scratch` a chunk at a time,
+/// consuming exactly the bytes copied: up to and including the terminating
+/// byte, or the tenth byte, or everything remaining, whichever comes first.
+/// Returns the number of bytes copied.
+fn gather<S: Source>(src: &mut S, scratch: &mut [u8; MAX_LEN]) -> usize {
+    let mut filled = 0;
+    while filled < MAX_LEN {
+        let chunk = src.chunk();
+        if chunk.is_empty() {
+            break;
+        }
+        let want = &chunk[..chunk.len().min(MAX_LEN - filled)];
+        let (take, done) = match want.iter().position(|b| b & CONTINUATION_BIT == 0) {
+            Some(pos) => (pos + 1, true),
+            None => (want.len(), false),
+        };
+        scratch[filled..filled + take].copy_from_slice(&want[..take]);
+        src.advance(take);
+        filled += take;
+        if done {
+            break;
+        }
+    }
+    filled
+}
+
+/// Find the length of the LEB128 encoding at the front of `bytes`, pulling
+/// only as many bytes as it occupies.
+pub(crate) fn scan<I>(bytes: I, last_byte_fits: fn(u8) -> bool) -> Result<usize, Error>
+    where I: IntoIterator<Item = u8>
+{
+    let mut bytes = bytes.into_iter();
+    for len in 1..=MAX_LEN {
+        let byte = bytes.next().ok_or_else(not_enough_data)?;
+        if len == MAX_LEN && !last_byte_fits(byte) {
+            return Err(Error::Overflow);
+        }
+        if byte & CONTINUATION_BIT == 0 {
+            return Ok(len);
+        }
+    }
+    unreachable!("the tenth byte either terminates or overflows")
+}
+
+pub(crate) fn not_enough_data() -> Error {
+    Error::Incomplete
+}
+
+/// Decode a signed LEB128 number from the front of `bytes`, pulling only as
+/// many bytes as the encoding occupies. Returns the number and that count.
+///
+/// This suits sources that produce bytes one at a time, such as
+/// decompressors and ring buffers; the rest of the iterator is left for the
+/// next value.
+///
+/// ```
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// use nt_leb128::read;
+///
+/// let mut bytes = vec![0x7e, 0xe5, 0x8e, 0x26].into_iter();
+/// assert_eq!(read::decode_signed(&mut bytes).unwrap(), (-2, 1));
+/// assert_eq!(read::decode_unsigned(&mut bytes).unwrap(), (624485, 3));
+/// assert!(read::decode_unsigned(&mut bytes).is_err());
+/// # }
+/// ```
+#[cfg(not(feature = "unsigned-only"))]
+pub fn decode_signed<I>(bytes: I) -> Result<(i64, usize), Error>
+    where I: IntoIterator<Item = u8>
+{
+    let mut bytes = bytes.into_iter();
+    let mut result = 0;
+    let mut shift = 0;
+    let mut byte;
+    let mut bytes_read = 0;
+
+    loop {
+        byte = bytes.next().ok_or_else(not_enough_data)?;
+        bytes_read += 1;
+        if shift == 63 && byte != 0x00 && byte != 0x7f {
+            return Err(Error::Overflow);
+        }
+
+        let low_bits = low_bits_of_byte(byte) as i64;
+        result |= low_bits << shift;
+        shift += 7;
+
+        if byte & CONTINUATION_BIT == 0 {
+            break;
+        }
+    }
+
+    Ok((raw::sign_extend(result, shift, byte), bytes_read))
+}
+
+/// Decode an unsigned LEB128 number from the front of `bytes`, pulling only as
+/// many bytes as the encoding occupies. Returns the number and that count.
+#[cfg(not(feature = "signed-only"))]
+pub fn decode_unsigned<I>(bytes: I) -> Result<(u64, usize), Error>
+    where I: IntoIterator<Item = u8>
+{
+    let mut bytes = bytes.into_iter();
+    let mut result = 0;
+    let mut shift = 0;
+    let mut bytes_read = 0;
+
+    loop {
+        let byte = bytes.next().ok_or_else(not_enough_data)?;
+        bytes_read += 1;
+
+        if shift == 63 && byte != 0x00 && byte != 0x01 {
+            return Err(Error::Overflow);
+        }
+
+        let low_bits = low_bits_of_byte(byte) as u64;
+        result |= low_bits << shift;
+
+        if byte & CONTINUATION_BIT == 0 {
+            return Ok((result, bytes_read));
+        }
+
+        shift += 7;
+    }
+}
+
+/// Like `decode_unsigned`, but fails with `Error::Overflow` at the first byte
+/// carrying a bit at or beyond position `bits`.
+#[cfg(not(feature = "signed-only"))]
+fn decode_unsigned_with_bit_limit<I>(bytes: I, bits: u32) -> Result<(u64, usize), Error>
+    where I: IntoIterator<Item = u8>
+{
+    let mut bytes = bytes.into_iter();
+    let mut result = 0;
+    let mut bytes_read = 0;
+
+    loop {
+        let byte = bytes.next().ok_or_else(not_enough_data)?;
+        let shift = raw::shift_of(bytes_read);
+        bytes_read += 1;
+
+        let low_bits = low_bits_of_byte(byte) as u64;
+        if shift >= bits || (bits - shift < 7 && low_bits >> (bits - shift) != 0) {
+            return Err(Error::Overflow);
+        }
+        result |= low_bits << shift;
+
+        if raw::is_terminal(byte) {
+            return Ok((result, bytes_read));
+        }
+    }
+}
+
+/// Like `decode_signed`, but for a fallible source of bytes, such as
+/// `std::io::Read::bytes`. The first error from `bytes` is returned as is;
+/// decoding errors are converted into `E`.
+///
+/// ```
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// use std::io::Read;
+/// use nt_leb128::read;
+///
+/// let mut bytes = (&[0x7e, 0xe5, 0x8e][..]).bytes();
+/// assert_eq!(read::try_decode_signed::<_, std::io::Error>(&mut bytes).unwrap(), (-2, 1));
+/// let err = read::try_decode_unsigned::<_, std::io::Error>(&mut bytes).unwrap_err();
+/// assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+/// # }
+/// ```
+#[cfg(not(feature = "unsigned-only"))]
+pub fn try_decode_signed<I, E>(bytes: I) -> Result<(i64, usize), E>
+    where I: IntoIterator<Item = Result<u8, E>>,
+          E: From<Error>
+{
+    try_decode(bytes, |bytes| decode_signed(bytes))
+}
 
-            shift += 7;
+/// Like `decode_unsigned`, but for a fallible source of bytes. The first
+/// error from `bytes` is returned as is; decoding errors are converted into
+/// `E`.
+#[cfg(not(feature = "signed-only"))]
+pub fn try_decode_unsigned<I, E>(bytes: I) -> Result<(u64, usize), E>
+    where I: IntoIterator<Item = Result<u8, E>>,
+          E: From<Error>
+{
+    try_decode(bytes, |bytes| decode_unsigned(bytes))
+}
+
+fn try_decode<I, E, T, F>(bytes: I, decode: F) -> Result<(T, usize), E>
+    where I: IntoIterator<Item = Result<u8, E>>,
+          E: From<Error>,
+          F: FnOnce(&mut dyn Iterator<Item = u8>) -> Result<(T, usize), Error>
+{
+    let mut failure = None;
+    let result = decode(&mut bytes.into_iter().map_while(|byte| match byte {
+        Ok(byte) => Some(byte),
+        Err(e) => {
+            failure = Some(e);
+            None
         }
+    }));
+    match failure {
+        Some(e) => Err(e),
+        None => result.map_err(E::from),
+    }
+}
+
+/// A reader restricted to the next `limit` bytes of an inner buffer.
+///
+/// Created by [`take`](fn.take.html). It implements `Buf`, so every
+/// `Leb128Read` method is available on it; a value that would cross the end of
+/// the scope fails with `Error::Incomplete` rather than reading past it.
+#[cfg(feature = "bytes")]
+#[derive(Debug)]
+pub struct Take<B> {
+    inner: B,
+    limit: usize,
+}
+
+/// Restrict reading from `buf` to its next `limit` bytes.
+///
+/// This is useful for length-prefixed sections, such as DWARF units, whose
+/// contents must not be decoded past their declared end.
+///
+/// ```
+/// # #[cfg(not(feature = "signed-only"))] {
+/// use nt_leb128::read::{self, Leb128Read};
+///
+/// let mut buf = &[0x80u8, 1, 0x80, 1][..];
+/// let mut unit = read::take(&mut buf, 3);
+/// assert_eq!(unit.read_unsigned().unwrap(), (128, 2));
+/// assert!(unit.read_unsigned().is_err());
+/// assert_eq!(unit.limit(), 0);
+/// # }
+/// ```
+#[cfg(feature = "bytes")]
+pub fn take<B: Buf>(buf: B, limit: usize) -> Take<B> {
+    Take { inner: buf, limit }
+}
+
+#[cfg(feature = "bytes")]
+impl<B: Buf> Take<B> {
+    /// The number of bytes left in the scope that have not been consumed.
+    ///
+    /// This can be larger than `remaining()` if the inner buffer holds fewer
+    /// bytes than the scope was created with.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Advance the inner buffer to the end of the scope, returning the number
+    /// of bytes skipped.
+    pub fn skip_rest(&mut self) -> usize {
+        let skipped = self.remaining();
+        self.advance(skipped);
+        skipped
+    }
+
+    /// Get a reference to the inner buffer.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwrap the inner buffer, positioned after the bytes consumed through
+    /// this scope.
+    pub fn into_inner(self) -> B {
+        self.inner
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "bytes")]
+impl<B: Buf> Buf for Take<B> {
+    fn remaining(&self) -> usize {
+        self.inner.remaining().min(self.limit)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        let bytes = self.inner.bytes();
+        &bytes[..bytes.len().min(self.limit)]
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.limit, "cannot advance past the end of a Take");
+        self.inner.advance(cnt);
+        self.limit -= cnt;
+    }
+}
@@ -0,0 +1,202 @@
+//! A schema is a list of field kinds, written as whitespace separated names:
+//! `u` (or `uleb`) for unsigned numbers, `s` (or `sleb`) for signed ones,
+//! `bytes` for length prefixed byte strings and `str` (or `string`) for
+//! length prefixed UTF-8 strings. A record is its fields back to back, in
+//! that order, with nothing between them.
+//!
+//! ```
+//! use nt_leb128::schema::{decode_record, encode_record, Schema, Value};
+//!
+//! let schema: Schema = "u s bytes".parse().unwrap();
+//! let record = vec![Value::Unsigned(624485), Value::Signed(-2), Value::Bytes(b"hi".to_vec())];
+//! let mut buf = Vec::new();
+//! encode_record(&schema, &record, &mut buf).expect("Should write record");
+//! assert_eq!(buf, [0xe5, 0x8e, 0x26, 0x7e, 0x02, b'h', b'i']);
+//! assert_eq!(decode_record(&schema, &mut &buf[..]).unwrap(), record);
+//! ```
+
+use super::config::Config;
+use super::read::{self, Leb128Read};
+use super::write::{self, Leb128Write};
+use std::fmt;
+use std::str::FromStr;
+
+/// The kind of one field of a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FieldKind {
+    /// An unsigned LEB128 number.
+    Unsigned,
+    /// A signed LEB128 number.
+    Signed,
+    /// A byte string prefixed with its length as unsigned LEB128.
+    Bytes,
+    /// A UTF-8 string prefixed with its length in bytes as unsigned LEB128.
+    String,
+}
+
+impl FieldKind {
+    /// The name of the kind in a schema string.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            FieldKind::Unsigned => "u",
+            FieldKind::Signed => "s",
+            FieldKind::Bytes => "bytes",
+            FieldKind::String => "str",
+        }
+    }
+}
+
+/// The value of one field of a record.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Value {
+    /// The value of a `FieldKind::Unsigned` field.
+    Unsigned(u64),
+    /// The value of a `FieldKind::Signed` field.
+    Signed(i64),
+    /// The value of a `FieldKind::Bytes` field.
+    Bytes(Vec<u8>),
+    /// The value of a `FieldKind::String` field.
+    String(String),
+}
+
+impl Value {
+    /// The kind of field this value belongs in.
+    pub fn kind(&self) -> FieldKind {
+        match *self {
+            Value::Unsigned(_) => FieldKind::Unsigned,
+            Value::Signed(_) => FieldKind::Signed,
+            Value::Bytes(_) => FieldKind::Bytes,
+            Value::String(_) => FieldKind::String,
+        }
+    }
+}
+
+/// The layout of a record, described at runtime.
+///
+/// Parsed from a schema string, or built from a list of `FieldKind`s. Its
+/// `Display` implementation prints the schema string back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Schema {
+    fields: Vec<FieldKind>,
+    max_len: usize,
+}
+
+impl Schema {
+    /// A schema of `fields`, in order, with no limit on the length of byte
+    /// strings and strings beyond the input holding them.
+    pub fn new(fields: Vec<FieldKind>) -> Self {
+        Schema {
+            fields,
+            max_len: usize::MAX,
+        }
+    }
+
+    /// Limit byte strings and strings to `max_len` bytes when decoding.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// The kinds of the fields, in order.
+    pub fn fields(&self) -> &[FieldKind] {
+        &self.fields
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(field.name())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Schema {
+    type Err = ParseSchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.split_whitespace()
+            .map(|name| match name {
+                "u" | "uleb" => Ok(FieldKind::Unsigned),
+                "s" | "sleb" => Ok(FieldKind::Signed),
+                "bytes" => Ok(FieldKind::Bytes),
+                "str" | "string" => Ok(FieldKind::String),
+                _ => Err(ParseSchemaError { name: name.to_string() }),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Schema::new(fields))
+    }
+}
+
+/// A schema string names a field kind that doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSchemaError {
+    name: String,
+}
+
+impl ParseSchemaError {
+    /// The unknown name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for ParseSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "leb128::schema::ParseSchemaError: Unknown field kind `{}`", self.name)
+    }
+}
+
+impl ::std::error::Error for ParseSchemaError {}
+
+/// Read one record laid out as `schema` describes from `buf`.
+///
+/// Fails as the `Leb128Read` method for each field would, including with
+/// `Error::LengthExceeded` for a byte string or string longer than the
+/// schema's `max_len`.
+pub fn decode_record<R: Leb128Read + ?Sized>(schema: &Schema,
+                                             buf: &mut R)
+                                             -> Result<Vec<Value>, read::Error> {
+    schema.fields
+        .iter()
+        .map(|field| {
+            Ok(match *field {
+                FieldKind::Unsigned => Value::Unsigned(buf.read_unsigned_with(&Config::new())?.0),
+                FieldKind::Signed => Value::Signed(buf.read_signed_with(&Config::new())?.0),
+                FieldKind::Bytes => Value::Bytes(buf.read_bytes(schema.max_len)?),
+                FieldKind::String => Value::String(buf.read_string(schema.max_len)?),
+            })
+        })
+        .collect()
+}
+
+/// Write `values` to `buf` as a record laid out as `schema` describes,
+/// returning the number of bytes written.
+///
+/// Fails with `write::Error::OutOfRange`, before writing anything, if the
+/// values don't match the schema's fields in number and kind.
+pub fn encode_record<W: Leb128Write + ?Sized>(schema: &Schema,
+                                              values: &[Value],
+                                              buf: &mut W)
+                                              -> Result<usize, write::Error> {
+    if values.len() != schema.fields.len() ||
+       values.iter().zip(&schema.fields).any(|(val, &field)| val.kind() != field) {
+        return Err(write::Error::OutOfRange);
+    }
+    let mut len = 0;
+    for val in values {
+        len += match *val {
+            Value::Unsigned(val) => buf.write_unsigned_with(val, &Config::new())?,
+            Value::Signed(val) => buf.write_signed_with(val, &Config::new())?,
+            Value::Bytes(ref bytes) => buf.write_bytes(bytes)?,
+            Value::String(ref s) => buf.write_str(s)?,
+        };
+    }
+    Ok(len)
+}
@@ -0,0 +1,78 @@
+//! Per-thread scratch buffers, so that bulk encoding and framing don't
+//! allocate a temporary buffer on every call.
+
+#[cfg(feature = "bytes")]
+use bytes::BytesMut;
+use std::cell::{Cell, RefCell};
+
+/// How many bytes a thread's scratch buffers keep between calls, unless
+/// changed with `with_scratch_capacity`.
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+thread_local! {
+    static CAPACITY: Cell<usize> = const { Cell::new(DEFAULT_CAPACITY) };
+    static VEC: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    #[cfg(feature = "bytes")]
+    static BYTES: RefCell<BytesMut> = RefCell::new(BytesMut::new());
+}
+
+/// Set how many bytes of scratch space the current thread keeps for bulk
+/// encoding and framing, such as `frame::read_delimited` and the Python
+/// module's `encode_unsigned_all`.
+///
+/// Payloads read by `read_delimited` are carved out of blocks of this size,
+/// so reading many small messages allocates once per block rather than once
+/// per message; each block is freed once every payload carved from it has
+/// been dropped. Temporary buffers that grow beyond this size are freed
+/// rather than kept.
+///
+/// The default is 8 KiB. Zero opts out: every call allocates exactly what it
+/// needs, and nothing is kept.
+///
+/// ```
+/// nt_leb128::with_scratch_capacity(64 * 1024);
+/// ```
+pub fn with_scratch_capacity(n: usize) {
+    CAPACITY.with(|capacity| capacity.set(n));
+    VEC.with(|vec| {
+        let mut vec = vec.borrow_mut();
+        if vec.capacity() > n {
+            *vec = Vec::new();
+        }
+    });
+    #[cfg(feature = "bytes")]
+    BYTES.with(|bytes| *bytes.borrow_mut() = BytesMut::new());
+}
+
+/// Call `f` with an empty `Vec` that keeps its allocation between calls on
+/// the same thread.
+#[cfg(all(feature = "python", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub(crate) fn with_vec<T, F: FnOnce(&mut Vec<u8>) -> T>(f: F) -> T {
+    let mut vec = VEC.with(|vec| std::mem::take(&mut *vec.borrow_mut()));
+    vec.clear();
+    let result = f(&mut vec);
+    if vec.capacity() <= CAPACITY.with(Cell::get) {
+        VEC.with(|slot| *slot.borrow_mut() = vec);
+    }
+    result
+}
+
+/// A zero-filled buffer of `len` bytes, carved out of the current thread's
+/// scratch block when it has room.
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+pub(crate) fn zeroed_bytes(len: usize) -> BytesMut {
+    let capacity = CAPACITY.with(Cell::get);
+    if len > capacity {
+        let mut bytes = BytesMut::with_capacity(len);
+        bytes.resize(len, 0);
+        return bytes;
+    }
+    BYTES.with(|block| {
+        let mut block = block.borrow_mut();
+        if block.capacity() < len {
+            *block = BytesMut::with_capacity(capacity);
+        }
+        block.resize(len, 0);
+        block.split_to(len)
+    })
+}
@@ -0,0 +1,132 @@
+use super::read::{Error, Leb128Read};
+use std::convert::TryFrom;
+
+/// A run of LEB128 numbers being read from a buffer, collecting their values
+/// into a tuple and counting the bytes they occupy.
+///
+/// Created by [`Leb128Read::seq`](../read/trait.Leb128Read.html#method.seq).
+/// Each method reads one number and returns the sequence with its value
+/// appended, so a whole header is read with a single `?` per field and one
+/// error path.
+///
+/// ```
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// use nt_leb128::read::Leb128Read;
+///
+/// let mut buf = &[0xe5, 0x8e, 0x26, 0x7e, 0x2a, 0x01][..];
+/// let ((offset, delta, opcode), len) = buf.seq().u64()?.i64()?.u8()?.finish();
+/// assert_eq!((offset, delta, opcode, len), (624485, -2, 42, 5));
+/// assert_eq!(buf, [0x01]);
+/// # }
+/// # Ok::<(), nt_leb128::read::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Seq<'a, R: ?Sized, T> {
+    reader: &'a mut R,
+    values: T,
+    len: usize,
+}
+
+impl<'a, R: Leb128Read + ?Sized> Seq<'a, R, ()> {
+    pub(crate) fn new(reader: &'a mut R) -> Self {
+        Seq {
+            reader,
+            values: (),
+            len: 0,
+        }
+    }
+}
+
+macro_rules! seq_methods {
+    ($read:ident, $wide:ty, $($ty:ident),*) => {
+        $(
+            #[doc = concat!("Read the next number as a `", stringify!($ty), "`, failing with ",
+                            "`Error::Overflow` if it's out of range.")]
+            pub fn $ty(self) -> Result<Seq<'a, R, T::Output>, Error>
+                where T: Push<$ty>
+            {
+                self.next(|reader| {
+                    let (val, len): ($wide, usize) = reader.$read()?;
+                    Ok(($ty::try_from(val).map_err(|_| Error::Overflow)?, len))
+                })
+            }
+        )*
+    }
+}
+
+impl<'a, R: Leb128Read + ?Sized, T> Seq<'a, R, T> {
+    #[cfg(not(feature = "signed-only"))]
+    seq_methods!(read_unsigned, u64, u8, u16, u32, u64, usize);
+    #[cfg(not(feature = "unsigned-only"))]
+    seq_methods!(read_signed, i64, i8, i16, i32, i64, isize);
+
+    /// The values read so far.
+    pub fn values(&self) -> &T {
+        &self.values
+    }
+
+    /// The number of bytes read so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing has been read yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// End the sequence, returning the values read and the number of bytes
+    /// they occupied.
+    pub fn finish(self) -> (T, usize) {
+        (self.values, self.len)
+    }
+
+    fn next<V, F>(self, read: F) -> Result<Seq<'a, R, T::Output>, Error>
+        where T: Push<V>,
+              F: FnOnce(&mut R) -> Result<(V, usize), Error>
+    {
+        let (val, len) = read(self.reader)?;
+        Ok(Seq {
+            reader: self.reader,
+            values: self.values.push(val),
+            len: self.len + len,
+        })
+    }
+}
+
+/// Tuples a value can be appended to, for collecting the values of a `Seq`.
+/// Implemented for tuples of up to eleven elements.
+pub trait Push<V> {
+    /// The tuple with `V` appended.
+    type Output;
+
+    /// Append `val` to the tuple.
+    fn push(self, val: V) -> Self::Output;
+}
+
+macro_rules! impl_push {
+    ($($name:ident),*) => {
+        impl<$($name,)* V> Push<V> for ($($name,)*) {
+            type Output = ($($name,)* V,);
+
+            #[allow(non_snake_case)]
+            fn push(self, val: V) -> Self::Output {
+                let ($($name,)*) = self;
+                ($($name,)* val,)
+            }
+        }
+    }
+}
+
+impl_push!();
+impl_push!(A);
+impl_push!(A, B);
+impl_push!(A, B, C);
+impl_push!(A, B, C, D);
+impl_push!(A, B, C, D, E);
+impl_push!(A, B, C, D, E, F);
+impl_push!(A, B, C, D, E, F, G);
+impl_push!(A, B, C, D, E, F, G, H);
+impl_push!(A, B, C, D, E, F, G, H, I);
+impl_push!(A, B, C, D, E, F, G, H, I, J);
+impl_push!(A, B, C, D, E, F, G, H, I, J, K);
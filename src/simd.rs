@@ -0,0 +1,45 @@
+//! Bulk kernels written against `core::simd`, so that every target LLVM can
+//! vectorize for gets them without per-architecture code.
+
+use super::read::{Error, MAX_LEN};
+use std::simd::cmp::SimdPartialOrd;
+use std::simd::u8x16;
+
+const LANES: usize = 16;
+
+/// Count the terminating bytes in the whole 16 byte blocks at the start of
+/// `bytes`, and return the count, the length of the run of continuation bytes
+/// the blocks end with, and the bytes left over for the scalar loop.
+///
+/// Fails with `Error::Overflow` as soon as a block holds, or completes, a run
+/// of `MAX_LEN` continuation bytes.
+pub(crate) fn count_terminals(bytes: &[u8]) -> Result<(usize, usize, &[u8]), Error> {
+    let mut count = 0;
+    let mut run = 0;
+    let mut blocks = bytes.chunks_exact(LANES);
+    for block in &mut blocks {
+        let terminal = u8x16::from_slice(block).simd_lt(u8x16::splat(0x80)).to_bitmask();
+        let continuation = !terminal & ((1 << LANES) - 1);
+        let leading = (terminal.trailing_zeros() as usize).min(LANES);
+        if run + leading >= MAX_LEN || has_run(continuation) {
+            return Err(Error::Overflow);
+        }
+
+        count += terminal.count_ones() as usize;
+        run = if terminal == 0 {
+            run + LANES
+        } else {
+            (terminal.leading_zeros() as usize) - (64 - LANES)
+        };
+    }
+    Ok((count, run, blocks.remainder()))
+}
+
+/// Whether `bits` has `MAX_LEN` consecutive set bits.
+fn has_run(bits: u64) -> bool {
+    let mut run = bits;
+    for shift in 1..MAX_LEN {
+        run &= bits >> shift;
+    }
+    run != 0
+}
@@ -0,0 +1,557 @@
+use super::{CONTINUATION_BIT, SIGN_BIT, low_bits_of_byte, metrics, raw};
+#[cfg(not(feature = "signed-only"))]
+use super::low_bits_of_u64;
+use super::read::{self, MAX_LEN, not_enough_data, scan, Error};
+#[cfg(not(feature = "signed-only"))]
+use super::read::unsigned_last_byte_fits;
+#[cfg(not(feature = "unsigned-only"))]
+use super::read::signed_last_byte_fits;
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::encode_unsigned_const;
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::encode_signed_const;
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::encoded_len_unsigned;
+use super::write;
+#[cfg(not(feature = "signed-only"))]
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// Check that `bytes` starts with a well-formed unsigned LEB128 encoding that
+/// fits in a `u64`, and return its length without computing the value.
+///
+/// ```
+/// assert_eq!(nt_leb128::validate_unsigned(&[0xe5, 0x8e, 0x26, 0xff]).unwrap(), 3);
+/// assert!(nt_leb128::validate_unsigned(&[0xe5, 0x8e]).is_err());
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn validate_unsigned<B: AsRef<[u8]>>(bytes: B) -> Result<usize, Error> {
+    let bytes = bytes.as_ref();
+    scan(bytes.iter().copied(), unsigned_last_byte_fits)
+}
+
+/// Check that `bytes` starts with a well-formed signed LEB128 encoding that
+/// fits in an `i64`, and return its length without computing the value.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn validate_signed<B: AsRef<[u8]>>(bytes: B) -> Result<usize, Error> {
+    let bytes = bytes.as_ref();
+    scan(bytes.iter().copied(), signed_last_byte_fits)
+}
+
+/// Like `validate_unsigned`, but additionally reject encodings that are longer
+/// than necessary with `Error::NonCanonical`.
+///
+/// ```
+/// assert_eq!(nt_leb128::validate_unsigned_canonical(&[0x02]).unwrap(), 1);
+/// assert!(nt_leb128::validate_unsigned_canonical(&[0x82, 0x00]).is_err());
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn validate_unsigned_canonical<B: AsRef<[u8]>>(bytes: B) -> Result<usize, Error> {
+    let bytes = bytes.as_ref();
+    let len = validate_unsigned(bytes)?;
+    if !is_canonical_unsigned(&bytes[..len]) {
+        metrics::record_error(&Error::NonCanonical);
+        return Err(Error::NonCanonical);
+    }
+    Ok(len)
+}
+
+/// Like `validate_signed`, but additionally reject encodings that are longer
+/// than necessary with `Error::NonCanonical`.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn validate_signed_canonical<B: AsRef<[u8]>>(bytes: B) -> Result<usize, Error> {
+    let bytes = bytes.as_ref();
+    let len = validate_signed(bytes)?;
+    if !is_canonical_signed(&bytes[..len]) {
+        metrics::record_error(&Error::NonCanonical);
+        return Err(Error::NonCanonical);
+    }
+    Ok(len)
+}
+
+/// Whether a complete unsigned encoding is the shortest one for its value: a
+/// final byte of zero is only necessary when it is the only byte.
+pub(crate) fn is_canonical_unsigned(encoding: &[u8]) -> bool {
+    match *encoding {
+        [_] => true,
+        [.., last] => last != 0x00,
+        [] => false,
+    }
+}
+
+/// Whether a complete signed encoding is the shortest one for its value: a
+/// final byte that only repeats the previous byte's sign bit is redundant.
+pub(crate) fn is_canonical_signed(encoding: &[u8]) -> bool {
+    match *encoding {
+        [_] => true,
+        [.., prev, last] => {
+            let sign = prev & SIGN_BIT != 0;
+            !(last == 0x00 && !sign || last == 0x7f && sign)
+        }
+        [] => false,
+    }
+}
+
+/// An iterator over the byte ranges of successive LEB128 encodings in a slice.
+///
+/// Created by [`boundaries`](fn.boundaries.html) and
+/// [`boundaries_signed`](fn.boundaries_signed.html).
+#[derive(Debug, Clone)]
+pub struct Boundaries<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    last_byte_fits: fn(u8) -> bool,
+    failed: bool,
+}
+
+/// Iterate over the byte range of each unsigned LEB128 encoding in `bytes`,
+/// without decoding any of them.
+///
+/// Each encoding is checked as `validate_unsigned` would. The iterator ends
+/// cleanly at the end of `bytes`; if an encoding is truncated or too long, the
+/// error is yielded once and the iterator then ends.
+///
+/// ```
+/// let ranges: Vec<_> = nt_leb128::boundaries(&[0x02, 0xe5, 0x8e, 0x26])
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(ranges, [0..1, 1..4]);
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn boundaries(bytes: &[u8]) -> Boundaries<'_> {
+    Boundaries {
+        bytes,
+        offset: 0,
+        last_byte_fits: unsigned_last_byte_fits,
+        failed: false,
+    }
+}
+
+/// Like `boundaries`, but checking each encoding as `validate_signed` would.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn boundaries_signed(bytes: &[u8]) -> Boundaries<'_> {
+    Boundaries {
+        bytes,
+        offset: 0,
+        last_byte_fits: signed_last_byte_fits,
+        failed: false,
+    }
+}
+
+impl<'a> Iterator for Boundaries<'a> {
+    type Item = Result<Range<usize>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.offset == self.bytes.len() {
+            return None;
+        }
+        match scan(self.bytes[self.offset..].iter().copied(), self.last_byte_fits) {
+            Ok(len) => {
+                let start = self.offset;
+                self.offset += len;
+                Some(Ok(start..self.offset))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Cut `bytes` into chunks of roughly `approx_chunk_size` bytes, each starting
+/// and ending on a value boundary, so they can be decoded independently (for
+/// example, on separate threads).
+///
+/// A boundary is found by jumping ahead and scanning forward to the next byte
+/// without the continuation bit, so this doesn't validate the encodings; a
+/// malformed encoding is reported when its chunk is decoded.
+///
+/// Requires the `alloc` feature.
+///
+/// ```
+/// let chunks = nt_leb128::split_at_boundaries(&[0x80, 0x01, 0x02, 0x80, 0x80, 0x01], 1);
+/// assert_eq!(chunks, [&[0x80, 0x01][..], &[0x02][..], &[0x80, 0x80, 0x01][..]]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn split_at_boundaries(bytes: &[u8], approx_chunk_size: usize) -> Vec<&[u8]> {
+    let approx_chunk_size = approx_chunk_size.max(1);
+    let mut chunks = Vec::with_capacity(bytes.len() / approx_chunk_size + 1);
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let target = approx_chunk_size.min(rest.len()) - 1;
+        let end = rest[target..]
+            .iter()
+            .position(|b| b & CONTINUATION_BIT == 0)
+            .map_or(rest.len(), |pos| target + pos + 1);
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// Count the LEB128 encodings in `bytes` by scanning for terminating bytes,
+/// without reconstructing any values.
+///
+/// Fails with `Error::Overflow` if an encoding runs longer than the ten bytes
+/// a 64 bit number can occupy, or with `Error::Incomplete` if the last
+/// encoding is truncated. The payload bits of the tenth byte aren't checked,
+/// so this counts signed and unsigned encodings alike.
+///
+/// ```
+/// assert_eq!(nt_leb128::count_values(&[0x02, 0xe5, 0x8e, 0x26, 0x7e]).unwrap(), 3);
+/// ```
+pub fn count_values<B: AsRef<[u8]>>(bytes: B) -> Result<usize, Error> {
+    let bytes = bytes.as_ref();
+    #[cfg(feature = "portable-simd")]
+    let (mut count, mut run, bytes) = super::simd::count_terminals(bytes)?;
+    #[cfg(not(feature = "portable-simd"))]
+    let (mut count, mut run) = (0, 0);
+    for &byte in bytes {
+        if byte & CONTINUATION_BIT == 0 {
+            count += 1;
+            run = 0;
+        } else {
+            run += 1;
+            if run == MAX_LEN {
+                return Err(Error::Overflow);
+            }
+        }
+    }
+    if run != 0 {
+        return Err(not_enough_data());
+    }
+    Ok(count)
+}
+
+/// Compare the values of the unsigned LEB128 encodings at the start of `a`
+/// and `b` without decoding them.
+///
+/// A longer encoding holds a larger value, once any redundant trailing zero
+/// bytes of an overlong encoding are disregarded; encodings of equal length
+/// are compared payload by payload from the most significant byte down. Bytes
+/// after each encoding are ignored.
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(nt_leb128::cmp_encoded(&[0xe5, 0x8e, 0x26], &[0x7f]).unwrap(), Ordering::Greater);
+/// assert_eq!(nt_leb128::cmp_encoded(&[0x82, 0x00], &[0x02]).unwrap(), Ordering::Equal);
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn cmp_encoded<A: AsRef<[u8]>, B: AsRef<[u8]>>(a: A, b: B) -> Result<Ordering, Error> {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    let a = significant(&a[..validate_unsigned(a)?]);
+    let b = significant(&b[..validate_unsigned(b)?]);
+    Ok(a.len().cmp(&b.len()).then_with(|| {
+        a.iter()
+            .rev()
+            .map(|&byte| low_bits_of_byte(byte))
+            .cmp(b.iter().rev().map(|&byte| low_bits_of_byte(byte)))
+    }))
+}
+
+/// Trim the bytes of an unsigned encoding that only contribute zero bits to
+/// the top of its value.
+#[cfg(not(feature = "signed-only"))]
+fn significant(encoding: &[u8]) -> &[u8] {
+    let len = encoding.iter()
+        .rposition(|&byte| low_bits_of_byte(byte) != 0)
+        .map_or(0, |pos| pos + 1);
+    &encoding[..len]
+}
+
+/// Decode the unsigned LEB128 number at the start of `input`, returning it
+/// along with the rest of `input`, so a parser can thread the remaining input
+/// through without a `Buf` or a byte count.
+///
+/// ```
+/// use nt_leb128::slice::decode_unsigned;
+///
+/// let (val, rest) = decode_unsigned(&[0xe5, 0x8e, 0x26, 0x02]).unwrap();
+/// assert_eq!((val, rest), (624485, &[0x02][..]));
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn decode_unsigned(input: &[u8]) -> Result<(u64, &[u8]), Error> {
+    let (val, len) = read::decode_unsigned(input.iter().copied())?;
+    Ok((val, &input[len..]))
+}
+
+/// Like `decode_unsigned`, but decoding a signed LEB128 number.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn decode_signed(input: &[u8]) -> Result<(i64, &[u8]), Error> {
+    let (val, len) = read::decode_signed(input.iter().copied())?;
+    Ok((val, &input[len..]))
+}
+
+/// Decode all of `input` with `decode`, failing unless it ends at a value
+/// boundary.
+#[cfg(all(any(feature = "wasm", feature = "python"),
+          not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub(crate) fn decode_all<T, F>(mut input: &[u8], decode: F) -> Result<Vec<T>, Error>
+    where F: Fn(&[u8]) -> Result<(T, &[u8]), Error>
+{
+    let mut vals = Vec::new();
+    while !input.is_empty() {
+        let (val, rest) = decode(input)?;
+        vals.push(val);
+        input = rest;
+    }
+    Ok(vals)
+}
+
+/// Like `decode_unsigned`, but failing with `Error::Overflow` if the
+/// encoding runs past `MAX` bytes. The bound is known at compile time, so
+/// the loop can be fully unrolled, as for a `u32` field that always fits in
+/// five bytes.
+///
+/// Fails to compile unless `MAX` is between 1 and 10.
+///
+/// ```
+/// use nt_leb128::slice::decode_unsigned_bounded;
+///
+/// let (val, rest) = decode_unsigned_bounded::<5>(&[0xe5, 0x8e, 0x26, 0x02]).unwrap();
+/// assert_eq!((val, rest), (624485, &[0x02][..]));
+/// assert!(decode_unsigned_bounded::<2>(&[0xe5, 0x8e, 0x26]).is_err());
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn decode_unsigned_bounded<const MAX: usize>(input: &[u8]) -> Result<(u64, &[u8]), Error> {
+    const { assert!(MAX >= 1 && MAX <= MAX_LEN, "MAX must be between 1 and 10") };
+    let mut result = 0;
+    for i in 0..MAX {
+        let byte = *input.get(i).ok_or_else(not_enough_data)?;
+        if i == MAX_LEN - 1 && byte != 0x00 && byte != 0x01 {
+            return Err(Error::Overflow);
+        }
+        result |= (low_bits_of_byte(byte) as u64) << raw::shift_of(i);
+        if byte & CONTINUATION_BIT == 0 {
+            return Ok((result, &input[i + 1..]));
+        }
+    }
+    Err(Error::Overflow)
+}
+
+/// Like `decode_unsigned_bounded`, but decoding a signed LEB128 number.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn decode_signed_bounded<const MAX: usize>(input: &[u8]) -> Result<(i64, &[u8]), Error> {
+    const { assert!(MAX >= 1 && MAX <= MAX_LEN, "MAX must be between 1 and 10") };
+    let mut result = 0;
+    for i in 0..MAX {
+        let byte = *input.get(i).ok_or_else(not_enough_data)?;
+        if i == MAX_LEN - 1 && byte != 0x00 && byte != 0x7f {
+            return Err(Error::Overflow);
+        }
+        result |= (low_bits_of_byte(byte) as i64) << raw::shift_of(i);
+        if byte & CONTINUATION_BIT == 0 {
+            return Ok((raw::sign_extend(result, raw::shift_of(i + 1), byte), &input[i + 1..]));
+        }
+    }
+    Err(Error::Overflow)
+}
+
+/// Decode the unsigned LEB128 number at `offset` in `buf`, returning it and
+/// the length of its encoding. Nothing is consumed, so this suits random
+/// access into shared or memory-mapped data.
+///
+/// Fails with `Error::Incomplete` if `offset` is past the end of `buf`.
+///
+/// ```
+/// use nt_leb128::slice::decode_unsigned_at;
+///
+/// let buf = [0x02, 0xe5, 0x8e, 0x26];
+/// assert_eq!(decode_unsigned_at(&buf, 1).unwrap(), (624485, 3));
+/// assert_eq!(decode_unsigned_at(&buf, 0).unwrap(), (2, 1));
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn decode_unsigned_at<B: AsRef<[u8]>>(buf: B, offset: usize) -> Result<(u64, usize), Error> {
+    let buf = buf.as_ref();
+    read::decode_unsigned(buf.get(offset..).ok_or_else(not_enough_data)?.iter().copied())
+}
+
+/// Like `decode_unsigned_at`, but decoding a signed LEB128 number.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn decode_signed_at<B: AsRef<[u8]>>(buf: B, offset: usize) -> Result<(i64, usize), Error> {
+    let buf = buf.as_ref();
+    read::decode_signed(buf.get(offset..).ok_or_else(not_enough_data)?.iter().copied())
+}
+
+/// Decode the unsigned LEB128 number at the start of `bytes` without any
+/// checks, returning it and the length of its encoding, for inner loops over
+/// buffers that were validated once when they were loaded.
+///
+/// # Safety
+///
+/// `bytes` must start with a complete encoding that `validate_unsigned`
+/// accepts. Anything else reads out of bounds or returns garbage.
+///
+/// ```
+/// use nt_leb128::slice::decode_unsigned_unchecked;
+///
+/// let buf = [0xe5, 0x8e, 0x26, 0x02];
+/// assert_eq!(nt_leb128::count_values(&buf).unwrap(), 2);
+/// // SAFETY: `count_values` found two whole numbers, the first at the start.
+/// assert_eq!(unsafe { decode_unsigned_unchecked(&buf) }, (624485, 3));
+/// ```
+#[cfg(not(feature = "signed-only"))]
+#[inline]
+pub unsafe fn decode_unsigned_unchecked(bytes: &[u8]) -> (u64, usize) {
+    debug_assert!(validate_unsigned(bytes).is_ok(), "invalid encoding");
+    let mut result = 0;
+    let mut i = 0;
+    loop {
+        let byte = *bytes.get_unchecked(i);
+        result |= (low_bits_of_byte(byte) as u64).wrapping_shl(i as u32 * 7);
+        i += 1;
+        if byte & CONTINUATION_BIT == 0 {
+            return (result, i);
+        }
+    }
+}
+
+/// Like `decode_unsigned_unchecked`, but decoding a signed LEB128 number.
+///
+/// # Safety
+///
+/// `bytes` must start with a complete encoding that `validate_signed`
+/// accepts.
+#[cfg(not(feature = "unsigned-only"))]
+#[inline]
+pub unsafe fn decode_signed_unchecked(bytes: &[u8]) -> (i64, usize) {
+    debug_assert!(validate_signed(bytes).is_ok(), "invalid encoding");
+    let mut result = 0;
+    let mut i = 0;
+    loop {
+        let byte = *bytes.get_unchecked(i);
+        result |= (low_bits_of_byte(byte) as i64).wrapping_shl(i as u32 * 7);
+        i += 1;
+        if byte & CONTINUATION_BIT == 0 {
+            return (raw::sign_extend(result, i as u32 * 7, byte), i);
+        }
+    }
+}
+
+/// Encode `val` as unsigned LEB128 at the front of `buf`, returning the part
+/// of `buf` after the encoding, so writes can be chained without keeping an
+/// offset.
+///
+/// Fails with `write::Error::InsufficientSpace`, leaving `buf` untouched, if
+/// the encoding doesn't fit.
+///
+/// ```
+/// use nt_leb128::slice::encode_unsigned;
+///
+/// let mut buf = [0; 4];
+/// let rest = encode_unsigned(&mut buf, 624485).unwrap();
+/// let rest = encode_unsigned(rest, 2).unwrap();
+/// assert!(rest.is_empty());
+/// assert_eq!(buf, [0xe5, 0x8e, 0x26, 0x02]);
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn encode_unsigned(buf: &mut [u8], val: u64) -> Result<&mut [u8], write::Error> {
+    let (bytes, len) = encode_unsigned_const::<MAX_LEN>(val);
+    put_front(buf, &bytes[..len])
+}
+
+/// Like `encode_unsigned`, but encoding `val` as signed LEB128.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn encode_signed(buf: &mut [u8], val: i64) -> Result<&mut [u8], write::Error> {
+    let (bytes, len) = encode_signed_const::<MAX_LEN>(val);
+    put_front(buf, &bytes[..len])
+}
+
+fn put_front<'a>(buf: &'a mut [u8], encoding: &[u8]) -> Result<&'a mut [u8], write::Error> {
+    if encoding.len() > buf.len() {
+        return Err(write::Error::InsufficientSpace {
+            needed: encoding.len(),
+            available: buf.len(),
+        });
+    }
+    let (head, rest) = buf.split_at_mut(encoding.len());
+    head.copy_from_slice(encoding);
+    Ok(rest)
+}
+
+/// The result of [`patch_unsigned_in_place`](fn.patch_unsigned_in_place.html).
+#[cfg(not(feature = "signed-only"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchOutcome {
+    /// The new value was written over the old encoding, occupying the same
+    /// `len` bytes. It's padded if its shortest encoding is shorter.
+    Patched {
+        /// The length of the encoding, before and after.
+        len: usize,
+    },
+    /// The new value needs more bytes than the old encoding occupies, so the
+    /// buffer was left untouched. The caller must re-encode, shifting the rest
+    /// of the buffer by `new_len - old_len` bytes.
+    NeedsResize {
+        /// The length of the existing encoding.
+        old_len: usize,
+        /// The length of the shortest encoding of the new value.
+        new_len: usize,
+    },
+}
+
+/// Overwrite the unsigned LEB128 encoding at `offset` in `buf` with
+/// `new_value`, if it fits in the same number of bytes.
+///
+/// Values whose shortest encoding is shorter are padded with continuation
+/// bytes, so the buffer's layout never changes. Fails if the existing encoding
+/// is malformed.
+///
+/// ```
+/// use nt_leb128::slice::{patch_unsigned_in_place, PatchOutcome};
+///
+/// let mut buf = [0x01, 0xe5, 0x8e, 0x26, 0x01];
+/// assert_eq!(patch_unsigned_in_place(&mut buf, 1, 2).unwrap(), PatchOutcome::Patched { len: 3 });
+/// assert_eq!(buf, [0x01, 0x82, 0x80, 0x00, 0x01]);
+/// assert_eq!(patch_unsigned_in_place(&mut buf, 1, u64::MAX).unwrap(),
+///            PatchOutcome::NeedsResize { old_len: 3, new_len: 10 });
+/// ```
+#[cfg(not(feature = "signed-only"))]
+pub fn patch_unsigned_in_place(buf: &mut [u8],
+                               offset: usize,
+                               new_value: u64)
+                               -> Result<PatchOutcome, Error> {
+    let old_len = validate_unsigned(buf.get(offset..).ok_or_else(not_enough_data)?)?;
+    let new_len = encoded_len_unsigned(new_value);
+    if new_len > old_len {
+        return Ok(PatchOutcome::NeedsResize { old_len, new_len });
+    }
+    encode_unsigned_padded(&mut buf[offset..offset + old_len], new_value);
+    Ok(PatchOutcome::Patched { len: old_len })
+}
+
+/// Encode `val` as unsigned LEB128 using exactly `out.len()` bytes, padding
+/// with continuation bytes as needed. `out` must be long enough for the
+/// shortest encoding of `val`, and at most ten bytes long.
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn encode_unsigned_padded(out: &mut [u8], mut val: u64) {
+    debug_assert!(encoded_len_unsigned(val) <= out.len() && out.len() <= MAX_LEN);
+    let last = out.len() - 1;
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = low_bits_of_u64(val);
+        if i != last {
+            *byte |= CONTINUATION_BIT;
+        }
+        val >>= 7;
+    }
+}
+
+/// Lengthen the complete encoding at the end of `bytes` by `pad` redundant
+/// bytes without changing its value. `negative` selects the sign extension
+/// for signed encodings; unsigned encodings always pad with zeros.
+#[cfg(all(any(feature = "arbitrary", feature = "proptest"),
+          not(any(feature = "signed-only", feature = "unsigned-only"))))]
+pub(crate) fn pad_encoding(bytes: &mut Vec<u8>, pad: usize, negative: bool) {
+    let fill = if negative { 0x7f } else { 0x00 };
+    if pad == 0 {
+        return;
+    }
+    *bytes.last_mut().expect("encodings are never empty") |= CONTINUATION_BIT;
+    for _ in 1..pad {
+        bytes.push(CONTINUATION_BIT | fill);
+    }
+    bytes.push(fill);
+}
@@ -0,0 +1,147 @@
+//! A snapshot is the fields that changed since the last one: a bitmask of
+//! them as unsigned LEB128, bit `i` for field `i`, followed by the difference
+//! of each changed field from its previous value, zigzag encoded and written
+//! as unsigned LEB128 in field order. Unchanged fields cost nothing past their
+//! bit, and small changes of either sign take a byte. This is the usual way
+//! of sending game state over a network.
+//!
+//! Both sides keep a baseline of up to 64 fields, which must start out the
+//! same and see the same snapshots in the same order.
+//!
+//! ```
+//! use nt_leb128::snapshot::{SnapshotDecoder, SnapshotEncoder};
+//!
+//! let mut encoder = SnapshotEncoder::new([0i64; 4]);
+//! let mut decoder = SnapshotDecoder::new([0i64; 4]);
+//!
+//! let mut buf = Vec::new();
+//! encoder.encode(&mut buf, &[100, 0, -3, 7]).expect("Should write snapshot");
+//! encoder.encode(&mut buf, &[101, 0, -3, 7]).expect("Should write snapshot");
+//! assert_eq!(buf[buf.len() - 2..], [0b0001, 0x02]);
+//!
+//! let mut readable = &buf[..];
+//! assert_eq!(decoder.decode(&mut readable).unwrap(), 0b1101);
+//! assert_eq!(decoder.decode(&mut readable).unwrap(), 0b0001);
+//! assert_eq!(decoder.baseline(), [101, 0, -3, 7]);
+//! ```
+
+use super::config::Config;
+use super::raw;
+use super::read::{Error, Leb128Read};
+use super::write::{self, Leb128Write};
+
+/// The most fields a baseline can have, one per bit of the mask.
+pub const MAX_FIELDS: usize = 64;
+
+/// The sending side of a stream of snapshots, holding the fields as last
+/// sent.
+#[derive(Debug, Clone)]
+pub struct SnapshotEncoder<B> {
+    baseline: B,
+}
+
+impl<B: AsRef<[i64]> + AsMut<[i64]>> SnapshotEncoder<B> {
+    /// Start from `baseline`, which the decoder must start from too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `baseline` has more than `MAX_FIELDS` fields.
+    pub fn new(baseline: B) -> Self {
+        assert!(baseline.as_ref().len() <= MAX_FIELDS, "a snapshot has at most 64 fields");
+        SnapshotEncoder { baseline }
+    }
+
+    /// Write the fields of `current` that differ from the baseline, then make
+    /// `current` the baseline. Returns the number of bytes written.
+    ///
+    /// If the write fails partway, the baseline is left as it was, and the
+    /// stream should be abandoned since part of a snapshot may have been
+    /// written.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `current` has a different number of fields than the
+    /// baseline.
+    pub fn encode<W: Leb128Write + ?Sized>(&mut self,
+                                           buf: &mut W,
+                                           current: &[i64])
+                                           -> Result<usize, write::Error> {
+        let baseline = self.baseline.as_mut();
+        assert_eq!(current.len(), baseline.len(), "a snapshot must have every field");
+        let mask = baseline.iter()
+            .zip(current)
+            .enumerate()
+            .filter(|&(_, (old, new))| old != new)
+            .fold(0u64, |mask, (i, _)| mask | 1 << i);
+        let mut len = buf.write_unsigned_with(mask, &Config::new())?;
+        for (old, &new) in baseline.iter().zip(current).filter(|&(old, new)| old != new) {
+            let delta = raw::zigzag_encode(new.wrapping_sub(*old));
+            len += buf.write_unsigned_with(delta, &Config::new())?;
+        }
+        baseline.copy_from_slice(current);
+        Ok(len)
+    }
+
+    /// The fields as last sent.
+    pub fn baseline(&self) -> &[i64] {
+        self.baseline.as_ref()
+    }
+
+    /// Consume the encoder, returning its baseline.
+    pub fn into_inner(self) -> B {
+        self.baseline
+    }
+}
+
+/// The receiving side of a stream of snapshots, applying each to its
+/// baseline.
+#[derive(Debug, Clone)]
+pub struct SnapshotDecoder<B> {
+    baseline: B,
+}
+
+impl<B: AsRef<[i64]> + AsMut<[i64]>> SnapshotDecoder<B> {
+    /// Start from `baseline`, which the encoder must start from too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `baseline` has more than `MAX_FIELDS` fields.
+    pub fn new(baseline: B) -> Self {
+        assert!(baseline.as_ref().len() <= MAX_FIELDS, "a snapshot has at most 64 fields");
+        SnapshotDecoder { baseline }
+    }
+
+    /// Read a snapshot and apply it to the baseline, returning the mask of
+    /// the fields it changed.
+    ///
+    /// Fails with `Error::Overflow` if the mask names a field beyond the
+    /// baseline. The baseline is only changed once the whole snapshot has
+    /// been read.
+    pub fn decode<R: Leb128Read + ?Sized>(&mut self, buf: &mut R) -> Result<u64, Error> {
+        let baseline = self.baseline.as_mut();
+        let (mask, _) = buf.read_unsigned_with(&Config::new())?;
+        if baseline.len() < MAX_FIELDS && mask >> baseline.len() != 0 {
+            return Err(Error::Overflow);
+        }
+        let mut deltas = [0i64; MAX_FIELDS];
+        for (i, delta) in deltas.iter_mut().enumerate().take(baseline.len()) {
+            if mask & 1 << i != 0 {
+                *delta = raw::zigzag_decode(buf.read_unsigned_with(&Config::new())?.0);
+            }
+        }
+        for (field, delta) in baseline.iter_mut().zip(&deltas) {
+            *field = field.wrapping_add(*delta);
+        }
+        Ok(mask)
+    }
+
+    /// The fields as of the last snapshot.
+    pub fn baseline(&self) -> &[i64] {
+        self.baseline.as_ref()
+    }
+
+    /// Consume the decoder, returning its baseline.
+    pub fn into_inner(self) -> B {
+        self.baseline
+    }
+}
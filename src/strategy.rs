@@ -0,0 +1,94 @@
+//! ```
+//! use nt_leb128::strategy::{canonical_encoding, overlong_encoding};
+//! use nt_leb128::validate_unsigned_canonical;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn canonical_encodings_validate(bytes in canonical_encoding()) {
+//!         prop_assert_eq!(validate_unsigned_canonical(&bytes).ok(), Some(bytes.len()));
+//!     }
+//!
+//!     fn overlong_encodings_are_rejected(bytes in overlong_encoding(4)) {
+//!         prop_assert!(validate_unsigned_canonical(&bytes).is_err());
+//!     }
+//! }
+//! # canonical_encodings_validate();
+//! # overlong_encodings_are_rejected();
+//! ```
+
+use super::{encoded_len_signed, encoded_len_unsigned};
+use super::read::MAX_LEN;
+use super::slice::pad_encoding;
+use super::write::{put_signed, put_unsigned};
+use proptest::prelude::*;
+
+fn unsigned(val: u64, pad: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MAX_LEN);
+    put_unsigned(&mut bytes, val);
+    pad_encoding(&mut bytes, pad, false);
+    bytes
+}
+
+fn signed(val: i64, pad: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MAX_LEN);
+    put_signed(&mut bytes, val);
+    pad_encoding(&mut bytes, pad, val < 0);
+    bytes
+}
+
+/// Any unsigned encoding the readers accept: the value is any `u64`, and the
+/// encoding is either the shortest one or padded with redundant bytes up to
+/// ten bytes in total.
+pub fn any_encoded_unsigned() -> impl Strategy<Value = Vec<u8>> {
+    (any::<u64>(), 0..MAX_LEN).prop_map(|(val, pad)| {
+        unsigned(val, pad.min(MAX_LEN - encoded_len_unsigned(val)))
+    })
+}
+
+/// Any signed encoding the readers accept: the value is any `i64`, and the
+/// encoding is either the shortest one or padded with redundant bytes up to
+/// ten bytes in total.
+pub fn any_encoded_signed() -> impl Strategy<Value = Vec<u8>> {
+    (any::<i64>(), 0..MAX_LEN).prop_map(|(val, pad)| {
+        signed(val, pad.min(MAX_LEN - encoded_len_signed(val)))
+    })
+}
+
+/// The shortest unsigned encoding of any `u64`.
+pub fn canonical_encoding() -> impl Strategy<Value = Vec<u8>> {
+    any::<u64>().prop_map(|val| unsigned(val, 0))
+}
+
+/// The shortest signed encoding of any `i64`.
+pub fn canonical_encoding_signed() -> impl Strategy<Value = Vec<u8>> {
+    any::<i64>().prop_map(|val| signed(val, 0))
+}
+
+/// The unsigned encoding of any `u64`, padded with between one and `max_pad`
+/// redundant bytes.
+///
+/// Padding is not capped at ten bytes in total, so with a large enough
+/// `max_pad` this also produces encodings of in-range values that the readers
+/// reject as too long.
+///
+/// # Panics
+///
+/// Panics if `max_pad` is zero.
+pub fn overlong_encoding(max_pad: usize) -> impl Strategy<Value = Vec<u8>> {
+    assert!(max_pad > 0, "overlong encodings need at least one byte of padding");
+    (any::<u64>(), 1..=max_pad).prop_map(|(val, pad)| unsigned(val, pad))
+}
+
+/// The signed encoding of any `i64`, padded with between one and `max_pad`
+/// redundant bytes.
+///
+/// As with [`overlong_encoding`](fn.overlong_encoding.html), the padding is
+/// not capped at ten bytes in total.
+///
+/// # Panics
+///
+/// Panics if `max_pad` is zero.
+pub fn overlong_encoding_signed(max_pad: usize) -> impl Strategy<Value = Vec<u8>> {
+    assert!(max_pad > 0, "overlong encodings need at least one byte of padding");
+    (any::<i64>(), 1..=max_pad).prop_map(|(val, pad)| signed(val, pad))
+}
@@ -0,0 +1,133 @@
+use super::{encode_signed_const, encode_unsigned_const, explain, read};
+use super::read::{decode_signed, decode_unsigned, MAX_LEN};
+use bytes::BytesMut;
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+/// Integer types that the assertion macros know how to encode and decode.
+///
+/// Unsigned types use unsigned LEB128, signed types use signed LEB128.
+pub trait TestValue: Copy + PartialEq + Debug {
+    /// Encode `self` into a fresh buffer.
+    fn encode(self) -> BytesMut;
+
+    /// Decode a value from the start of `bytes`, returning it along with the
+    /// number of bytes it occupied.
+    fn decode(bytes: &[u8]) -> Result<(Self, usize), read::Error>;
+}
+
+macro_rules! impl_test_value {
+    ($wide:ty, $encode:ident, $decode:ident, $($ty:ty),*) => {
+        $(
+            impl TestValue for $ty {
+                fn encode(self) -> BytesMut {
+                    let (bytes, len) = $encode::<MAX_LEN>(self as $wide);
+                    BytesMut::from(&bytes[..len])
+                }
+
+                fn decode(bytes: &[u8]) -> Result<(Self, usize), read::Error> {
+                    let (value, len) = $decode(bytes.iter().copied())?;
+                    let value = <$ty>::try_from(value).map_err(|_| read::Error::Overflow)?;
+                    Ok((value, len))
+                }
+            }
+        )*
+    }
+}
+
+impl_test_value!(u64, encode_unsigned_const, decode_unsigned, u8, u16, u32, u64);
+impl_test_value!(i64, encode_signed_const, decode_signed, i8, i16, i32, i64);
+
+/// The function behind [`assert_leb_roundtrip!`](../macro.assert_leb_roundtrip.html).
+#[track_caller]
+pub fn assert_roundtrip<T: TestValue>(value: T) {
+    let encoded = value.encode();
+    match T::decode(&encoded) {
+        Ok((decoded, len)) if decoded == value && len == encoded.len() => {}
+        Ok((decoded, len)) => {
+            panic!("LEB128 round trip of {:?} failed: decoded {:?} from {} of {} bytes\n\
+                    encoded:\n{}\n\
+                    re-encoded:\n{}",
+                   value,
+                   decoded,
+                   len,
+                   encoded.len(),
+                   explain(&encoded),
+                   explain(decoded.encode()))
+        }
+        Err(e) => {
+            panic!("LEB128 round trip of {:?} failed: {}\nencoded:\n{}",
+                   value,
+                   e,
+                   explain(&encoded))
+        }
+    }
+}
+
+/// The function behind [`assert_leb_eq!`](../macro.assert_leb_eq.html).
+#[track_caller]
+pub fn assert_eq<T: TestValue>(bytes: &[u8], value: T) {
+    let expected = value.encode();
+    match T::decode(bytes) {
+        Ok((decoded, len)) if decoded == value && len == bytes.len() => {}
+        Ok((decoded, len)) => {
+            panic!("LEB128 bytes decode to {:?} ({} of {} bytes used), expected {:?}\n\
+                    actual:\n{}\n\
+                    expected:\n{}",
+                   decoded,
+                   len,
+                   bytes.len(),
+                   value,
+                   explain(bytes),
+                   explain(&expected))
+        }
+        Err(e) => {
+            panic!("LEB128 bytes failed to decode ({}), expected {:?}\n\
+                    actual:\n{}\n\
+                    expected:\n{}",
+                   e,
+                   value,
+                   explain(bytes),
+                   explain(&expected))
+        }
+    }
+}
+
+/// Assert that a value survives being encoded and decoded again.
+///
+/// Unsigned integers are encoded as unsigned LEB128 and signed integers as
+/// signed LEB128. On failure, the panic message includes the
+/// [`explain`](fn.explain.html) breakdown of both encodings.
+///
+/// ```
+/// # #[macro_use] extern crate nt_leb128;
+/// # fn main() {
+/// assert_leb_roundtrip!(624485u64);
+/// assert_leb_roundtrip!(-123456i64);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_leb_roundtrip {
+    ($value:expr) => {
+        $crate::test_util::assert_roundtrip($value)
+    };
+}
+
+/// Assert that `bytes` holds exactly one LEB128 encoding of `value`.
+///
+/// On failure, the panic message includes the [`explain`](fn.explain.html)
+/// breakdown of both the given bytes and the expected encoding.
+///
+/// ```
+/// # #[macro_use] extern crate nt_leb128;
+/// # fn main() {
+/// assert_leb_eq!(&[0xe5, 0x8e, 0x26], 624485u64);
+/// assert_leb_eq!(&[0x7e], -2i64);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_leb_eq {
+    ($bytes:expr, $value:expr) => {
+        $crate::test_util::assert_eq(&$bytes[..], $value)
+    };
+}
@@ -0,0 +1,100 @@
+//! Every pair is an encoding and the value it decodes to. The `*_INVALID`
+//! tables hold encodings that a conforming 64 bit decoder must reject.
+//!
+//! ```
+//! use nt_leb128::read::Leb128Read;
+//! use nt_leb128::test_vectors::DWARF_UNSIGNED;
+//!
+//! for &(bytes, value) in DWARF_UNSIGNED {
+//!     let mut readable = bytes;
+//!     assert_eq!(readable.read_unsigned().unwrap(), (value, bytes.len()));
+//! }
+//! ```
+
+/// The unsigned examples from figure 22 of the DWARF 5 specification.
+pub const DWARF_UNSIGNED: &[(&[u8], u64)] = &[(&[0x02], 2),
+                                              (&[0x7f], 127),
+                                              (&[0x80, 0x01], 128),
+                                              (&[0x81, 0x01], 129),
+                                              (&[0x82, 0x01], 130),
+                                              (&[0xb9, 0x64], 12857)];
+
+/// The signed examples from figure 23 of the DWARF 5 specification.
+pub const DWARF_SIGNED: &[(&[u8], i64)] = &[(&[0x02], 2),
+                                            (&[0x7e], -2),
+                                            (&[0xff, 0x00], 127),
+                                            (&[0x81, 0x7f], -127),
+                                            (&[0x80, 0x01], 128),
+                                            (&[0x80, 0x7f], -128),
+                                            (&[0x81, 0x01], 129),
+                                            (&[0xff, 0x7e], -129)];
+
+/// Unsigned encodings the WebAssembly specification accepts for `u64`
+/// despite being longer than necessary, along with the largest value.
+pub const WASM_UNSIGNED: &[(&[u8], u64)] =
+    &[(&[0x80, 0x00], 0),
+      (&[0x81, 0x80, 0x00], 1),
+      (&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00], 0),
+      (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01], u64::MAX)];
+
+/// Signed encodings the WebAssembly specification accepts for `i64` despite
+/// being longer than necessary, along with the extreme values.
+pub const WASM_SIGNED: &[(&[u8], i64)] =
+    &[(&[0x80, 0x00], 0),
+      (&[0xff, 0x7f], -1),
+      (&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00], 0),
+      (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f], -1),
+      (&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f], i64::MIN),
+      (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00], i64::MAX)];
+
+/// Unsigned encodings the WebAssembly specification rejects for `u64`:
+/// truncated, longer than ten bytes, or with unused bits set in the final
+/// byte.
+pub const WASM_UNSIGNED_INVALID: &[&[u8]] =
+    &[&[],
+      &[0x80],
+      &[0xff, 0xff],
+      &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00],
+      &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02],
+      &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]];
+
+/// Signed encodings the WebAssembly specification rejects for `i64`:
+/// truncated, longer than ten bytes, or with a final byte that isn't a sign
+/// extension of bit 63.
+pub const WASM_SIGNED_INVALID: &[&[u8]] =
+    &[&[],
+      &[0xff],
+      &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00],
+      &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+      &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7e],
+      &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x40]];
+
+/// The shortest unsigned encodings of the values at every change in encoded
+/// length, and of `u64::MAX`.
+pub const BOUNDARY_UNSIGNED: &[(&[u8], u64)] =
+    &[(&[0x00], 0),
+      (&[0x7f], 0x7f),
+      (&[0x80, 0x01], 0x80),
+      (&[0xff, 0x7f], 0x3fff),
+      (&[0x80, 0x80, 0x01], 0x4000),
+      (&[0xff, 0xff, 0xff, 0xff, 0x0f], 0xffff_ffff),
+      (&[0x80, 0x80, 0x80, 0x80, 0x10], 0x1_0000_0000),
+      (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f], (1 << 63) - 1),
+      (&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01], 1 << 63),
+      (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01], u64::MAX)];
+
+/// The shortest signed encodings of the values at every change in encoded
+/// length, and of `i64::MIN` and `i64::MAX`.
+pub const BOUNDARY_SIGNED: &[(&[u8], i64)] =
+    &[(&[0x00], 0),
+      (&[0x7f], -1),
+      (&[0x3f], 63),
+      (&[0x40], -64),
+      (&[0xc0, 0x00], 64),
+      (&[0xbf, 0x7f], -65),
+      (&[0xff, 0x3f], 0x1fff),
+      (&[0x80, 0x40], -0x2000),
+      (&[0xff, 0xff, 0xff, 0xff, 0x07], i32::MAX as i64),
+      (&[0x80, 0x80, 0x80, 0x80, 0x78], i32::MIN as i64),
+      (&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00], i64::MAX),
+      (&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f], i64::MIN)];
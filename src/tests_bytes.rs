@@ -1,7 +1,15 @@
+//! Tests of the traits through `bytes` buffers, which pass under every
+//! default flavor. Numbers the flavor may reject go through `Config::new()`,
+//! or are the `WIDEST` it takes.
+
 use super::*;
-use std;
+#[cfg(not(feature = "signed-only"))]
 use std::io;
-use bytes::{BytesMut, Bytes, BufMut, Buf};
+#[cfg(not(feature = "signed-only"))]
+use bytes::{Buf, BufMut};
+use bytes::{BytesMut, Bytes};
+#[cfg(not(feature = "signed-only"))]
+use bytes::buf::BufExt;
 
 #[test]
 fn test_low_bits_of_byte() {
@@ -20,10 +28,13 @@ fn test_low_bits_of_u64() {
     }
 }
 
-use read::LEB128Read;
-use write::LEB128Write;
+use read::Leb128Read;
+use write::Leb128Write;
+#[cfg(not(feature = "signed-only"))]
+use tests_slice::WIDEST;
 
 // Examples from the DWARF 4 standard, section 7.6, figure 22.
+#[cfg(not(feature = "signed-only"))]
 #[test]
 fn test_read_unsigned() {
     let mut readable = Bytes::from(&[2u8][..]);
@@ -52,6 +63,7 @@ fn test_read_unsigned() {
 }
 
 // Examples from the DWARF 4 standard, section 7.6, figure 23.
+#[cfg(not(feature = "unsigned-only"))]
 #[test]
 fn test_read_signed() {
     let mut readable = Bytes::from(&[2u8][..]);
@@ -79,6 +91,7 @@ fn test_read_signed() {
     assert_eq!(-129, readable.read_signed().expect("Should read number").0);
 }
 
+#[cfg(not(feature = "unsigned-only"))]
 #[test]
 fn test_read_signed_63_bits() {
     let mut readable = Bytes::from(&[CONTINUATION_BIT,
@@ -91,27 +104,30 @@ fn test_read_signed_63_bits() {
         CONTINUATION_BIT,
         0x40][..]);
     assert_eq!(-0x4000000000000000,
-               readable.read_signed().expect("Should read number").0);
+               readable.read_signed_with(&Config::new()).expect("Should read number").0);
 }
 
+#[cfg(not(feature = "signed-only"))]
 #[test]
 fn test_read_unsigned_not_enough_data() {
     let mut readable = Bytes::from(&[CONTINUATION_BIT][..]);
     match readable.read_unsigned() {
-        Err(read::Error::IoError(e)) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+        Err(read::Error::Incomplete) => {}
         otherwise => panic!("Unexpected: {:?}", otherwise),
     }
 }
 
+#[cfg(not(feature = "unsigned-only"))]
 #[test]
 fn test_read_signed_not_enough_data() {
     let mut readable = Bytes::from(&[CONTINUATION_BIT][..]);
     match readable.read_signed() {
-        Err(read::Error::IoError(e)) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+        Err(read::Error::Incomplete) => {}
         otherwise => panic!("Unexpected: {:?}", otherwise),
     }
 }
 
+#[cfg(not(feature = "unsigned-only"))]
 #[test]
 fn dogfood_signed() {
     fn inner(i: i64) {
@@ -128,9 +144,16 @@ fn dogfood_signed() {
     for i in -513..513 {
         inner(i);
     }
-    inner(std::i64::MIN);
+
+    let mut writable = BytesMut::new();
+    writable.write_signed_with(i64::MIN, &Config::new()).expect("Should write signed number");
+    let mut readable = writable.freeze();
+    let result = readable.read_signed_with(&Config::new())
+        .expect("Should be able to read it back again");
+    assert_eq!(i64::MIN, result.0);
 }
 
+#[cfg(not(feature = "signed-only"))]
 #[test]
 fn dogfood_unsigned() {
     for i in 0..1025 {
@@ -147,6 +170,7 @@ fn dogfood_unsigned() {
     }
 }
 
+#[cfg(not(feature = "signed-only"))]
 #[test]
 fn test_read_unsigned_overflow() {
     let mut readable = BytesMut::from(&[2u8 | CONTINUATION_BIT,
@@ -183,6 +207,7 @@ fn test_read_unsigned_overflow() {
     assert!(readable.read_unsigned().is_err());
 }
 
+#[cfg(not(feature = "unsigned-only"))]
 #[test]
 fn test_read_signed_overflow() {
     let mut readable = Bytes::from(&[2u8 | CONTINUATION_BIT,
@@ -219,6 +244,7 @@ fn test_read_signed_overflow() {
     assert!(readable.read_signed().is_err());
 }
 
+#[cfg(not(feature = "signed-only"))]
 #[test]
 fn test_read_multiple() {
     let mut readable = Bytes::from(&[2u8 | CONTINUATION_BIT, 1u8, 1u8][..]);
@@ -227,4 +253,1409 @@ fn test_read_multiple() {
                130u64);
     assert_eq!(readable.read_unsigned().expect("Should read first number").0,
                1u64);
-}
\ No newline at end of file
+}
+#[cfg(feature = "alloc")]
+#[test]
+fn test_explain() {
+    let explanation = explain([0xe5, 0x8e, 0x26, 0xff]);
+    assert!(explanation.complete);
+    assert_eq!(explanation.len(), 3);
+    assert_eq!(explanation.bytes[1].payload, 0x0e);
+    assert_eq!(explanation.bytes[1].shift, 7);
+    assert_eq!(explanation.bytes[1].running, 0x65 | 0x0e << 7);
+    assert!(explanation.bytes[1].continuation);
+    assert!(!explanation.bytes[2].continuation);
+    assert_eq!(explanation.unsigned(), Some(624485));
+    assert_eq!(explanation.signed(), Some(624485));
+
+    let explanation = explain([0x7f]);
+    assert_eq!(explanation.unsigned(), Some(127));
+    assert_eq!(explanation.signed(), Some(-1));
+}
+
+#[cfg(all(feature = "alloc", not(feature = "unsigned-only")))]
+#[test]
+fn test_explain_agrees_with_readers() {
+    let mut writable = BytesMut::new();
+    writable.write_signed_with(i64::MIN, &Config::new()).expect("Should write number");
+    let explanation = explain(&writable);
+    assert_eq!(explanation.signed(), Some(i64::MIN));
+    assert_eq!(explanation.unsigned(), None);
+
+    let explanation = explain([CONTINUATION_BIT]);
+    assert!(!explanation.complete);
+    assert_eq!(explanation.unsigned(), None);
+    assert!(explanation.to_string().contains("incomplete"));
+}
+
+#[cfg(all(feature = "test-util", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_assertion_macros() {
+    assert_leb_roundtrip!(0u8);
+    assert_leb_roundtrip!(u64::MAX);
+    assert_leb_roundtrip!(i64::MIN);
+    assert_leb_roundtrip!(-1i32);
+    assert_leb_eq!([2u8 | CONTINUATION_BIT, 1], 130u64);
+    assert_leb_eq!([0x7fu8 | CONTINUATION_BIT, 0x7e], -129i64);
+}
+
+#[cfg(all(feature = "test-util", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_assert_leb_eq_failure_explains() {
+    let result = std::panic::catch_unwind(|| assert_leb_eq!([2u8 | CONTINUATION_BIT, 1], 131u64));
+    let message = result.expect_err("Should fail")
+        .downcast::<String>()
+        .expect("Should have a formatted message");
+    assert!(message.contains("actual:"));
+    assert!(message.contains("unsigned: 130"));
+    assert!(message.contains("unsigned: 131"));
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_free_functions() {
+    let mut writable = BytesMut::new();
+    assert_eq!(write_unsigned(&mut writable, 130).expect("Should write number"), 2);
+    assert_eq!(write_signed(&mut writable, -129).expect("Should write number"), 2);
+
+    let mut readable = writable.freeze();
+    assert_eq!(read_unsigned(&mut readable).expect("Should read number"), (130, 2));
+    assert_eq!(read_signed(&mut readable).expect("Should read number"), (-129, 2));
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+#[test]
+fn test_codec_bound() {
+    fn transcode<C: Leb128Codec>(codec: &mut C, val: u64) -> i64 {
+        codec.write_signed(val as i64).expect("Should write number");
+        codec.read_signed().expect("Should read number").0
+    }
+
+    let mut buf = BytesMut::new();
+    assert_eq!(transcode(&mut buf, 12857), 12857);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_read_decoded() {
+    let mut readable = Bytes::from(&[2u8 | CONTINUATION_BIT, 1, 0x7e][..]);
+    let decoded = readable.read_unsigned_decoded().expect("Should read number");
+    assert_eq!((decoded.value, decoded.len), (130, 2));
+
+    let decoded = readable.read_signed_decoded().expect("Should read number");
+    assert_eq!(<(i64, usize)>::from(decoded), (-2, 1));
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_counting_writer() {
+    let mut writer = write::CountingWriter::new(BytesMut::new());
+    for i in 0..200 {
+        writer.write_unsigned(i).expect("Should write number");
+    }
+    writer.write_signed(-129).expect("Should write number");
+    assert_eq!(writer.total_values(), 201);
+    assert_eq!(writer.total_bytes(), 128 + 72 * 2 + 2);
+    assert_eq!(writer.total_bytes(), writer.get_ref().len());
+
+    writer.reset();
+    assert_eq!((writer.total_values(), writer.total_bytes()), (0, 0));
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_iter_unsigned() {
+    let mut readable = Bytes::from(&[2u8, CONTINUATION_BIT, 1, 127][..]);
+    let values: Vec<u64> = iter_unsigned(&mut readable)
+        .collect::<Result<_, _>>()
+        .expect("Should read all numbers");
+    assert_eq!(values, [2, 128, 127]);
+    assert!(readable.is_empty());
+
+    let mut iter = iter_signed(Bytes::from(&[0x7eu8, CONTINUATION_BIT][..]));
+    assert_eq!(iter.next().map(|r| r.expect("Should read number")), Some(-2));
+    match iter.next() {
+        Some(Err(read::Error::Incomplete)) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert!(iter.next().is_none());
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_encode_buf() {
+    let values = (0..1025u64).chain(vec![u64::MAX]);
+    // Plain LEB128, which `EncodeBuf` always produces.
+    let mut expected = Vec::new();
+    for i in values.clone() {
+        write::put_unsigned(&mut expected, i);
+    }
+
+    let mut buf = lazy::EncodeBuf::new(values);
+    assert_eq!(buf.remaining(), expected.len());
+
+    let mut collected = BytesMut::new();
+    collected.put_slice(&buf.bytes()[..1]);
+    buf.advance(1);
+    // Advance across several values at once.
+    collected.put_slice(&expected[1..300]);
+    buf.advance(299);
+    collected.put(buf);
+    assert_eq!(collected, expected);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_peek() {
+    let mut readable = Bytes::from(&[2u8 | CONTINUATION_BIT, 1, 0x7e][..]);
+    assert_eq!(readable.peek_unsigned().expect("Should peek number"), (130, 2));
+    assert_eq!(readable.peek_signed().expect("Should peek number"), (130, 2));
+    assert_eq!(readable.remaining(), 3);
+
+    readable.advance(2);
+    assert_eq!(readable.peek_signed().expect("Should peek number"), (-2, 1));
+    assert_eq!(readable.remaining(), 1);
+
+    // A value straddling the chunks of a chained buffer.
+    let chained = (&[CONTINUATION_BIT][..]).chain(&[CONTINUATION_BIT, 1][..]);
+    assert_eq!(chained.peek_unsigned().expect("Should peek number"), (1 << 14, 3));
+
+    let truncated = Bytes::from(&[CONTINUATION_BIT][..]);
+    assert!(truncated.peek_unsigned().is_err());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_skip() {
+    let mut readable = Bytes::from(&[2u8 | CONTINUATION_BIT, 1, 0x7e, 3][..]);
+    assert_eq!(readable.skip_unsigned().expect("Should skip number"), 2);
+    assert_eq!(readable.skip_signed().expect("Should skip number"), 1);
+    assert_eq!(readable.read_unsigned().expect("Should read number").0, 3);
+
+    let mut chained = (&[CONTINUATION_BIT][..]).chain(&[CONTINUATION_BIT, 1, 5][..]);
+    assert_eq!(chained.skip_unsigned().expect("Should skip number"), 3);
+    assert_eq!(chained.read_unsigned().expect("Should read number").0, 5);
+
+    let mut max = BytesMut::new();
+    max.write_unsigned_with(u64::MAX, &Config::new()).expect("Should write number");
+    max.write_signed_with(i64::MIN, &Config::new()).expect("Should write number");
+    let mut max = max.freeze();
+    if cfg!(feature = "default-flavor-wasm") {
+        // Skipping accepts what reading does, which is only 32 bits here.
+        assert!(matches!(max.skip_unsigned(), Err(read::Error::Overflow)));
+        assert!(matches!(max.skip_signed(), Err(read::Error::Overflow)));
+    } else {
+        assert_eq!(max.skip_unsigned().expect("Should skip number"), 10);
+        assert_eq!(max.skip_signed().expect("Should skip number"), 10);
+    }
+
+    let mut overflow = Bytes::from(&[0xffu8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02][..]);
+    // Protobuf discards the bits beyond 64 rather than rejecting them.
+    let protobuf = cfg!(feature = "default-flavor-protobuf");
+    assert_eq!(overflow.clone().skip_unsigned().is_err(), !protobuf);
+    assert_eq!(overflow.skip_signed().is_err(), !protobuf);
+
+    let mut truncated = Bytes::from(&[CONTINUATION_BIT][..]);
+    match truncated.skip_unsigned() {
+        Err(read::Error::Incomplete) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_take() {
+    let mut readable = Bytes::from(&[2u8 | CONTINUATION_BIT, 1, 2 | CONTINUATION_BIT, 1, 7][..]);
+    {
+        let mut unit = read::take(&mut readable, 3);
+        assert_eq!(unit.read_unsigned().expect("Should read number").0, 130);
+        assert_eq!(unit.limit(), 1);
+        match unit.read_unsigned() {
+            Err(read::Error::Incomplete) => {}
+            otherwise => panic!("Unexpected: {:?}", otherwise),
+        }
+        assert_eq!(unit.limit(), 0);
+    }
+    assert_eq!(readable.read_unsigned().expect("Should read number").0, 1);
+
+    let mut unit = read::take(&mut readable, 10);
+    assert_eq!(unit.remaining(), 1);
+    assert_eq!(unit.skip_rest(), 1);
+    assert_eq!(unit.limit(), 9);
+    assert!(readable.is_empty());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_write_iter() {
+    let mut writable = BytesMut::new();
+    assert_eq!(writable.write_unsigned_iter(vec![2, 130, 12857]).expect("Should write numbers"), 5);
+    assert_eq!(writable.write_signed_iter(-129..-127).expect("Should write numbers"), 4);
+
+    let values: Vec<u64> = iter_unsigned(writable.split_to(5).freeze())
+        .collect::<Result<_, _>>()
+        .expect("Should read numbers");
+    assert_eq!(values, [2, 130, 12857]);
+    let values: Vec<i64> = iter_signed(writable.freeze())
+        .collect::<Result<_, _>>()
+        .expect("Should read numbers");
+    assert_eq!(values, [-129, -128]);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_const_encoding_agrees_with_writer() {
+    fn check_unsigned(i: u64) {
+        let mut writable = BytesMut::new();
+        writable.write_unsigned_with(i, &Config::new()).expect("Should write number");
+        let (encoded, len) = encode_unsigned_const::<10>(i);
+        assert_eq!(&encoded[..len], &writable[..]);
+        assert_eq!(encoded_len_unsigned(i), len);
+    }
+    fn check_signed(i: i64) {
+        let mut writable = BytesMut::new();
+        writable.write_signed_with(i, &Config::new()).expect("Should write number");
+        let (encoded, len) = encode_signed_const::<10>(i);
+        assert_eq!(&encoded[..len], &writable[..]);
+        assert_eq!(encoded_len_signed(i), len);
+    }
+
+    for shift in 0..64 {
+        for &delta in &[-1i64, 0, 1] {
+            let i = (1u64 << shift).wrapping_add(delta as u64);
+            check_unsigned(i);
+            check_signed(i as i64);
+            check_signed((i as i64).wrapping_neg());
+        }
+    }
+    check_unsigned(u64::MAX);
+    check_signed(i64::MIN);
+    check_signed(i64::MAX);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_leb128_macro() {
+    const UNSIGNED: [u8; 2] = leb128!(12857u64);
+    assert_eq!(UNSIGNED, [57 | CONTINUATION_BIT, 100]);
+    assert_eq!(leb128!(signed -129), [0x7f | CONTINUATION_BIT, 0x7e]);
+    assert_eq!(leb128!(u64::MAX).len(), 10);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_const_decoding() {
+    const TABLE: &[u8] = &[2, 57 | CONTINUATION_BIT, 100, 0x7f | CONTINUATION_BIT, 0x7e];
+    const SECOND: (u64, usize) = match decode_unsigned_const(TABLE, 1) {
+        Ok(decoded) => decoded,
+        Err(_) => panic!("Should decode"),
+    };
+    assert_eq!(SECOND, (12857, 2));
+    assert_eq!(decode_signed_const(TABLE, 3), Ok((-129, 2)));
+    assert_eq!(decode_unsigned_const(TABLE, 5), Err(ConstDecodeError::UnexpectedEof));
+    assert_eq!(decode_unsigned_const(&[CONTINUATION_BIT], 0),
+               Err(ConstDecodeError::UnexpectedEof));
+    assert_eq!(decode_unsigned_const(&[0xff; 10], 0), Err(ConstDecodeError::Overflow));
+
+    let mut max = BytesMut::new();
+    max.write_signed_with(i64::MIN, &Config::new()).expect("Should write number");
+    assert_eq!(decode_signed_const(&max, 0), Ok((i64::MIN, 10)));
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_codec_roundtrip() {
+    let mut writable = BytesMut::new();
+    let names = vec!["abc".to_string(), "ünïcödé".to_string()];
+    let mut written = 130u32.encode(&mut writable).expect("Should encode");
+    written += (-2i8).encode(&mut writable).expect("Should encode");
+    written += names.encode(&mut writable).expect("Should encode");
+    assert_eq!(written, writable.len());
+
+    let mut readable = writable.freeze();
+    assert_eq!(u32::decode(&mut readable).expect("Should decode"), 130);
+    assert_eq!(i8::decode(&mut readable).expect("Should decode"), -2);
+    assert_eq!(Vec::<String>::decode(&mut readable).expect("Should decode"), names);
+    assert!(readable.is_empty());
+
+    let mut too_big = Bytes::from(&[CONTINUATION_BIT, 2][..]);
+    match u8::decode(&mut too_big) {
+        Err(read::Error::Overflow) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+}
+
+#[cfg(all(feature = "derive", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_derive() {
+    #[derive(Debug, PartialEq, Leb128Encode, Leb128Decode)]
+    struct Point(i32, i32);
+
+    #[derive(Debug, PartialEq, Leb128Encode, Leb128Decode)]
+    struct Unit;
+
+    #[derive(Debug, PartialEq, Leb128Encode, Leb128Decode)]
+    struct Shape<T> {
+        id: u64,
+        name: String,
+        points: Vec<Point>,
+        tag: T,
+        unit: Unit,
+    }
+
+    let shape = Shape {
+        id: 12857,
+        name: "triangle".into(),
+        points: vec![Point(0, 0), Point(-129, 64), Point(1, -1)],
+        tag: true,
+        unit: Unit,
+    };
+    let mut writable = BytesMut::new();
+    let written = shape.encode(&mut writable).expect("Should encode");
+    assert_eq!(written, writable.len());
+    assert_eq!(&writable[..3], &[57 | CONTINUATION_BIT, 100, 8]);
+
+    let mut readable = writable.freeze();
+    assert_eq!(Shape::<bool>::decode(&mut readable).expect("Should decode"), shape);
+    assert!(readable.is_empty());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_encoded_values() {
+    for &i in &[0u64, 127, 128, 12857, u64::MAX] {
+        let encoded = EncodedU64::from(i);
+        let mut writable = BytesMut::new();
+        writable.write_unsigned_with(i, &Config::new()).expect("Should write number");
+        assert_eq!(&*encoded, &writable[..]);
+        assert_eq!(encoded, i);
+        assert_eq!(u64::from(encoded), i);
+    }
+
+    let encoded = EncodedI64::from(-129);
+    assert_eq!(encoded.as_slice(), &[0x7f | CONTINUATION_BIT, 0x7e]);
+    assert_eq!(encoded.len(), 2);
+    assert!(encoded < -128 && -130 < encoded);
+    assert_eq!(EncodedI64::new(i64::MIN).value(), i64::MIN);
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_lazy_uleb() {
+    for &i in &[0u64, 127, 128, 12857, u64::MAX] {
+        let mut lazy = lazy::LazyUleb::new(i);
+        assert_eq!(lazy.remaining(), encoded_len_unsigned(i));
+        let mut collected = BytesMut::new();
+        while lazy.has_remaining() {
+            assert_eq!(lazy.bytes().len(), 1);
+            collected.put_u8(lazy.get_u8());
+        }
+        assert!(lazy.bytes().is_empty());
+        assert_eq!(&collected[..], &*EncodedU64::from(i));
+    }
+
+    let mut chained = (&[1u8][..]).chain(lazy::LazyUleb::from(12857)).chain(&[2u8][..]);
+    assert_eq!(chained.to_bytes(), &[1u8, 57 | CONTINUATION_BIT, 100, 2][..]);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_wrapper_try_from() {
+    use std::convert::{TryFrom, TryInto};
+
+    assert_eq!(Uleb128::try_from(&[2u8 | CONTINUATION_BIT, 1][..]).expect("Should decode"),
+               Uleb128(130));
+    let decoded: Sleb128 = (&[0x7fu8 | CONTINUATION_BIT, 0x7e][..]).try_into().expect("Should decode");
+    assert_eq!(i64::from(decoded), -129);
+
+    match Uleb128::try_from(&[2u8, 0][..]) {
+        Err(read::Error::IoError(e)) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert!(Sleb128::try_from(&[CONTINUATION_BIT][..]).is_err());
+    assert!(Uleb128::try_from(&[][..]).is_err());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_validate() {
+    assert_eq!(validate_unsigned([2u8 | CONTINUATION_BIT, 1, 9]).expect("Should validate"), 2);
+    assert_eq!(validate_signed([0x7e]).expect("Should validate"), 1);
+    assert!(validate_unsigned([CONTINUATION_BIT]).is_err());
+    assert!(validate_unsigned([0xff; 10]).is_err());
+    assert!(validate_unsigned([]).is_err());
+
+    // Overlong, but well-formed.
+    assert_eq!(validate_unsigned([2u8 | CONTINUATION_BIT, 0]).expect("Should validate"), 2);
+    match validate_unsigned_canonical([2u8 | CONTINUATION_BIT, 0]) {
+        Err(read::Error::NonCanonical) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    match validate_signed_canonical([0x7fu8 | CONTINUATION_BIT, 0x7f]) {
+        Err(read::Error::NonCanonical) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert!(validate_unsigned_canonical([0]).is_ok());
+
+    // Everything the writers produce is canonical.
+    for i in -513i64..513 {
+        let mut writable = BytesMut::new();
+        writable.write_signed_with(i, &Config::new()).expect("Should write number");
+        writable.write_unsigned_with(i as u64, &Config::new()).expect("Should write number");
+        let signed_len = validate_signed_canonical(&writable).expect("Should be canonical");
+        validate_unsigned_canonical(&writable[signed_len..]).expect("Should be canonical");
+    }
+    for &i in &[i64::MIN, i64::MAX] {
+        validate_signed_canonical(EncodedI64::from(i)).expect("Should be canonical");
+    }
+    validate_unsigned_canonical(EncodedU64::from(u64::MAX)).expect("Should be canonical");
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_boundaries() {
+    let bytes = [2u8, 2 | CONTINUATION_BIT, 1, 0x7e, CONTINUATION_BIT];
+    let mut ranges = boundaries(&bytes);
+    assert_eq!(ranges.next().map(|r| r.expect("Should scan")), Some(0..1));
+    assert_eq!(ranges.next().map(|r| r.expect("Should scan")), Some(1..3));
+    assert_eq!(ranges.next().map(|r| r.expect("Should scan")), Some(3..4));
+    assert!(ranges.next().expect("Should report the truncated value").is_err());
+    assert!(ranges.next().is_none());
+
+    let mut writable = BytesMut::new();
+    writable.write_signed_with(i64::MIN, &Config::new()).expect("Should write number");
+    writable.write_signed(-1).expect("Should write number");
+    let ranges: Vec<_> = boundaries_signed(&writable)
+        .collect::<Result<_, _>>()
+        .expect("Should scan");
+    assert_eq!(ranges, [0..10, 10..11]);
+    assert!(boundaries(&writable).next().expect("Should scan").is_err());
+    assert!(boundaries(&[]).next().is_none());
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_split_at_boundaries() {
+    let mut writable = BytesMut::new();
+    writable.write_unsigned_iter(0..5000).expect("Should write numbers");
+
+    for &size in &[0, 1, 7, 100, 4096, 100_000] {
+        let chunks = split_at_boundaries(&writable, size);
+        assert_eq!(chunks.iter().map(|c| c.len()).sum::<usize>(), writable.len());
+        let mut values = Vec::new();
+        for chunk in chunks {
+            assert!(chunk.len() <= size.max(1) + 9);
+            values.extend(iter_unsigned(chunk).map(|r| r.expect("Should read number")));
+        }
+        assert_eq!(values, (0..5000).collect::<Vec<_>>());
+    }
+    assert!(split_at_boundaries(&[], 16).is_empty());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_count_values() {
+    let mut writable = BytesMut::new();
+    writable.write_unsigned_iter(0..5000).expect("Should write numbers");
+    writable.write_signed_with(i64::MIN, &Config::new()).expect("Should write number");
+    writable.write_signed(-1).expect("Should write number");
+    assert_eq!(count_values(&writable).expect("Should count"), 5002);
+    assert_eq!(count_values([]).expect("Should count"), 0);
+
+    match count_values([2, CONTINUATION_BIT]) {
+        Err(read::Error::Incomplete) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    match count_values([0xff; 11]) {
+        Err(read::Error::Overflow) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_cmp_encoded() {
+    use std::cmp::Ordering;
+
+    let values = [0u64, 1, 127, 128, 129, 255, 256, 12857, 1 << 35, u64::MAX - 1, u64::MAX];
+    for &a in &values {
+        for &b in &values {
+            assert_eq!(cmp_encoded(EncodedU64::from(a), EncodedU64::from(b))
+                           .expect("Should compare"),
+                       a.cmp(&b));
+        }
+    }
+
+    // Overlong encodings compare by value.
+    assert_eq!(cmp_encoded([CONTINUATION_BIT, CONTINUATION_BIT, 0], [0]).expect("Should compare"),
+               Ordering::Equal);
+    assert_eq!(cmp_encoded([0x7f | CONTINUATION_BIT, 0], [CONTINUATION_BIT, 1])
+                   .expect("Should compare"),
+               Ordering::Less);
+    assert!(cmp_encoded([CONTINUATION_BIT], [0]).is_err());
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_patch_unsigned_in_place() {
+    use slice::{patch_unsigned_in_place, PatchOutcome};
+
+    let mut writable = BytesMut::new();
+    writable.write_unsigned_iter(vec![1, 12857, 3]).expect("Should write numbers");
+
+    for &i in &[0u64, 127, 128, 16383] {
+        assert_eq!(patch_unsigned_in_place(&mut writable, 1, i).expect("Should patch"),
+                   PatchOutcome::Patched { len: 2 });
+        let values: Vec<u64> = iter_unsigned(&writable[..])
+            .collect::<Result<_, _>>()
+            .expect("Should read numbers");
+        assert_eq!(values, [1, i, 3]);
+    }
+
+    let before = writable.clone();
+    assert_eq!(patch_unsigned_in_place(&mut writable, 1, 16384).expect("Should check"),
+               PatchOutcome::NeedsResize { old_len: 2, new_len: 3 });
+    assert_eq!(writable, before);
+
+    assert!(patch_unsigned_in_place(&mut writable, 4, 0).is_err());
+    assert!(patch_unsigned_in_place(&mut [CONTINUATION_BIT], 0, 0).is_err());
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_placeholder() {
+    let mut writable = BytesMut::new();
+    writable.write_unsigned(7).expect("Should write number");
+    let placeholder = write::reserve_unsigned(&mut writable, 3);
+    assert_eq!((placeholder.offset(), placeholder.len()), (1, 3));
+    writable.write_unsigned_iter(0..200).expect("Should write numbers");
+    let body_len = writable.len() - 4;
+    placeholder.fill(&mut writable, body_len as u64).expect("Should fill");
+
+    let mut readable = writable.freeze();
+    assert_eq!(readable.read_unsigned().expect("Should read number"), (7, 1));
+    assert_eq!(readable.read_unsigned().expect("Should read number"), (body_len as u64, 3));
+    assert_eq!(readable.remaining(), body_len);
+
+    let mut small = Vec::new();
+    let placeholder = write::reserve_unsigned(&mut small, 1);
+    assert_eq!(placeholder.fill(&mut small, 128),
+               Err(write::Error::InsufficientSpace { needed: 2, available: 1 }));
+    assert_eq!(small, [0]);
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_length_prefixed() {
+    let mut writable = BytesMut::new();
+    assert_eq!(writable.write_bytes(&[0xff; 200]).expect("Should write bytes"), 202);
+    assert_eq!(writable.write_str("ünïcödé").expect("Should write string"), 12);
+    writable.write_bytes(&[0xff, 0xfe]).expect("Should write bytes");
+
+    let mut readable = writable.freeze();
+    assert_eq!(readable.read_bytes(200).expect("Should read bytes"), vec![0xff; 200]);
+    match readable.clone().read_string(10) {
+        Err(read::Error::LengthExceeded { len: 11, max: 10 }) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert_eq!(readable.read_string(11).expect("Should read string"), "ünïcödé");
+    match readable.read_string(2) {
+        Err(read::Error::IoError(e)) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+
+    let mut truncated = Bytes::from(&[5u8, 1, 2][..]);
+    match truncated.read_bytes(usize::MAX) {
+        Err(read::Error::Incomplete) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+}
+
+// The deprecated method must keep working until it's removed.
+#[cfg(not(feature = "signed-only"))]
+#[allow(deprecated)]
+#[test]
+fn test_uleb_pair() {
+    let mut writable = BytesMut::new();
+    assert_eq!(writable.write_uleb_pair(12857, 0x11).expect("Should write pair"), 3);
+    writable.write_uleb_pair(WIDEST, 130).expect("Should write pair");
+
+    let mut readable = writable.clone().freeze();
+    assert_eq!(readable.read_uleb_pair().expect("Should read pair"), (12857, 0x11));
+    assert_eq!(readable.read_uleb_pair().expect("Should read pair"), (WIDEST, 130));
+    assert!(readable.is_empty());
+
+    // A pair straddling the chunks of a chained buffer.
+    let mut chained = (&writable[..4]).chain(&writable[4..]);
+    assert_eq!(chained.read_uleb_pair().expect("Should read pair"), (12857, 0x11));
+    assert_eq!(chained.read_uleb_pair().expect("Should read pair"), (WIDEST, 130));
+
+    let mut truncated = Bytes::from(&[2u8, CONTINUATION_BIT][..]);
+    assert!(truncated.read_uleb_pair().is_err());
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_unsigned_pair_and_triple() {
+    let mut writable = BytesMut::new();
+    writable.write_unsigned_iter(vec![12857, 0x11, WIDEST, 1, 130]).expect("Should write");
+
+    let mut readable = writable.clone().freeze();
+    assert_eq!(readable.read_unsigned_pair().expect("Should read pair"), ((12857, 0x11), 3));
+    assert_eq!(readable.read_unsigned_triple().expect("Should read triple"),
+               ((WIDEST, 1, 130), 3 + encoded_len_unsigned(WIDEST)));
+    assert!(readable.is_empty());
+
+    // Groups straddling the chunks of a chained buffer, split at every point.
+    for split in 0..writable.len() {
+        let mut chained = (&writable[..split]).chain(&writable[split..]);
+        assert_eq!(chained.read_unsigned_triple().expect("Should read triple"),
+                   ((12857, 0x11, WIDEST), 3 + encoded_len_unsigned(WIDEST)));
+        assert_eq!(chained.read_unsigned_pair().expect("Should read pair"), ((1, 130), 3));
+    }
+
+    let mut truncated = Bytes::from(&[2u8, 3, CONTINUATION_BIT][..]);
+    assert!(truncated.read_unsigned_triple().unwrap_err().is_incomplete());
+    // The numbers before the one that failed stay consumed.
+    assert!(truncated.is_empty());
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_skip_n() {
+    let mut writable = BytesMut::new();
+    writable.write_unsigned_iter(0..1000).expect("Should write numbers");
+
+    let mut readable = writable.clone().freeze();
+    assert_eq!(readable.skip_n(0).expect("Should skip nothing"), 0);
+    assert_eq!(readable.skip_n(200).expect("Should skip numbers"), 128 + 72 * 2);
+    assert_eq!(readable.read_unsigned().expect("Should read number").0, 200);
+
+    let mut chained = (&writable[..3]).chain(&writable[3..]);
+    chained.skip_n(999).expect("Should skip numbers");
+    assert_eq!(chained.read_unsigned().expect("Should read number").0, 999);
+
+    let mut readable = writable.freeze();
+    match readable.skip_n(1001) {
+        Err(read::Error::Incomplete) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    match Bytes::from(&[0xffu8; 12][..]).skip_n(1) {
+        Err(read::Error::Overflow) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_encoded_len_measures_without_writing() {
+    let names = vec!["abc".to_string(), "x".repeat(300)];
+    let values = vec![WIDEST, 0, 128];
+    let mut writable = BytesMut::new();
+    assert_eq!(names.encode(&mut writable).expect("Should encode"), names.encoded_len());
+    assert_eq!(values.encode(&mut writable).expect("Should encode"), values.encoded_len());
+    assert_eq!((-129i16).encoded_len(), 2);
+    assert_eq!(Sleb128(i64::MIN).encoded_len(), 10);
+
+    let mut measure = write::Measure::new();
+    names.encode(&mut measure).expect("Should encode");
+    assert_eq!(measure.len(), names.encoded_len());
+}
+
+#[cfg(all(feature = "derive", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_derive_encoded_len() {
+    use tests_slice::WIDEST_POSITIVE;
+
+    #[derive(Leb128Encode)]
+    struct Inner(i64, String);
+
+    #[derive(Leb128Encode)]
+    struct Message {
+        id: u64,
+        inner: Vec<Inner>,
+    }
+
+    let message = Message {
+        id: WIDEST,
+        inner: vec![Inner(-1, "a".into()), Inner(WIDEST_POSITIVE, "é".repeat(100))],
+    };
+    let mut writable = BytesMut::with_capacity(message.encoded_len());
+    message.encode(&mut writable).expect("Should encode");
+    assert_eq!(writable.len(), message.encoded_len());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_encoded_len_iter() {
+    let mut writable = BytesMut::new();
+    for shift in 0..64 {
+        writable.write_unsigned_with(1 << shift, &Config::new()).expect("Should write number");
+    }
+    assert_eq!(encoded_len_unsigned_iter((0..64).map(|shift| 1u64 << shift)), writable.len());
+
+    let mut writable = BytesMut::new();
+    writable.write_signed_iter(-1025..1025).expect("Should write numbers");
+    assert_eq!(encoded_len_signed_iter(-1025..1025), writable.len());
+    assert_eq!(encoded_len_unsigned_iter(vec![]), 0);
+}
+
+#[cfg(all(feature = "ffi", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_ffi() {
+    use ffi::*;
+
+    let mut buf = [0u8; 10];
+    let mut len = 0usize;
+    unsafe {
+        assert_eq!(leb128_write_u64(624485, buf.as_mut_ptr(), buf.len(), &mut len), LEB128_OK);
+        assert_eq!(&buf[..len], &[0xe5, 0x8e, 0x26]);
+
+        let mut val = 0u64;
+        let mut read = 0usize;
+        assert_eq!(leb128_read_u64(buf.as_ptr(), len, &mut val, &mut read), LEB128_OK);
+        assert_eq!((val, read), (624485, 3));
+        assert_eq!(leb128_read_u64(buf.as_ptr(), 2, &mut val, &mut read), LEB128_ERR_EOF);
+
+        let mut sval = 0i64;
+        assert_eq!(leb128_write_i64(-123456, buf.as_mut_ptr(), buf.len(), &mut len), LEB128_OK);
+        assert_eq!(leb128_read_i64(buf.as_ptr(), len, &mut sval, std::ptr::null_mut()), LEB128_OK);
+        assert_eq!(sval, -123456);
+
+        assert_eq!(leb128_write_u64(u64::MAX, buf.as_mut_ptr(), 9, &mut len),
+                   LEB128_ERR_BUFFER_TOO_SMALL);
+        assert_eq!(len, 10);
+        assert_eq!(leb128_read_u64(std::ptr::null(), 1, &mut val, &mut read), LEB128_ERR_NULL);
+        assert_eq!(leb128_read_u64(&[0xff; 10][0], 10, &mut val, &mut read), LEB128_ERR_OVERFLOW);
+    }
+    assert_eq!(leb128_encoded_len_u64(u64::MAX), 10);
+    assert_eq!(leb128_encoded_len_i64(-64), 1);
+}
+
+#[cfg(all(feature = "wasm", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_wasm_bindings() {
+    use wasm::*;
+
+    assert_eq!(encode_unsigned(624485), vec![0xe5, 0x8e, 0x26]);
+    assert_eq!(decode_signed(&encode_signed(-123456)).ok(), Some(-123456));
+
+    let vals = [0, 1, 127, 128, u64::MAX];
+    assert_eq!(decode_unsigned_all(&encode_unsigned_all(&vals)).ok(), Some(vals.to_vec()));
+    let vals = [0, -1, 63, -64, i64::MIN];
+    assert_eq!(decode_signed_all(&encode_signed_all(&vals)).ok(), Some(vals.to_vec()));
+}
+
+#[cfg(all(feature = "python", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_python_module() {
+    use pyo3::prelude::*;
+    use pyo3::types::PyModule;
+
+    Python::initialize();
+    Python::attach(|py| {
+        let module = PyModule::new(py, "nt_leb128").expect("Should create module");
+        python::init(&module).expect("Should populate module");
+
+        let encoded = module.call_method1("encode_unsigned_all", (vec![2u64, 127, 128],))
+            .expect("Should encode");
+        assert_eq!(encoded.extract::<Vec<u8>>().ok(), Some(vec![0x02, 0x7f, 0x80, 0x01]));
+        let decoded = module.call_method1("decode_unsigned_all", (encoded,))
+            .expect("Should decode");
+        assert_eq!(decoded.extract::<Vec<u64>>().ok(), Some(vec![2, 127, 128]));
+
+        let err = module.call_method1("decode_signed", (pyo3::types::PyBytes::new(py, &[0x80]),))
+            .expect_err("Should fail on truncated input");
+        assert!(err.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+    });
+}
+
+#[cfg(all(feature = "arbitrary", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_fuzz_edge_cases() {
+    use arbitrary::Unstructured;
+    use fuzz::{edge_case, Edge};
+
+    let data: Vec<u8> = (0u32..4096).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+    let mut u = Unstructured::new(&data);
+    for _ in 0..40 {
+        for &edge in &Edge::ALL {
+            for &signed in &[false, true] {
+                let bytes = edge_case(&mut u, edge, signed).expect("Should generate");
+                let (len, canonical) = if signed {
+                    (validate_signed(&bytes), validate_signed_canonical(&bytes))
+                } else {
+                    (validate_unsigned(&bytes), validate_unsigned_canonical(&bytes))
+                };
+                assert_eq!(len.is_ok(), edge.is_valid(), "{:?} {:02x?}", edge, bytes);
+                match edge {
+                    Edge::Canonical => assert_eq!(canonical.ok(), Some(bytes.len())),
+                    Edge::Overlong => assert!(canonical.is_err()),
+                    Edge::Maximal => assert_eq!(canonical.ok(), Some(10)),
+                    Edge::Truncated => assert!(bytes.len() < 10),
+                    Edge::Overflowing => assert!(matches!(len, Err(read::Error::Overflow))),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "proptest", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+    use strategy::*;
+
+    proptest! {
+        #[test]
+        fn any_encoding_decodes_to_its_canonical_value(bytes in any_encoded_unsigned()) {
+            let (val, len) = read::decode_unsigned(bytes.iter().copied())
+                .expect("Should read number");
+            prop_assert_eq!(len, bytes.len());
+            prop_assert!(cmp_encoded(&bytes, EncodedU64::new(val)).is_ok());
+        }
+
+        #[test]
+        fn overlong_signed_encodings_keep_their_value(bytes in overlong_encoding_signed(3)) {
+            prop_assert!(validate_signed_canonical(&bytes).is_err());
+            match read::decode_signed(bytes.iter().copied()) {
+                Ok((val, _)) => prop_assert!(EncodedI64::new(val).len() < bytes.len()),
+                Err(_) => prop_assert!(bytes.len() > 10),
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "test-vectors", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_vectors_agree_with_codec() {
+    use test_vectors::*;
+
+    let unsigned = DWARF_UNSIGNED.iter().chain(WASM_UNSIGNED).chain(BOUNDARY_UNSIGNED);
+    for &(bytes, value) in unsigned {
+        let mut readable = bytes;
+        assert_eq!(readable.read_unsigned_with(&Config::new()).unwrap(),
+                   (value, bytes.len()),
+                   "{:02x?}",
+                   bytes);
+    }
+    let signed = DWARF_SIGNED.iter().chain(WASM_SIGNED).chain(BOUNDARY_SIGNED);
+    for &(bytes, value) in signed {
+        let mut readable = bytes;
+        assert_eq!(readable.read_signed_with(&Config::new()).unwrap(),
+                   (value, bytes.len()),
+                   "{:02x?}",
+                   bytes);
+    }
+
+    // The DWARF and boundary encodings are the shortest ones.
+    for &(bytes, value) in DWARF_UNSIGNED.iter().chain(BOUNDARY_UNSIGNED) {
+        assert_eq!(&*EncodedU64::new(value), bytes);
+    }
+    for &(bytes, value) in DWARF_SIGNED.iter().chain(BOUNDARY_SIGNED) {
+        assert_eq!(&*EncodedI64::new(value), bytes);
+    }
+
+    for &bytes in WASM_UNSIGNED_INVALID {
+        assert!(validate_unsigned(bytes).is_err(), "{:02x?}", bytes);
+    }
+    for &bytes in WASM_SIGNED_INVALID {
+        assert!(validate_signed(bytes).is_err(), "{:02x?}", bytes);
+    }
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+#[test]
+fn test_push_decoder_across_chunks() {
+    let mut writable = vec![];
+    let values = [0i64, -1, 624485, i64::MIN, i64::MAX, -123456];
+    for &val in &values {
+        writable.write_signed_with(val, &Config::new()).expect("Should write number");
+    }
+
+    // Every chunk size, including one byte at a time.
+    for size in 1..=writable.len() {
+        let mut decoder = Decoder::signed();
+        let mut decoded = vec![];
+        for chunk in writable.chunks(size) {
+            let mut rest = chunk;
+            while let DecodeProgress::Complete { value, consumed, .. } =
+                decoder.push(rest).expect("Should decode")
+            {
+                decoded.push(value);
+                rest = &rest[consumed..];
+            }
+        }
+        assert_eq!(decoded, values);
+        assert_eq!(decoder.pending(), 0);
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_push_decoder_errors() {
+    let mut decoder = Decoder::unsigned();
+    assert_eq!(decoder.push(&[0xff; 5]).unwrap(), DecodeProgress::Incomplete);
+    assert_eq!(decoder.pending(), 5);
+    match decoder.push(&[0xff; 5]) {
+        Err(read::Error::Overflow) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert_eq!(decoder.pending(), 0);
+    assert_eq!(decoder.push(&[0x80, 0x01]).unwrap(),
+               DecodeProgress::Complete { value: 128, len: 2, consumed: 2 });
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_encoder_windows() {
+    for &val in &[0u64, 127, 128, 624485, u64::MAX] {
+        let expected = EncodedU64::new(val);
+        for window in 1..=10 {
+            let mut encoder = Encoder::unsigned(val);
+            let mut out = vec![];
+            loop {
+                let mut buf = vec![0; window];
+                let progress = encoder.emit(&mut buf);
+                out.extend_from_slice(&buf[..progress.written]);
+                if progress.complete {
+                    break;
+                }
+                assert_eq!(progress.written, window);
+            }
+            assert_eq!(&out[..], &*expected);
+            assert_eq!(encoder.emit(&mut [0; 4]), EncodeProgress { written: 0, complete: true });
+        }
+    }
+
+    let mut encoder = Encoder::signed(-129);
+    assert_eq!((encoder.len(), encoder.remaining()), (2, 2));
+    assert_eq!(encoder.emit(&mut []), EncodeProgress { written: 0, complete: false });
+    let mut buf = [0; 3];
+    assert_eq!(encoder.emit(&mut buf), EncodeProgress { written: 2, complete: true });
+    assert_eq!(buf, [0xff, 0x7e, 0]);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_read_across_chunks() {
+    let mut writable = vec![];
+    let values = [624485u64, u64::MAX, 0, 1 << 63, 300];
+    for &val in &values {
+        writable.write_unsigned_with(val, &Config::new()).expect("Should write number");
+    }
+
+    // Split at every possible point, so each value straddles a boundary in
+    // some iteration.
+    for split in 0..writable.len() {
+        let (a, b) = writable.split_at(split);
+        let mut readable = a.chain(b);
+        for &val in &values {
+            assert_eq!(readable.read_unsigned_with(&Config::new())
+                       .expect("Should read number").0,
+                       val);
+        }
+        assert!(!readable.has_remaining());
+    }
+
+    // Overflow consumes the ten bytes read so far, whatever the chunking.
+    let overflowing = [0xffu8; 12];
+    let mut readable = overflowing[..4].chain(&overflowing[4..]);
+    match readable.read_unsigned_with(&Config::new()) {
+        Err(read::Error::Overflow) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert_eq!(readable.remaining(), 2);
+
+    let mut truncated = (&[0x80u8][..]).chain(&[0x80u8][..]);
+    assert!(truncated.read_signed().is_err());
+    assert!(!truncated.has_remaining());
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_frames() {
+    use frame::{FrameReader, FrameWriter};
+
+    let payloads: Vec<Vec<u8>> = vec![b"hello".to_vec(), vec![], vec![7; 300]];
+    let mut writer = FrameWriter::new(vec![], 300);
+    for payload in &payloads {
+        writer.write_frame(payload).expect("Should write frame");
+    }
+    assert_eq!(writer.write_frame(&[0; 301]),
+               Err(write::Error::LengthExceeded { len: 301, max: 300 }));
+    let wire = writer.into_inner();
+    assert_eq!(wire.len(), 1 + 5 + 1 + 2 + 300);
+
+    // Delivered one byte at a time, frames only appear once complete.
+    let mut reader = FrameReader::new(300);
+    let mut frames = vec![];
+    for byte in &wire {
+        let mut readable = std::slice::from_ref(byte);
+        if let Some(frame) = reader.read_frame(&mut readable).expect("Should read frame") {
+            frames.push(frame.to_vec());
+        }
+        assert!(readable.is_empty());
+    }
+    assert_eq!(frames, payloads);
+    assert_eq!(reader.buffered(), 0);
+
+    // Pushed all at once, frames come out one by one.
+    let mut reader = FrameReader::new(300);
+    reader.push(&wire);
+    for payload in &payloads {
+        assert_eq!(reader.next_frame().expect("Should read frame").as_deref(), Some(&payload[..]));
+    }
+    assert_eq!(reader.next_frame().expect("Should be empty"), None);
+
+    // An oversized frame is refused as soon as its length is known.
+    let mut reader = FrameReader::new(299);
+    match reader.read_frame(&mut &wire[7..10]) {
+        Err(read::Error::LengthExceeded { len: 300, max: 299 }) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_protobuf_delimited() {
+    use frame::{delimited_messages, read_delimited, write_delimited};
+
+    let mut writable = BytesMut::new();
+    write_delimited(&mut writable, b"first").expect("Should write message");
+    write_delimited(&mut writable, &[0x42; 200]).expect("Should write message");
+    let data = writable.freeze();
+
+    let mut readable = data.clone();
+    assert_eq!(read_delimited(&mut readable, 1024).unwrap().unwrap(), "first");
+    assert_eq!(read_delimited(&mut readable, 1024).unwrap().unwrap(), &[0x42; 200][..]);
+    assert_eq!(read_delimited(&mut readable, 1024).unwrap(), None);
+
+    let mut messages = delimited_messages(data.clone(), 1024);
+    assert_eq!(messages.next().unwrap().unwrap(), "first");
+    assert_eq!(messages.next().unwrap().unwrap().len(), 200);
+    assert!(messages.next().is_none());
+
+    // Limits and truncation are errors, after which iteration stops.
+    let mut messages = delimited_messages(data.clone(), 100);
+    assert!(messages.next().unwrap().is_ok());
+    match messages.next() {
+        Some(Err(read::Error::LengthExceeded { len: 200, max: 100 })) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert!(messages.next().is_none());
+    let mut truncated = data.slice(..data.len() - 1);
+    read_delimited(&mut truncated, 1024).expect("Should read first message");
+    assert!(read_delimited(&mut truncated, 1024).is_err());
+}
+
+#[cfg(all(feature = "tokio", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_async_read_is_cancel_safe() {
+    use async_io::AsyncReader;
+    use tokio::io::{AsyncWriteExt, BufReader};
+
+    tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+        let (mut tx, rx) = tokio::io::duplex(64);
+        let mut reader = AsyncReader::new(BufReader::new(rx));
+
+        // Half of a value arrives, and the read is abandoned while it waits
+        // for the rest.
+        tx.write_all(&[0xe5, 0x8e]).await.unwrap();
+        tokio::select! {
+            biased;
+            _ = reader.read_unsigned() => panic!("The value isn't complete"),
+            _ = tokio::task::yield_now() => {}
+        }
+        assert_eq!(reader.pending(), 2);
+
+        tx.write_all(&[0x26, 0x7e]).await.unwrap();
+        assert_eq!(reader.read_unsigned().await.unwrap(), (624485, 3));
+        assert_eq!(reader.read_signed().await.unwrap(), (-2, 1));
+
+        drop(tx);
+        assert!(reader.read_unsigned().await.is_err());
+    });
+}
+
+#[cfg(all(feature = "tokio", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_async_read_across_refills() {
+    use async_io::AsyncReader;
+    use tokio::io::BufReader;
+
+    let mut data = Vec::new();
+    let vals = [0, 127, 128, 624485, u64::MAX, 1 << 35, 3];
+    for &val in &vals {
+        data.write_unsigned_with(val, &Config::new()).unwrap();
+    }
+    data.extend_from_slice(&[0xff; 10]);
+
+    tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+        // Buffers of every size split the values at every point.
+        for capacity in 1..16 {
+            let mut reader = AsyncReader::new(BufReader::with_capacity(capacity, &data[..]));
+            for &val in &vals {
+                let (read, len) = reader.read_unsigned().await.expect("Should read number");
+                assert_eq!((read, len), (val, encoded_len_unsigned(val)), "capacity {}", capacity);
+                assert_eq!(reader.pending(), 0);
+            }
+            assert!(matches!(reader.read_unsigned().await, Err(read::Error::Overflow)));
+            assert!(reader.read_signed().await.unwrap_err().is_incomplete());
+        }
+    });
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_owned_buffers() {
+    let (written, buf) = owned::write_unsigned_iter(BytesMut::new(), vec![1, 300, u64::MAX]);
+    assert_eq!(written.unwrap(), 1 + 2 + 10);
+    let (written, buf) = owned::write_signed_iter(buf, vec![-1, i64::MIN]);
+    assert_eq!(written.unwrap(), 1 + 10);
+
+    let mut buf = buf.freeze();
+    let mut offset = 0;
+    for &expected in &[1, 300, u64::MAX] {
+        let (read, returned) = owned::read_unsigned(buf, offset);
+        let (val, len) = read.expect("Should read number");
+        assert_eq!(val, expected);
+        offset += len;
+        buf = returned;
+    }
+    let (read, buf) = owned::read_signed(buf, offset + 1);
+    assert_eq!(read.expect("Should read number").0, i64::MIN);
+
+    let len = buf.len();
+    let (read, _) = owned::read_signed(buf, len + 1);
+    assert!(read.is_err());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_try_write_checks_space() {
+    use write::{CountingWriter, Error};
+
+    let mut storage = [0u8; 4];
+    let mut writable = &mut storage[..];
+    assert_eq!(writable.try_write_unsigned(624485), Ok(3));
+    assert_eq!(writable.try_write_signed(-129),
+               Err(Error::InsufficientSpace { needed: 2, available: 1 }));
+    assert_eq!(writable.try_write_signed(-1), Ok(1));
+    assert_eq!(writable.try_write_unsigned(0),
+               Err(Error::InsufficientSpace { needed: 1, available: 0 }));
+    assert_eq!(storage, [0xe5, 0x8e, 0x26, 0x7f]);
+
+    let mut counting = CountingWriter::new(&mut storage[..2]);
+    assert!(counting.try_write_unsigned(u64::MAX).is_err());
+    assert_eq!(counting.try_write_unsigned(300), Ok(2));
+    assert_eq!((counting.total_values(), counting.total_bytes()), (1, 2));
+
+    let err: io::Error = Error::InsufficientSpace { needed: 2, available: 1 }.into();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_slice_encode_chains() {
+    use write::Error;
+
+    let mut buf = [0u8; 5];
+    let rest = slice::encode_signed(&mut buf, -129).expect("Should encode number");
+    let rest = slice::encode_unsigned(rest, 300).expect("Should encode number");
+    assert_eq!(slice::encode_unsigned(rest, u64::MAX),
+               Err(Error::InsufficientSpace { needed: 10, available: 1 }));
+    let rest = slice::encode_signed(rest, -1).expect("Should encode number");
+    assert!(rest.is_empty());
+    assert_eq!(buf, [0xff, 0x7e, 0xac, 0x02, 0x7f]);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_slice_decode_threads_rest() {
+    let input = [0xff, 0x7e, 0xac, 0x02, 0x80];
+    let (signed, rest) = slice::decode_signed(&input).expect("Should decode number");
+    let (unsigned, rest) = slice::decode_unsigned(rest).expect("Should decode number");
+    assert_eq!((signed, unsigned, rest), (-129, 300, &[0x80][..]));
+    assert!(slice::decode_unsigned(rest).is_err());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_slice_decode_at() {
+    let buf = [0x02, 0xff, 0x7e];
+    assert_eq!(slice::decode_signed_at(buf, 1).expect("Should decode number"), (-129, 2));
+    assert_eq!(slice::decode_unsigned_at(buf, 0).expect("Should decode number"), (2, 1));
+    assert!(slice::decode_unsigned_at(buf, 3).is_err());
+    assert!(slice::decode_unsigned_at(buf, 4).is_err());
+}
+
+#[cfg(all(feature = "bytes-1", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_bytes1_matches_bytes05() {
+    use bytes1::Bytes1;
+    use bytes_1::Buf as Buf1;
+    use tests_slice::{WIDEST_NEGATIVE, WIDEST_POSITIVE};
+
+    let mut old = BytesMut::new();
+    let mut new = Bytes1::new(bytes_1::BytesMut::new());
+    for val in &[0i64, -1, 63, -64, 624485, WIDEST_NEGATIVE, WIDEST_POSITIVE] {
+        old.write_signed(*val).expect("Should write number");
+        new.write_signed(*val).expect("Should write number");
+    }
+    new.write_bytes(b"leb").expect("Should write bytes");
+    assert_eq!(&old[..], &new.get_ref()[..old.len()]);
+
+    // A value split across chunks takes the slow path.
+    let (front, back) = old.split_at(old.len() - 3);
+    let rest = &new.get_ref()[old.len()..];
+    let mut reader = Bytes1::new(Buf1::chain(Buf1::chain(front, back), rest));
+    for val in &[0i64, -1, 63, -64, 624485, WIDEST_NEGATIVE] {
+        assert_eq!(reader.read_signed().expect("Should read number").0, *val);
+    }
+    let widest_len = encoded_len_signed(WIDEST_POSITIVE);
+    assert_eq!(reader.peek_signed().expect("Should peek number"), (WIDEST_POSITIVE, widest_len));
+    assert_eq!(reader.skip_signed().expect("Should skip number"), widest_len);
+    assert_eq!(reader.read_bytes(3).expect("Should read bytes"), b"leb");
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_read_length_prefixed_bytes() {
+    use read::Leb128ReadShared;
+
+    let storage = Bytes::from(&[0x03, b'a', b'b', b'c', 0x05, b'x'][..]);
+    let mut readable = storage.clone();
+    let payload = readable.read_length_prefixed_bytes(3).expect("Should read payload");
+    assert_eq!(payload, &b"abc"[..]);
+    // The payload shares the original storage.
+    assert_eq!(payload.as_ptr(), storage[1..].as_ptr());
+
+    // Neither a too long nor a truncated payload consumes anything.
+    match readable.read_length_prefixed_bytes(4) {
+        Err(read::Error::LengthExceeded { len: 5, max: 4 }) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert!(readable.read_length_prefixed_bytes(5).expect_err("Should fail").is_incomplete());
+    assert_eq!(readable, &[0x05, b'x'][..]);
+
+    let mut readable = BytesMut::from(&[0x01, b'z'][..]);
+    assert_eq!(readable.read_length_prefixed_bytes(1).expect("Should read payload"), &b"z"[..]);
+    assert!(readable.is_empty());
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_transcode() {
+    let dwarf = Config::with_flavor(Flavor::Dwarf);
+    let zigzag = Config::with_flavor(Flavor::ProtobufZigzag);
+    let vals = [0, -1, 63, -64, 64, i64::MIN, i64::MAX];
+    let mut sleb = Vec::new();
+    for &val in &vals {
+        sleb.write_signed_with(val, &dwarf).expect("Should write number");
+    }
+    let mut readable = &sleb[..];
+    let mut converted = BytesMut::new();
+    assert_eq!(transcode::transcode_signed(&dwarf, &zigzag, &mut readable, &mut converted)
+                   .expect("Should transcode"),
+               vals.len());
+    let mut back = Vec::new();
+    assert_eq!(transcode::transcode_signed(&zigzag, &dwarf, &mut converted.freeze(), &mut back)
+                   .expect("Should transcode"),
+               vals.len());
+    assert_eq!(back, sleb);
+
+    // Padding is dropped, and a truncated number is reported after the ones
+    // before it are written.
+    let mut readable = &[0x85, 0x80, 0x00, 0x80][..];
+    let mut canonical = Vec::new();
+    match transcode::transcode_unsigned(&dwarf, &dwarf, &mut readable, &mut canonical) {
+        Err(transcode::Error::Read(read::Error::Incomplete)) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert_eq!(canonical, [0x05]);
+
+    let mut readable = &[0x80, 0x80, 0x04][..];
+    let wasm = Config::with_flavor(Flavor::Wasm).bits(16);
+    match transcode::transcode_unsigned(&dwarf, &wasm, &mut readable, &mut canonical) {
+        Err(transcode::Error::Write(write::Error::OutOfRange)) => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_as_ref_inputs() {
+    let owned: Vec<u8> = vec![0xe5, 0x8e, 0x26, 0x7e];
+    let shared: crate::bytes::Bytes = owned.clone().into();
+    assert_eq!(validate_unsigned(&owned).expect("Should validate"), 3);
+    assert_eq!(count_values(shared.clone()).expect("Should count"), 2);
+    assert_eq!(slice::decode_signed_at(&shared, 3).expect("Should decode"), (-2, 1));
+    assert_eq!(cmp_encoded(owned, shared).expect("Should compare"), std::cmp::Ordering::Equal);
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_scratch_capacity() {
+    use frame::{read_delimited, write_delimited};
+
+    let mut writable = BytesMut::new();
+    for msg in &[&b"one"[..], b"two", &[0x42; 100], b"three"] {
+        write_delimited(&mut writable, msg).expect("Should write message");
+    }
+    let data = writable.freeze();
+    let read_all = || {
+        let mut readable = data.clone();
+        let mut payloads = Vec::new();
+        while let Some(payload) = read_delimited(&mut readable, 1024).unwrap() {
+            payloads.push(payload);
+        }
+        payloads
+    };
+
+    // Small payloads are carved from one block; those over the capacity
+    // get their own allocation.
+    with_scratch_capacity(64);
+    let payloads = read_all();
+    assert_eq!(payloads.len(), 4);
+    assert_eq!(payloads[1], "two");
+    assert_eq!(payloads[1].as_ptr(), payloads[0].as_ptr().wrapping_add(3));
+    assert_eq!(payloads[2], &[0x42; 100][..]);
+    assert_eq!(payloads[3], "three");
+    assert_eq!(hex::to_hex(624485), "e58e26");
+
+    // With no capacity, every payload is separate.
+    with_scratch_capacity(0);
+    let payloads = read_all();
+    assert_eq!(payloads[1], "two");
+    assert_ne!(payloads[1].as_ptr(), payloads[0].as_ptr().wrapping_add(3));
+    assert_eq!(hex::to_hex_signed(-123456), "c0bb78");
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_helpers_ignore_default_flavor() {
+    // Out of range under `default-flavor-wasm`, and ten bytes under
+    // `default-flavor-protobuf`.
+    let (written, buf) = owned::write_unsigned(Vec::new(), 1 << 40);
+    assert_eq!(written.expect("Should write"), 6);
+    let (written, buf) = owned::write_signed_iter(buf, vec![-1, -2]);
+    assert_eq!(written.expect("Should write"), 2);
+    let unsigned: Result<Vec<u64>, _> = iter_unsigned(&buf[..6]).collect();
+    assert_eq!(unsigned.expect("Should read"), [1 << 40]);
+    let signed: Result<Vec<i64>, _> = iter_signed(&buf[6..]).collect();
+    assert_eq!(signed.expect("Should read"), [-1, -2]);
+
+    // A length prefix padded past the five bytes WebAssembly allows.
+    let mut delimited = &[0x82, 0x80, 0x80, 0x80, 0x80, 0x00, b'h', b'i'][..];
+    let payload = frame::read_delimited(&mut delimited, 16).expect("Should read");
+    assert_eq!(payload.expect("Should have a message"), &b"hi"[..]);
+
+    let schema: schema::Schema = "s".parse().expect("Should parse schema");
+    let mut record = Vec::new();
+    assert_eq!(schema::encode_record(&schema, &[schema::Value::Signed(-1)], &mut record)
+                   .expect("Should write"),
+               1);
+    assert_eq!(schema::decode_record(&schema, &mut &record[..]).expect("Should read"),
+               [schema::Value::Signed(-1)]);
+}
@@ -0,0 +1,1011 @@
+//! Tests of the traits through plain slices and `Vec`s, which pass with or
+//! without the `bytes` feature, and under every default flavor. Numbers the
+//! flavor may reject go through `Config::new()`, or are the `WIDEST` it
+//! takes.
+
+use super::*;
+use read::Leb128Read;
+use write::Leb128Write;
+
+/// The widest numbers the trait methods take under the default flavor, which
+/// are 32 bits for `default-flavor-wasm`.
+#[cfg(not(feature = "signed-only"))]
+#[cfg_attr(all(feature = "unsigned-only", not(feature = "bytes")), allow(dead_code))]
+pub(crate) const WIDEST: u64 =
+    if cfg!(feature = "default-flavor-wasm") { u32::MAX as u64 } else { u64::MAX };
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) const WIDEST_NEGATIVE: i64 =
+    if cfg!(feature = "default-flavor-wasm") { i32::MIN as i64 } else { i64::MIN };
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) const WIDEST_POSITIVE: i64 =
+    if cfg!(feature = "default-flavor-wasm") { i32::MAX as i64 } else { i64::MAX };
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_slice_roundtrip() {
+    let mut storage = [0u8; 16];
+    let mut writable = &mut storage[..];
+    assert_eq!(writable.write_unsigned(624485).expect("Should write number"), 3);
+    assert_eq!(writable.write_signed(-2).expect("Should write number"), 1);
+    assert_eq!(writable.write_signed_with(i64::MIN, &Config::new()).expect("Should write number"),
+               10);
+    assert_eq!(writable.try_write_unsigned(WIDEST),
+               Err(write::Error::InsufficientSpace {
+                   needed: encoded_len_unsigned(WIDEST),
+                   available: 2,
+               }));
+    assert_eq!(writable.len(), 2);
+
+    let mut readable = &storage[..14];
+    assert_eq!(readable.peek_unsigned().expect("Should peek number"), (624485, 3));
+    assert_eq!(readable.read_unsigned().expect("Should read number"), (624485, 3));
+    assert_eq!(readable.skip_signed().expect("Should skip number"), 1);
+    assert_eq!(readable.read_signed_with(&Config::new()).expect("Should read number"),
+               (i64::MIN, 10));
+    assert!(readable.read_signed().is_err());
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_vec_and_codec() {
+    let mut buf = Vec::new();
+    300u16.encode(&mut buf).expect("Should encode value");
+    vec![1u32, 2, 3].encode(&mut buf).expect("Should encode value");
+    buf.write_str("hi").expect("Should write string");
+    assert_eq!(buf, [0xac, 0x02, 0x03, 0x01, 0x02, 0x03, 0x02, b'h', b'i']);
+
+    let mut readable = &buf[..];
+    assert_eq!(u16::decode(&mut readable).expect("Should decode value"), 300);
+    assert_eq!(Vec::<u32>::decode(&mut readable).expect("Should decode value"), [1, 2, 3]);
+    assert_eq!(readable.read_string(2).expect("Should read string"), "hi");
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_decode_from_iterators() {
+    let mut bytes = [0xff, 0x7e, 0xac, 0x02].iter().copied();
+    assert_eq!(read::decode_signed(&mut bytes).expect("Should decode number"), (-129, 2));
+    assert_eq!(read::decode_unsigned(&mut bytes).expect("Should decode number"), (300, 2));
+
+    // A source error is passed through untouched, before any decoding error.
+    let source_error = std::io::Error::from(std::io::ErrorKind::Interrupted);
+    let mut fallible = vec![Ok(0x80), Err(source_error), Ok(0x01)].into_iter();
+    let err = read::try_decode_unsigned::<_, std::io::Error>(&mut fallible)
+        .expect_err("Should fail");
+    assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+
+    let mut io_bytes = [0x80u8; 11].iter().map(|&b| Ok::<_, std::io::Error>(b));
+    let err = read::try_decode_unsigned::<_, std::io::Error>(&mut io_bytes)
+        .expect_err("Should overflow");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_max_encoded_len() {
+    assert_eq!((MAX_LEN_U8, MAX_LEN_U16, MAX_LEN_U32, MAX_LEN_U64), (2, 3, 5, 10));
+    assert_eq!((MAX_LEN_I8, MAX_LEN_I16, MAX_LEN_I32, MAX_LEN_I64), (2, 3, 5, 10));
+    assert_eq!(encoded_len_unsigned(u32::MAX as u64), max_encoded_len::<u32>());
+    assert_eq!(encoded_len_signed(i16::MIN as i64), max_encoded_len::<i16>());
+    assert_eq!(max_encoded_len::<usize>(), encoded_len_unsigned(usize::MAX as u64));
+}
+
+#[test]
+fn test_next_len() {
+    let readable = &[0xe5, 0x8e, 0x26, 0x7e][..];
+    assert_eq!(readable.next_len().expect("Should measure number"), 3);
+    assert_eq!(readable.len(), 4);
+    assert!((&[0x80, 0x80][..]).next_len().is_err());
+    assert!((&[0xff; 11][..]).next_len().is_err());
+    // Only the length limit applies, not the value's range.
+    assert_eq!((&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f][..]).next_len()
+                   .expect("Should measure number"),
+               10);
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_trait_names() {
+    use read::LEB128Read;
+    use write::LEB128Write;
+
+    let mut buf = Vec::new();
+    LEB128Write::write_unsigned(&mut buf, 300).expect("Should write number");
+    assert_eq!(LEB128Read::read_unsigned(&mut &buf[..]).expect("Should read number"), (300, 2));
+}
+
+#[test]
+fn test_raw_primitives() {
+    assert_eq!(raw::split_payload(0x80), (0, true));
+    assert_eq!(raw::split_payload(0x7f), (0x7f, false));
+    assert!(raw::is_terminal(0x00) && !raw::is_terminal(0xff));
+    assert_eq!(raw::shift_of(3), 21);
+
+    // -129 is 0xff 0x7e; decoding it with the primitives matches the reader.
+    let (mut val, mut shift) = (0i64, 0);
+    for (i, &byte) in [0xff, 0x7e].iter().enumerate() {
+        val |= (raw::low_bits_of_byte(byte) as i64) << raw::shift_of(i);
+        shift = raw::shift_of(i + 1);
+    }
+    assert_eq!(raw::sign_extend(val, shift, 0x7e), -129);
+    assert_eq!(raw::sign_extend(i64::MIN, 70, 0x7f), i64::MIN);
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_read_unsigned_with_bit_limit() {
+    let mut readable = &[0xff, 0xff, 0x3f, 0x80, 0x80, 0x40, 0x7f][..];
+    // 2^20 - 1 fits in 20 bits; 2^20 doesn't, and fails at its third byte.
+    assert_eq!(readable.read_unsigned_with_bit_limit(20).expect("Should read number"),
+               ((1 << 20) - 1, 3));
+    assert!(matches!(readable.read_unsigned_with_bit_limit(20), Err(read::Error::Overflow)));
+    assert_eq!(readable, [0x7f]);
+
+    // Padding beyond the limit is rejected even when it carries no bits.
+    assert!(matches!((&[0x80, 0x80, 0x00][..]).read_unsigned_with_bit_limit(14),
+                     Err(read::Error::Overflow)));
+    let max = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert_eq!((&max[..]).read_unsigned_with_bit_limit(64).expect("Should read number"),
+               (u64::MAX, 10));
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_seq() {
+    let mut readable = &[0x80, 0x02, 0x7f, 0xff, 0x01, 0x05][..];
+    let seq = readable.seq().u16().expect("Should read number");
+    assert_eq!((seq.values(), seq.len()), (&(256,), 2));
+    let ((a, b, c), len) = seq.i8().and_then(|s| s.u32()).expect("Should read numbers").finish();
+    assert_eq!((a, b, c, len), (256, -1, 255, 5));
+
+    // An out of range value fails the whole run.
+    let mut readable = &[0x80, 0x02][..];
+    assert!(matches!(readable.seq().u8(), Err(read::Error::Overflow)));
+}
+
+#[cfg(all(feature = "time", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_time() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let before = UNIX_EPOCH - Duration::new(2, 250_000_000);
+    let mut buf = Vec::new();
+    time::write_system_time(&mut buf, before).expect("Should write time");
+    // 1.75 seconds into the third second before the epoch.
+    assert_eq!(buf, [0x7d, 0x80, 0xaf, 0xd0, 0xe5, 0x02]);
+    time::write_system_time_micros(&mut buf, before).expect("Should write time");
+    time::write_duration_micros(&mut buf, Duration::new(1, 999)).expect("Should write duration");
+    assert_eq!(time::write_duration_micros(&mut buf, Duration::MAX),
+               Err(write::Error::OutOfRange));
+
+    let mut readable = &buf[..];
+    assert_eq!(time::read_system_time(&mut readable).expect("Should read time"), before);
+    assert_eq!(time::read_system_time_micros(&mut readable).expect("Should read time"), before);
+    assert_eq!(time::read_duration_micros(&mut readable).expect("Should read duration"),
+               Duration::from_secs(1));
+    assert!(readable.is_empty());
+
+    let mut readable = &[0x00, 0x80, 0x94, 0xeb, 0xdc, 0x03][..];
+    assert!(matches!(time::read_duration(&mut readable), Err(read::Error::Overflow)));
+
+    // Wider than any flavor's limit, yet always written.
+    let then = UNIX_EPOCH + Duration::from_micros(1_600_000_000_000_001);
+    let mut buf = Vec::new();
+    assert_eq!(time::write_system_time_micros(&mut buf, then).expect("Should write time"), 8);
+    assert_eq!(time::read_system_time_micros(&mut &buf[..]).expect("Should read time"), then);
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_option_unsigned() {
+    let mut buf = Vec::new();
+    assert_eq!(buf.write_option_unsigned(None).expect("Should write option"), 1);
+    assert_eq!(buf.write_option_unsigned(Some(300)).expect("Should write option"), 3);
+    assert_eq!(buf.write_option_unsigned(Some(0)).expect("Should write option"), 2);
+    assert_eq!(buf, [0x00, 0x01, 0xac, 0x02, 0x01, 0x00]);
+
+    let mut readable = &buf[..];
+    assert_eq!(readable.read_option_unsigned().expect("Should read option"), (None, 1));
+    assert_eq!(readable.read_option_unsigned().expect("Should read option"), (Some(300), 3));
+    assert_eq!(readable.read_option_unsigned().expect("Should read option"), (Some(0), 2));
+    assert!((&[0x02, 0x00][..]).read_option_unsigned().is_err());
+    // A padded zero is not a tag.
+    match (&[0x80, 0x00][..]).read_option_unsigned() {
+        Err(read::Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::InvalidData => {}
+        otherwise => panic!("Unexpected: {:?}", otherwise),
+    }
+    assert!((&[][..]).read_option_unsigned().expect_err("Should fail").is_incomplete());
+    // A bad tag is rejected on its own, leaving what follows unread.
+    let mut readable = &[0x81, 0x85, 0x05, 0x07][..];
+    assert!(readable.read_option_unsigned().is_err());
+    assert_eq!(readable, [0x81, 0x85, 0x05, 0x07]);
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_vec_of_numbers() {
+    let mut buf = Vec::new();
+    assert_eq!(buf.write_vec_unsigned(&[1, 300]).expect("Should write numbers"), 4);
+    assert_eq!(buf.write_vec_signed(&[-1, 64]).expect("Should write numbers"), 4);
+    assert_eq!(buf, [0x02, 0x01, 0xac, 0x02, 0x02, 0x7f, 0xc0, 0x00]);
+
+    let mut readable = &buf[..];
+    assert_eq!(readable.read_vec_unsigned(2).expect("Should read numbers"), [1, 300]);
+    match readable.read_vec_signed(1) {
+        Err(read::Error::LengthExceeded { len: 2, max: 1 }) => {}
+        other => panic!("Expected LengthExceeded, got {:?}", other),
+    }
+    // A huge count fails on running out of data, not on allocating.
+    let huge = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0x01];
+    assert!((&huge[..]).read_vec_unsigned(usize::MAX).is_err());
+}
+
+#[cfg(all(feature = "metrics", not(feature = "signed-only")))]
+#[test]
+fn test_metrics() {
+    // Other tests bump the counters concurrently, so only lower bounds hold.
+    let before = metrics::stats();
+    assert!((&[0xff; 10][..]).read_unsigned().is_err());
+    assert!(validate_unsigned_canonical([0x80, 0x00]).is_err());
+    assert_eq!((&[0xac, 0x02][..]).read_unsigned().expect("Should read number"), (300, 2));
+    let after = metrics::stats();
+    assert!(after.overflows > before.overflows);
+    assert!(after.non_canonical > before.non_canonical);
+    assert!(after.values_decoded > before.values_decoded);
+    assert!(after.bytes_decoded >= before.bytes_decoded + 2);
+}
+
+#[test]
+fn test_analyze() {
+    let report = analyze(&[0, 127, 128, u64::MAX]);
+    assert_eq!(report.histogram, [2, 1, 0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!((report.count, report.total_bytes), (4, 14));
+    assert_eq!(report.average(), 3.5);
+    assert_eq!(report.bitpacked_bytes, 32);
+
+    let empty = analyze(&[]);
+    assert_eq!((empty.total_bytes, empty.bitpacked_bytes, empty.average()), (0, 0, 0.0));
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_config() {
+    // Generic rules match the plain methods.
+    let generic = Config::new();
+    assert_eq!(generic.decode_unsigned(&[0x80, 0x00]).expect("Should decode"), (0, 2));
+    assert!(matches!(generic.decode_unsigned(&[0xff; 10]), Err(read::Error::Overflow)));
+    assert_eq!(generic.decode_signed(&[0xff, 0x7e]).expect("Should decode"), (-129, 2));
+
+    // Wasm allows five byte padded u32s and rejects a sixth byte or a 33rd bit.
+    let wasm = Config::with_flavor(Flavor::Wasm);
+    assert_eq!(wasm.decode_unsigned(&[0xff, 0xff, 0xff, 0xff, 0x0f]).expect("Should decode"),
+               (u32::MAX as u64, 5));
+    assert!(matches!(wasm.decode_unsigned(&[0xff, 0xff, 0xff, 0xff, 0x1f]),
+                     Err(read::Error::Overflow)));
+    assert!(matches!(wasm.decode_unsigned(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00]),
+                     Err(read::Error::Overflow)));
+    assert_eq!(wasm.decode_signed(&[0x80, 0x80, 0x80, 0x80, 0x78]).expect("Should decode"),
+               (i32::MIN as i64, 5));
+    assert!(matches!(wasm.decode_signed(&[0x80, 0x80, 0x80, 0x80, 0x70]),
+                     Err(read::Error::Overflow)));
+
+    // Protobuf drops the bits beyond 64 instead.
+    let protobuf = Config::with_flavor(Flavor::Protobuf);
+    let wide = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+    assert_eq!(protobuf.decode_unsigned(&wide).expect("Should decode"), (u64::MAX, 10));
+
+    let strict = Config::new().canonical(true).max_len(3);
+    assert!(matches!(strict.decode_signed(&[0xff, 0x7f]), Err(read::Error::NonCanonical)));
+    assert!(strict.decode_unsigned(&[0x80, 0x80]).is_err());
+
+    let mut readable = &[0x80, 0x80, 0x80, 0x01, 0x05][..];
+    assert!(matches!(readable.read_unsigned_with(&strict), Err(read::Error::Overflow)));
+    assert_eq!(readable.read_signed_with(&strict).expect("Should read number"), (5, 1));
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_write_with_config() {
+    let wasm = Config::with_flavor(Flavor::Wasm);
+    let mut buf = Vec::new();
+    assert_eq!(buf.write_unsigned_with(u32::MAX as u64, &wasm).expect("Should write"), 5);
+    assert_eq!(buf.write_unsigned_with(1 << 32, &wasm), Err(write::Error::OutOfRange));
+    assert_eq!(buf.write_signed_with(i32::MIN as i64 - 1, &wasm), Err(write::Error::OutOfRange));
+
+    let truncating = wasm.overflow(OverflowPolicy::Truncate);
+    assert_eq!(buf.write_unsigned_with((1 << 32) + 7, &truncating).expect("Should write"), 1);
+    assert_eq!(buf.write_signed_with(u32::MAX as i64, &truncating).expect("Should write"), 1);
+    assert_eq!(buf.write_unsigned_with(300, &Config::new().max_len(1)),
+               Err(write::Error::LengthExceeded { len: 2, max: 1 }));
+    assert_eq!(buf, [0xff, 0xff, 0xff, 0xff, 0x0f, 0x07, 0x7f]);
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+#[test]
+fn test_zigzag_config() {
+    for &val in &[0, -1, 1, i64::MIN, i64::MAX] {
+        assert_eq!(raw::zigzag_decode(raw::zigzag_encode(val)), val);
+    }
+
+    let zigzag = Config::with_flavor(Flavor::ProtobufZigzag);
+    assert_eq!(zigzag.signed(), SignedEncoding::ZigZag);
+    assert_eq!(zigzag.decode_signed(&[0x03]).expect("Should decode"), (-2, 1));
+    assert_eq!(zigzag.decode_signed(&[0xfe, 0x01]).expect("Should decode"), (127, 2));
+    let sint32 = zigzag.bits(32).overflow(OverflowPolicy::Error);
+    assert!(matches!(sint32.decode_signed(&[0x80, 0x80, 0x80, 0x80, 0x10]),
+                     Err(read::Error::Overflow)));
+
+    let mut storage = [0u8; 3];
+    let mut writable = &mut storage[..];
+    assert_eq!(writable.write_signed_with(-2, &zigzag).expect("Should write"), 1);
+    assert_eq!(writable.write_signed_with(64, &zigzag).expect("Should write"), 2);
+    assert_eq!(storage, [0x03, 0x80, 0x01]);
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_flavor_codec() {
+    let flavors = [Flavor::Generic, Flavor::Dwarf, Flavor::Wasm, Flavor::Protobuf,
+                   Flavor::ProtobufZigzag, Flavor::Multiformats, Flavor::Orc];
+    for &flavor in &flavors {
+        let mut buf = Vec::new();
+        let len = flavor.write_unsigned(&mut buf, 300).expect("Should write number");
+        flavor.write_signed(&mut buf, -300).expect("Should write number");
+        let mut readable = &buf[..];
+        assert_eq!(flavor.read_unsigned(&mut readable).expect("Should read number"), (300, len));
+        assert_eq!(flavor.read_signed(&mut readable).expect("Should read number").0, -300);
+        assert!(readable.is_empty());
+    }
+
+    // Multiformats caps values at 63 bits and encodings at nine bytes.
+    let mut buf = Vec::new();
+    assert_eq!(Flavor::Multiformats.write_unsigned(&mut buf, 1 << 63),
+               Err(write::Error::OutOfRange));
+    let nine = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+    assert_eq!(Flavor::Multiformats.read_unsigned(&mut &nine[..]).expect("Should read number"),
+               (u64::MAX >> 1, 9));
+    assert_eq!(Flavor::ProtobufZigzag.write_signed(&mut buf, -1).expect("Should write"), 1);
+    assert_eq!(buf, [0x01]);
+
+    // Protobuf's `int64` and `int32` write negative numbers in ten bytes.
+    let minus_one = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    let mut buf = Vec::new();
+    assert_eq!(Flavor::Protobuf.write_signed(&mut buf, -1).expect("Should write"), 10);
+    assert_eq!(buf, minus_one);
+    let int32 = Config::with_flavor(Flavor::Protobuf).bits(32);
+    assert_eq!(buf.write_signed_with(-1, &int32).expect("Should write"), 10);
+    assert_eq!(buf[10..], minus_one);
+    assert_eq!(Flavor::Protobuf.read_signed(&mut &buf[..]).expect("Should read"), (-1, 10));
+    assert_eq!(int32.decode_signed(&minus_one).expect("Should decode"), (-1, 10));
+    let wide = [0x80, 0x80, 0x80, 0x80, 0x10];
+    assert_eq!(int32.decode_signed(&wide).expect("Should decode"), (0, 5));
+    assert!(matches!(int32.overflow(OverflowPolicy::Error).decode_signed(&wide),
+                     Err(read::Error::Overflow)));
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_snapshot() {
+    use snapshot::{SnapshotDecoder, SnapshotEncoder};
+
+    let mut encoder = SnapshotEncoder::new([0i64; 3]);
+    let mut decoder = SnapshotDecoder::new([0i64; 3]);
+    let mut storage = [0u8; 32];
+    let mut writable = &mut storage[..];
+    assert_eq!(encoder.encode(&mut writable, &[0, i64::MIN, 1]).expect("Should write"), 12);
+    assert_eq!(encoder.encode(&mut writable, &[0, i64::MIN, 1]).expect("Should write"), 1);
+    assert_eq!(encoder.encode(&mut writable, &[-1, i64::MAX, 1]).expect("Should write"), 3);
+    assert_eq!(encoder.baseline(), [-1, i64::MAX, 1]);
+
+    let mut readable = &storage[..16];
+    assert_eq!(decoder.decode(&mut readable).expect("Should read"), 0b110);
+    assert_eq!(decoder.decode(&mut readable).expect("Should read"), 0);
+    assert_eq!(decoder.decode(&mut readable).expect("Should read"), 0b011);
+    assert_eq!(decoder.into_inner(), [-1, i64::MAX, 1]);
+
+    // A mask naming a fourth field, or a truncated snapshot, changes nothing.
+    let mut decoder = SnapshotDecoder::new([5i64; 3]);
+    assert!(matches!(decoder.decode(&mut &[0x08, 0x02][..]), Err(read::Error::Overflow)));
+    assert!(decoder.decode(&mut &[0x03, 0x02][..]).expect_err("Should fail").is_incomplete());
+    assert_eq!(decoder.baseline(), [5, 5, 5]);
+}
+
+#[cfg(all(feature = "default-flavor-wasm",
+          not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_default_flavor_wasm() {
+    assert_eq!(Config::DEFAULT, Config::with_flavor(Flavor::Wasm));
+    let mut readable = &[0xff, 0xff, 0xff, 0xff, 0x1f][..];
+    assert!(matches!(readable.read_unsigned(), Err(read::Error::Overflow)));
+    let mut buf = Vec::new();
+    assert_eq!(buf.write_unsigned(1 << 32), Err(write::Error::OutOfRange));
+
+    // Peeking and skipping accept exactly what reading does.
+    let wide = [0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+    assert!(matches!((&wide[..]).peek_unsigned(), Err(read::Error::Overflow)));
+    assert!(matches!((&wide[..]).skip_unsigned(), Err(read::Error::Overflow)));
+    assert!(matches!((&wide[..]).peek_signed(), Err(read::Error::Overflow)));
+    assert!(matches!((&wide[..]).skip_signed(), Err(read::Error::Overflow)));
+    assert_eq!((&[0x85, 0x01][..]).peek_unsigned().expect("Should peek"), (133, 2));
+    assert_eq!((&[0x85, 0x01][..]).skip_unsigned().expect("Should skip"), 2);
+
+    // Explicit rules are the only ones applied.
+    assert_eq!(buf.write_unsigned_with(1 << 35, &Config::new()).expect("Should write"), 6);
+    assert_eq!(buf, wide);
+    assert_eq!(Flavor::Protobuf.write_signed(&mut buf, -1).expect("Should write"), 10);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_write_32_bit() {
+    let mut storage = [0u8; MAX_LEN_U32 + MAX_LEN_I32];
+    let mut writable = &mut storage[..];
+    assert_eq!(writable.write_unsigned_u32(u32::MAX).expect("Should write number"), MAX_LEN_U32);
+    assert_eq!(writable.try_write_signed_i32(i32::MIN).expect("Should write number"), MAX_LEN_I32);
+    assert!(writable.try_write_unsigned_u32(0).is_err());
+    assert_eq!(storage, [0xff, 0xff, 0xff, 0xff, 0x0f, 0x80, 0x80, 0x80, 0x80, 0x78]);
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_enum_discriminants() {
+    use std::convert::TryFrom;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[repr(u64)]
+    enum Tag {
+        Small = 1,
+        Large = 200,
+    }
+
+    impl From<Tag> for u64 {
+        fn from(tag: Tag) -> u64 {
+            tag as u64
+        }
+    }
+
+    impl TryFrom<u64> for Tag {
+        type Error = u64;
+        fn try_from(val: u64) -> Result<Self, u64> {
+            match val {
+                1 => Ok(Tag::Small),
+                200 => Ok(Tag::Large),
+                other => Err(other),
+            }
+        }
+    }
+
+    let mut buf = Vec::new();
+    assert_eq!(buf.write_enum(Tag::Large).expect("Should write tag"), 2);
+    buf.write_enum(Tag::Small).expect("Should write tag");
+    buf.write_enum(7u8).expect("Should write tag");
+
+    let mut readable = &buf[..];
+    assert_eq!(readable.read_enum::<Tag>().expect("Should read tag"), Tag::Large);
+    assert_eq!(readable.read_enum::<Tag>().expect("Should read tag"), Tag::Small);
+    assert!(matches!(readable.read_enum::<Tag>(), Err(read::Error::UnknownDiscriminant(7))));
+}
+
+#[cfg(all(feature = "arrow", not(feature = "signed-only")))]
+#[test]
+fn test_arrow_roundtrip() {
+    use arrow_array::{Array, UInt64Array};
+    use arrow::{decode_to_array, encode_array};
+
+    let dense = UInt64Array::from(vec![0, 127, 128, u64::MAX]);
+    let buffer = encode_array(&dense);
+    assert_eq!(buffer.len(), 2 + 1 + 1 + 2 + 10);
+    assert_eq!(decode_to_array(&buffer).expect("Should decode"), dense);
+
+    let sliced = UInt64Array::from(vec![Some(5), None, None, Some(6)]).slice(1, 3);
+    let decoded = decode_to_array(&encode_array(&sliced)).expect("Should decode");
+    assert_eq!(decoded, sliced);
+    assert_eq!(decoded.null_count(), 2);
+
+    assert!(decode_to_array(&buffer[..buffer.len() - 1]).expect_err("Should fail").is_incomplete());
+    assert!(decode_to_array(&[0x02, 0x01, 0b11, 0x01, 0x02]).is_err());
+    assert!(decode_to_array(&[0x01, 0x00, 0x01, 0x00]).is_err());
+}
+
+#[cfg(not(feature = "signed-only"))]
+#[test]
+fn test_parquet_run_headers() {
+    use parquet::{decode_bit_width, decode_run_header, encode_run_header, RunHeader};
+
+    let mut storage = [0u8; 10];
+    let headers = [RunHeader::Rle { len: 0 },
+                   RunHeader::BitPacked { groups: 1 },
+                   RunHeader::Rle { len: i32::MAX as u32 }];
+    let mut rest = &mut storage[..];
+    for &header in &headers {
+        rest = encode_run_header(rest, header).expect("Should write header");
+    }
+    assert_eq!(rest.len(), 3);
+    let mut readable = &storage[..7];
+    for &header in &headers {
+        let (decoded, rest) = decode_run_header(readable).expect("Should read header");
+        assert_eq!(decoded, header);
+        readable = rest;
+    }
+
+    assert_eq!(encode_run_header(&mut storage, RunHeader::Rle { len: 1 << 31 }),
+               Err(write::Error::OutOfRange));
+    assert!(matches!(decode_run_header(&[0xff, 0xff, 0xff, 0xff, 0x1f]),
+                     Err(read::Error::Overflow)));
+    assert!(matches!(decode_bit_width(&[33]), Err(read::Error::Overflow)));
+    assert!(decode_bit_width(&[]).expect_err("Should fail").is_incomplete());
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+#[test]
+fn test_orc_decimal() {
+    use orc::{decode_decimal, encode_decimal, MAX_LEN_DECIMAL};
+
+    let mut storage = [0u8; MAX_LEN_DECIMAL];
+    for &val in &[0, -1, 1, 63, -64, i64::MIN as i128, i128::MAX, i128::MIN] {
+        let len = MAX_LEN_DECIMAL - encode_decimal(&mut storage, val).expect("Should write").len();
+        assert_eq!(decode_decimal(&storage[..len]).expect("Should read"), (val, &[][..]));
+    }
+    assert_eq!(&storage[..3], [0xff, 0xff, 0xff]);
+    assert_eq!(storage[MAX_LEN_DECIMAL - 1], 0x03);
+
+    // Bits beyond 128, or a 20th byte, overflow.
+    storage[MAX_LEN_DECIMAL - 1] = 0x04;
+    assert!(matches!(decode_decimal(&storage), Err(read::Error::Overflow)));
+    assert!(matches!(decode_decimal(&[0x80; 20]), Err(read::Error::Overflow)));
+    assert!(decode_decimal(&[0x80; 18]).expect_err("Should fail").is_incomplete());
+    assert_eq!(encode_decimal(&mut [0u8; 1], 64),
+               Err(write::Error::InsufficientSpace { needed: 2, available: 1 }));
+
+    // ORC zigzags its integers where protobuf's int64 would take ten bytes.
+    let mut writable = &mut storage[..];
+    assert_eq!(Flavor::Orc.write_signed(&mut writable, -3).expect("Should write"), 1);
+    assert_eq!(storage[0], 0x05);
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_budgeted_writer() {
+    use write::{BudgetedWriter, Error};
+
+    let mut storage = [0u8; 16];
+    let mut writer = BudgetedWriter::new(&mut storage[..], 6);
+    assert_eq!(writer.write_signed(-129).expect("Should write number"), 2);
+    assert_eq!(writer.write_bytes(b"abcd"), Err(Error::BudgetExceeded { needed: 5, remaining: 4 }));
+    assert_eq!(writer.write_unsigned_with(1 << 32, &Config::with_flavor(Flavor::Wasm)),
+               Err(Error::OutOfRange));
+    assert_eq!(writer.write_bytes(b"abc").expect("Should write bytes"), 4);
+    assert_eq!(writer.try_write_unsigned(0), Err(Error::BudgetExceeded { needed: 1, remaining: 0 }));
+    assert_eq!(writer.used(), 6);
+    assert_eq!(storage[..7], [0xff, 0x7e, 0x03, b'a', b'b', b'c', 0x00]);
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_buffered_writer() {
+    use std::io::Write;
+    use write::{Error, Leb128Writer};
+
+    let mut writer = Leb128Writer::with_capacity(12, Vec::new());
+    writer.write_all(b"LEB").expect("Should write header");
+    writer.write_unsigned_with(u64::MAX, &Config::new()).expect("Should write number");
+    assert_eq!(writer.get_ref().len(), 3);
+    assert_eq!(writer.write_signed(-1).expect("Should write number"), 1);
+    assert_eq!(writer.buffer(), [0x7f]);
+    // Too long to buffer, so it's written straight through.
+    assert_eq!(writer.write_bytes(&[0x42; 20]).expect("Should write bytes"), 21);
+    assert!(writer.buffer().is_empty());
+    assert_eq!(writer.total_bytes(), 35);
+    let out = writer.into_inner().expect("Should flush");
+    assert_eq!(out.len(), 35);
+    assert_eq!(slice::decode_unsigned(&out[3..]).expect("Should read number").0, u64::MAX);
+
+    let mut storage = [0u8; 4];
+    let mut writer = Leb128Writer::with_capacity(4, &mut storage[..]);
+    writer.write_unsigned(624485).expect("Should write number");
+    writer.write_unsigned(624485).expect("Should write number");
+    assert_eq!(writer.write_unsigned(624485),
+               Err(Error::Io(std::io::ErrorKind::WriteZero)));
+    assert_eq!(writer.flush().expect_err("Should fail").kind(), std::io::ErrorKind::WriteZero);
+    assert_eq!(writer.take_error().expect("Should keep error").kind(),
+               std::io::ErrorKind::WriteZero);
+    // Whatever did fit is gone from the buffer, and the rest comes back
+    // with the writer.
+    let err = writer.into_inner().expect_err("Should fail");
+    assert_eq!(err.error().kind(), std::io::ErrorKind::WriteZero);
+    assert_eq!(err.into_inner().buffer(), [0x8e, 0x26]);
+
+    // A failed long byte string leaves no length prefix behind.
+    let mut storage = [0u8; 1];
+    let mut writer = Leb128Writer::with_capacity(8, &mut storage[..]);
+    writer.write_unsigned(1).expect("Should write number");
+    assert_eq!(writer.write_bytes(&[0x42; 20]),
+               Err(Error::Io(std::io::ErrorKind::WriteZero)));
+    assert!(writer.buffer().is_empty());
+    drop(writer);
+    assert_eq!(storage, [0x01]);
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+#[test]
+fn test_decimal() {
+    let mut storage = [0u8; 32];
+    let mut writable = &mut storage[..];
+    let decimals = [(1234, -2), (0, 0), (WIDEST_NEGATIVE, i8::MIN), (WIDEST_POSITIVE, i8::MAX)];
+    let mut total = 0;
+    for &(mantissa, exponent) in &decimals {
+        total += writable.write_decimal(mantissa, exponent).expect("Should write decimal");
+    }
+    let mut readable = &storage[..total];
+    for &decimal in &decimals {
+        assert_eq!(readable.read_decimal().expect("Should read decimal").0, decimal);
+    }
+    assert!(readable.is_empty());
+
+    // An exponent of 128 is out of range, and a missing one is incomplete.
+    assert!(matches!((&[0x01, 0x80, 0x01][..]).read_decimal(), Err(read::Error::Overflow)));
+    assert!((&[0x01][..]).read_decimal().expect_err("Should fail").is_incomplete());
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_hex() {
+    assert_eq!(hex::to_hex(0), "00");
+    assert_eq!(hex::to_hex(u64::MAX), "ffffffffffffffffff01");
+    assert_eq!(hex::to_hex_signed(-1), "7f");
+    assert_eq!(hex::from_hex("0xe58e26").expect("Should parse"), (624485, 3));
+    assert_eq!(hex::from_hex("80,80,00").expect("Should parse"), (0, 3));
+    assert_eq!(hex::from_hex_signed(" 80 7f ").expect("Should parse"), (-128, 2));
+
+    for bad in &["e5 8e 2", "zz", "01 02", "+f", "-1"] {
+        match hex::from_hex(bad) {
+            Err(read::Error::IoError(ref e)) if e.kind() == std::io::ErrorKind::InvalidData => {}
+            otherwise => panic!("Unexpected for {:?}: {:?}", bad, otherwise),
+        }
+    }
+    assert!(hex::from_hex("").expect_err("Should fail").is_incomplete());
+    assert!(hex::from_hex("e5 8e").expect_err("Should fail").is_incomplete());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_base64_vlq() {
+    let mut s = String::new();
+    for &val in &[0, 1, -1, 15, 16, -16, 1 << 40, i64::MAX, i64::MIN] {
+        s.clear();
+        let len = vlq::encode(val, &mut s);
+        assert_eq!(vlq::decode(&s).expect("Should decode"), (val, len));
+    }
+    assert_eq!(s, "hgggggggggggQ");
+    assert_eq!(vlq::decode("B").expect("Should decode"), (0, 1));
+    assert!(matches!(vlq::decode("ggggggggggggggC"), Err(read::Error::Overflow)));
+    assert!(vlq::decode("g").expect_err("Should fail").is_incomplete());
+
+    s.clear();
+    vlq::encode_segment(&[0, 0, 0, 0], &mut s);
+    s.push(',');
+    vlq::encode_segment(&[8, 1, -3, 2, 0], &mut s);
+    s.push_str(";;;");
+    vlq::encode_segment(&[500], &mut s);
+    assert_eq!(s, "AAAA,QCHEA;;;of");
+    let segments: Vec<_> = vlq::segments(&s).collect::<Result<_, _>>().expect("Should decode");
+    assert_eq!(segments.len(), 3);
+    assert_eq!((segments[1].line(), segments[1].fields()), (0, &[8, 1, -3, 2, 0][..]));
+    assert_eq!((segments[2].line(), segments[2].fields()), (3, &[500][..]));
+
+    let results: Vec<_> = vlq::segments("AA,A*AA;AAAAAA,C").collect();
+    assert!(results[..3].iter().all(Result::is_err));
+    assert_eq!(results[3].as_ref().expect("Should decode").fields(), [1]);
+}
+
+#[cfg(all(feature = "alloc", not(any(feature = "signed-only", feature = "unsigned-only"))))]
+#[test]
+fn test_schema_records() {
+    use schema::{decode_record, encode_record, FieldKind, Schema, Value};
+
+    let schema: Schema = " uleb  sleb string ".parse().expect("Should parse schema");
+    assert_eq!(schema.fields(), [FieldKind::Unsigned, FieldKind::Signed, FieldKind::String]);
+    assert_eq!(schema.to_string(), "u s str");
+    assert_eq!("u f64".parse::<Schema>().expect_err("Should fail").name(), "f64");
+
+    let record = vec![Value::Unsigned(0), Value::Signed(i64::MIN), Value::String("ok".into())];
+    let mut buf = Vec::new();
+    assert_eq!(encode_record(&schema, &record, &mut buf).expect("Should write"), 1 + 10 + 3);
+    assert_eq!(decode_record(&schema, &mut &buf[..]).expect("Should read"), record);
+
+    // Values that don't match the schema write nothing.
+    assert_eq!(encode_record(&schema, &record[..2], &mut buf), Err(write::Error::OutOfRange));
+    assert_eq!(encode_record(&schema, &[Value::Signed(0), Value::Signed(0), Value::Signed(0)],
+                             &mut buf),
+               Err(write::Error::OutOfRange));
+    assert_eq!(buf.len(), 14);
+
+    let strict = schema.max_len(1);
+    assert!(matches!(decode_record(&strict, &mut &buf[..]),
+                     Err(read::Error::LengthExceeded { len: 2, max: 1 })));
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_decode_unchecked() {
+    use slice::{decode_signed_unchecked, decode_unsigned_unchecked, encode_signed,
+                encode_unsigned};
+
+    let mut storage = [0u8; 11];
+    for &val in &[0, 1, 127, 128, 624485, u64::MAX >> 1, u64::MAX] {
+        let len = 11 - encode_unsigned(&mut storage, val).expect("Should write").len();
+        assert_eq!(unsafe { decode_unsigned_unchecked(&storage) }, (val, len));
+    }
+    for &val in &[0, -1, 63, -64, 64, -65, i64::MIN, i64::MAX] {
+        let len = 11 - encode_signed(&mut storage, val).expect("Should write").len();
+        assert_eq!(unsafe { decode_signed_unchecked(&storage) }, (val, len));
+    }
+    // Padded encodings are valid too.
+    assert_eq!(unsafe { decode_unsigned_unchecked(&[0x85, 0x80, 0x00]) }, (5, 3));
+    assert_eq!(unsafe { decode_signed_unchecked(&[0xff, 0xff, 0x7f]) }, (-1, 3));
+}
+
+#[test]
+fn test_count_values_across_blocks() {
+    // Runs of continuation bytes of every length, starting at every offset
+    // into a 16 byte block, so vectorized scans see them split across blocks.
+    for offset in 0..40 {
+        for run in 0..=read::MAX_LEN {
+            let mut bytes = vec![0x01; offset];
+            bytes.resize(offset + run, 0x80);
+            bytes.extend_from_slice(&[0x01; 20]);
+            let expected = if run == read::MAX_LEN {
+                Err(read::Error::Overflow)
+            } else {
+                Ok(offset + 20)
+            };
+            assert_eq!(count_values(&bytes).map_err(|e| e.to_string()),
+                       expected.map_err(|e| e.to_string()), "offset {}, run {}", offset, run);
+
+            // Cut off inside the run, it's incomplete instead.
+            if run > 0 && run < read::MAX_LEN {
+                assert!(matches!(count_values(&bytes[..offset + run]),
+                                 Err(read::Error::Incomplete)));
+            }
+        }
+    }
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_decode_bounded() {
+    use slice::{decode_signed_bounded, decode_unsigned_bounded};
+
+    let mut buf = Vec::new();
+    for &val in &[0, 1, 127, 128, 624485, u32::MAX as u64, u64::MAX] {
+        buf.clear();
+        buf.write_unsigned_with(val, &Config::new()).expect("Should write");
+        assert_eq!(decode_unsigned_bounded::<10>(&buf).expect("Should read"), (val, &[][..]));
+        let bounded = decode_unsigned_bounded::<5>(&buf);
+        if val <= u32::MAX as u64 {
+            assert_eq!(bounded.expect("Should read").0, val);
+        } else {
+            assert!(matches!(bounded, Err(read::Error::Overflow)));
+        }
+    }
+    for &val in &[0, -1, 63, -64, 64, -65, i32::MIN as i64, i64::MIN, i64::MAX] {
+        buf.clear();
+        buf.write_signed_with(val, &Config::new()).expect("Should write");
+        assert_eq!(decode_signed_bounded::<10>(&buf).expect("Should read"), (val, &[][..]));
+    }
+
+    assert!(matches!(decode_unsigned_bounded::<3>(&[0x80, 0x80]), Err(read::Error::Incomplete)));
+    assert!(matches!(decode_unsigned_bounded::<10>(&[0xff; 10]), Err(read::Error::Overflow)));
+    assert!(matches!(decode_signed_bounded::<10>(&[0xff; 11]), Err(read::Error::Overflow)));
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_log_segment_recovery() {
+    use log::{SegmentReader, SegmentWriter, HEADER};
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!("nt-leb128-log-{}", std::process::id()));
+    let open = || {
+        OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)
+                          .expect("Should open file")
+    };
+
+    let mut writer = SegmentWriter::open(open()).expect("Should create segment");
+    assert_eq!(writer.append(b"first").expect("Should append"), HEADER.len() as u64);
+    writer.append(&[0x42; 200]).expect("Should append");
+    // Half of a third record makes it to disk before a crash.
+    writer.get_mut().write_all(&[0xc8, 0x01, 0x42]).expect("Should write");
+    let size = writer.size();
+    drop(writer);
+
+    let mut writer = SegmentWriter::open(open()).expect("Should recover segment");
+    assert_eq!(writer.size(), size);
+    writer.append(b"last").expect("Should append");
+    drop(writer);
+
+    let segment = fs::read(&path).expect("Should read file");
+    fs::remove_file(&path).expect("Should remove file");
+    let records: Vec<_> = SegmentReader::new(&segment).expect("Should have a header")
+                                                      .collect::<Result<_, _>>()
+                                                      .expect("Should read records");
+    assert_eq!(records, [&b"first"[..], &[0x42; 200][..], b"last"]);
+
+    assert!(matches!(SegmentReader::new(b"LOG\x01"), Err(read::Error::IoError(_))));
+    assert_eq!(log::recover(&b"LE"[..]).expect("Should scan"), 0);
+    assert!(log::recover(&b"\x00\x01"[..]).is_err());
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_stream_index() {
+    use index::StreamIndex;
+
+    let streams: [Vec<u64>; 4] = [vec![],
+                                  vec![7],
+                                  (0..1000).map(|i| i * 3 / 2).collect(),
+                                  (0..300).map(|i| (i as u64).pow(5) + (1 << 40)).collect()];
+    for vals in &streams {
+        let mut stream = Vec::new();
+        for &val in vals {
+            stream.write_unsigned_with(val, &Config::new()).expect("Should write");
+        }
+        for &stride in &[1, 2, 7, 64, 1000] {
+            let index = StreamIndex::build(&stream, stride).expect("Should index");
+            assert_eq!(index.len(), vals.len());
+            for (i, &val) in vals.iter().enumerate() {
+                assert_eq!(index.get(&stream, i).expect("Should read"), Some(val));
+            }
+            assert_eq!(index.get(&stream, vals.len()).expect("Should read"), None);
+
+            let probes = vals.iter().flat_map(|&v| vec![v.saturating_sub(1), v, v + 1]);
+            for probe in probes.chain(Some(u64::MAX)) {
+                let expected = vals.iter().position(|&v| v >= probe).map(|i| (i, vals[i]));
+                assert_eq!(index.seek_to_value(&stream, probe).expect("Should search"), expected,
+                           "stride {}, probe {}", stride, probe);
+            }
+        }
+    }
+
+    assert!(StreamIndex::build([0x80], 4).is_err());
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_hybrid_blocks() {
+    use block::{decode_block, encode_block, encode_block_with, BlockCodec};
+
+    let blocks: [(Vec<u64>, BlockCodec); 5] =
+        [(vec![], BlockCodec::Leb128),
+         ((0..100).map(|i| if i % 10 == 0 { 1 << 40 } else { i % 7 }).collect(),
+          BlockCodec::Leb128),
+         ((0..100).map(|i| (1 << 50) + i * 3).collect(), BlockCodec::ForLeb128),
+         ((0..100).map(|i| (i * 7919) % 65536).collect(), BlockCodec::BitPacked),
+         (vec![u64::MAX, 0, u64::MAX - 1], BlockCodec::Leb128)];
+
+    let mut buf = Vec::new();
+    for (vals, expected) in &blocks {
+        assert_eq!(encode_block(vals, &mut buf), *expected);
+    }
+    let mut rest = &buf[..];
+    for (vals, _) in &blocks {
+        let mut decoded = Vec::new();
+        rest = decode_block(rest, 1000, &mut decoded).expect("Should decode");
+        assert_eq!(&decoded, vals);
+    }
+    assert!(rest.is_empty());
+
+    // Every codec round trips every block, whether or not it's the best.
+    for (vals, _) in &blocks {
+        for &codec in &[BlockCodec::Leb128, BlockCodec::ForLeb128, BlockCodec::BitPacked] {
+            buf.clear();
+            encode_block_with(vals, codec, &mut buf);
+            let mut decoded = Vec::new();
+            assert!(decode_block(&buf, 1000, &mut decoded).expect("Should decode").is_empty());
+            assert_eq!(&decoded, vals);
+        }
+    }
+
+    let mut out = Vec::new();
+    assert!(matches!(decode_block(&[7, 0], 10, &mut out), Err(read::Error::UnknownDiscriminant(7))));
+    assert!(matches!(decode_block(&[2, 200, 1, 0], 10, &mut out),
+                     Err(read::Error::LengthExceeded { len: 200, max: 10 })));
+    assert!(matches!(decode_block(&[2, 4, 8, 1, 2, 3], 10, &mut out), Err(read::Error::Incomplete)));
+    assert!(matches!(decode_block(&[2, 1, 65, 0], 10, &mut out), Err(read::Error::Overflow)));
+}
+
+#[cfg(all(feature = "alloc", not(feature = "signed-only")))]
+#[test]
+fn test_adjacency_list() {
+    use graph::AdjacencyList;
+
+    let edges = [(3, 1), (0, 2), (3, 7), (0, 2), (3, 0), (5, 5), (0, 100_000)];
+    let graph: AdjacencyList = edges.iter().copied().collect();
+    assert_eq!(graph.node_count(), 100_001);
+    assert_eq!(graph.edge_count(), edges.len());
+    for node in 0..10 {
+        let mut expected: Vec<_> =
+            edges.iter().filter(|&&(from, _)| from == node).map(|&(_, to)| to).collect();
+        expected.sort_unstable();
+        assert_eq!(graph.neighbors(node).collect::<Vec<_>>(), expected, "node {}", node);
+        assert_eq!(graph.degree(node), expected.len());
+    }
+    assert_eq!(graph.neighbors(u64::MAX).count(), 0);
+
+    let empty = AdjacencyList::from_edges(Vec::new());
+    assert_eq!((empty.node_count(), empty.edge_count()), (0, 0));
+    assert_eq!(empty.neighbors(0).count(), 0);
+
+    for &node in &[graph::MAX_NODES, 1 << 40, u64::MAX] {
+        let result = std::panic::catch_unwind(|| AdjacencyList::from_edges(vec![(node, 0)]));
+        assert!(result.is_err(), "node {}", node);
+    }
+}
+
+#[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+#[test]
+fn test_deque_reader_across_wrap() {
+    use deque::DequeReader;
+    use std::collections::VecDeque;
+
+    let mut encoded = Vec::new();
+    encoded.write_unsigned(WIDEST).expect("Should write");
+    encoded.write_signed(-624485).expect("Should write");
+
+    let mut wrapped = 0;
+    for offset in 0..16 {
+        let mut deque = VecDeque::with_capacity(16);
+        // Leave one byte behind, or the emptied deque starts over at the
+        // front of its buffer.
+        deque.extend(std::iter::repeat_n(0, offset + 1));
+        deque.drain(..offset);
+        deque.extend(&encoded);
+        if !deque.as_slices().1.is_empty() {
+            wrapped += 1;
+        }
+
+        let mut reader = DequeReader::new(&mut deque);
+        assert_eq!(reader.read_unsigned().expect("Should read"), (0, 1));
+        assert_eq!(reader.next_len().expect("Should measure"), encoded_len_unsigned(WIDEST));
+        let widest = (WIDEST, encoded_len_unsigned(WIDEST));
+        assert_eq!(reader.peek_unsigned().expect("Should peek"), widest);
+        assert_eq!(reader.read_unsigned().expect("Should read"), widest);
+        assert_eq!(reader.read_signed().expect("Should read"), (-624485, 3));
+        assert!(reader.read_signed().unwrap_err().is_incomplete());
+    }
+    assert!(wrapped > 0);
+}
+
+#[cfg(feature = "unsigned-only")]
+#[test]
+fn test_unsigned_only() {
+    let mut storage = [0u8; 4];
+    let mut writable = &mut storage[..];
+    assert_eq!(writable.write_unsigned_with(624485, &Config::new()).expect("Should write number"),
+               3);
+    assert_eq!(storage, [0xe5, 0x8e, 0x26, 0x00]);
+
+    let mut readable = &storage[..];
+    assert_eq!(readable.read_unsigned_with(&Config::new()).expect("Should read number"),
+               (624485, 3));
+    assert_eq!(slice::decode_unsigned(&storage).expect("Should decode"), (624485, &[0][..]));
+    assert_eq!(encoded_len_unsigned(624485), 3);
+}
+
+#[cfg(feature = "signed-only")]
+#[test]
+fn test_signed_only() {
+    let mut storage = [0u8; 4];
+    let mut writable = &mut storage[..];
+    assert_eq!(writable.write_signed_with(-123456, &Config::new()).expect("Should write number"),
+               3);
+    assert_eq!(storage, [0xc0, 0xbb, 0x78, 0x00]);
+
+    let mut readable = &storage[..];
+    assert_eq!(readable.read_signed_with(&Config::new()).expect("Should read number"),
+               (-123456, 3));
+    assert_eq!(slice::decode_signed(&storage).expect("Should decode"), (-123456, &[0][..]));
+    assert_eq!(encoded_len_signed(-123456), 3);
+}
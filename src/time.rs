@@ -0,0 +1,149 @@
+//! Two encodings are offered. The default one is exact: a `Duration` is its
+//! whole seconds as unsigned LEB128 followed by its nanoseconds as unsigned
+//! LEB128, and a `SystemTime` is its whole seconds since the Unix epoch as
+//! signed LEB128, followed by the nanoseconds past that second. The `_micros`
+//! variants trade precision for size, encoding a single count of
+//! microseconds, which suits timestamps that are large but close together.
+//!
+//! ```
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use nt_leb128::time;
+//!
+//! let mut buf = Vec::new();
+//! time::write_duration(&mut buf, Duration::new(300, 5)).expect("Should write duration");
+//! let then = UNIX_EPOCH + Duration::from_micros(1_600_000_000_000_001);
+//! time::write_system_time_micros(&mut buf, then).expect("Should write time");
+//!
+//! let mut readable = &buf[..];
+//! assert_eq!(time::read_duration(&mut readable).unwrap(), Duration::new(300, 5));
+//! assert_eq!(time::read_system_time_micros(&mut readable).unwrap(), then);
+//! ```
+
+use super::config::Config;
+use super::read::{self, Leb128Read};
+use super::write::{self, Leb128Write};
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Write `duration` as its seconds and then its nanoseconds, each as
+/// unsigned LEB128, returning the number of bytes written.
+///
+/// If the nanoseconds don't fit, the seconds have already been written.
+pub fn write_duration<W: Leb128Write>(buf: &mut W,
+                                      duration: Duration)
+                                      -> Result<usize, write::Error> {
+    let secs = buf.write_unsigned_with(duration.as_secs(), &Config::new())?;
+    Ok(secs + buf.write_unsigned_with(duration.subsec_nanos() as u64, &Config::new())?)
+}
+
+/// Read a `Duration` written by `write_duration`.
+///
+/// Fails with `read::Error::Overflow` if the nanoseconds are a whole second or
+/// more.
+pub fn read_duration<R: Leb128Read>(buf: &mut R) -> Result<Duration, read::Error> {
+    let (secs, _) = buf.read_unsigned_with(&Config::new())?;
+    Ok(Duration::new(secs, read_nanos(buf)?))
+}
+
+/// Write `duration` as a count of microseconds in unsigned LEB128, returning
+/// the number of bytes written. Precision below a microsecond is truncated.
+///
+/// Fails with `write::Error::OutOfRange` if the count doesn't fit in a `u64`.
+pub fn write_duration_micros<W: Leb128Write>(buf: &mut W,
+                                             duration: Duration)
+                                             -> Result<usize, write::Error> {
+    let micros = u64::try_from(duration.as_micros()).map_err(|_| write::Error::OutOfRange)?;
+    buf.write_unsigned_with(micros, &Config::new())
+}
+
+/// Read a `Duration` written by `write_duration_micros`.
+pub fn read_duration_micros<R: Leb128Read>(buf: &mut R) -> Result<Duration, read::Error> {
+    let (micros, _) = buf.read_unsigned_with(&Config::new())?;
+    Ok(Duration::from_micros(micros))
+}
+
+/// Write `time` as its whole seconds since the Unix epoch in signed LEB128,
+/// negative before the epoch, and then the nanoseconds past that second in
+/// unsigned LEB128. Returns the number of bytes written.
+///
+/// Fails with `write::Error::OutOfRange` if the seconds don't fit in an
+/// `i64`. If the nanoseconds don't fit in `buf`, the seconds have already
+/// been written.
+pub fn write_system_time<W: Leb128Write>(buf: &mut W,
+                                         time: SystemTime)
+                                         -> Result<usize, write::Error> {
+    let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => {
+            (i64::try_from(since.as_secs()).map_err(|_| write::Error::OutOfRange)?,
+             since.subsec_nanos())
+        }
+        Err(before) => {
+            let before = before.duration();
+            let secs = i64::try_from(before.as_secs()).map_err(|_| write::Error::OutOfRange)?;
+            match before.subsec_nanos() {
+                0 => (-secs, 0),
+                nanos => {
+                    let secs = secs.checked_add(1).ok_or(write::Error::OutOfRange)?;
+                    (-secs, NANOS_PER_SEC as u32 - nanos)
+                }
+            }
+        }
+    };
+    let len = buf.write_signed_with(secs, &Config::new())?;
+    Ok(len + buf.write_unsigned_with(nanos as u64, &Config::new())?)
+}
+
+/// Read a `SystemTime` written by `write_system_time`.
+///
+/// Fails with `read::Error::Overflow` if the nanoseconds are a whole second or
+/// more, or if the time can't be represented on this platform.
+pub fn read_system_time<R: Leb128Read>(buf: &mut R) -> Result<SystemTime, read::Error> {
+    let (secs, _) = buf.read_signed_with(&Config::new())?;
+    let nanos = Duration::from_nanos(read_nanos(buf)? as u64);
+    let second = if secs < 0 {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+    } else {
+        UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+    };
+    second.and_then(|second| second.checked_add(nanos)).ok_or(read::Error::Overflow)
+}
+
+/// Write `time` as a count of microseconds since the Unix epoch in signed
+/// LEB128, negative before the epoch, returning the number of bytes written.
+/// Precision below a microsecond is truncated towards the epoch.
+///
+/// Fails with `write::Error::OutOfRange` if the count doesn't fit in an `i64`.
+pub fn write_system_time_micros<W: Leb128Write>(buf: &mut W,
+                                                time: SystemTime)
+                                                -> Result<usize, write::Error> {
+    let micros = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => i64::try_from(since.as_micros()).ok(),
+        Err(before) => i64::try_from(before.duration().as_micros()).ok().map(|micros| -micros),
+    };
+    buf.write_signed_with(micros.ok_or(write::Error::OutOfRange)?, &Config::new())
+}
+
+/// Read a `SystemTime` written by `write_system_time_micros`.
+///
+/// Fails with `read::Error::Overflow` if the time can't be represented on
+/// this platform.
+pub fn read_system_time_micros<R: Leb128Read>(buf: &mut R) -> Result<SystemTime, read::Error> {
+    let (micros, _) = buf.read_signed_with(&Config::new())?;
+    let offset = Duration::from_micros(micros.unsigned_abs());
+    let time = if micros < 0 {
+        UNIX_EPOCH.checked_sub(offset)
+    } else {
+        UNIX_EPOCH.checked_add(offset)
+    };
+    time.ok_or(read::Error::Overflow)
+}
+
+fn read_nanos<R: Leb128Read>(buf: &mut R) -> Result<u32, read::Error> {
+    let (nanos, _) = buf.read_unsigned_with(&Config::new())?;
+    if nanos >= NANOS_PER_SEC {
+        return Err(read::Error::Overflow);
+    }
+    Ok(nanos as u32)
+}
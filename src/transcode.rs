@@ -0,0 +1,105 @@
+//! Every number is decoded under the source `Config` and written back under
+//! the destination's, so one pass can turn signed LEB128 into protobuf's
+//! zigzag encoding, or padded encodings into canonical ones, since encodings
+//! are always written at their shortest.
+//!
+//! ```
+//! # #[cfg(not(feature = "unsigned-only"))] {
+//! use nt_leb128::{transcode, Config, Flavor};
+//!
+//! // -2 and 63 as signed LEB128, the second padded to two bytes.
+//! let mut input = &[0x7e, 0xbf, 0x00][..];
+//! let mut output = Vec::new();
+//! let count = transcode::transcode_signed(&Config::with_flavor(Flavor::Dwarf),
+//!                                         &Config::with_flavor(Flavor::ProtobufZigzag),
+//!                                         &mut input,
+//!                                         &mut output)?;
+//! assert_eq!(count, 2);
+//! assert_eq!(output, [0x03, 0x7e]);
+//! # }
+//! # Ok::<(), nt_leb128::transcode::Error>(())
+//! ```
+
+use super::config::Config;
+use super::read::{self, Leb128Read};
+use super::write::{self, Leb128Write};
+use bytes::{Buf, BufMut};
+use std::fmt;
+
+/// A failure to transcode, from either side.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A number couldn't be read under the source rules. `Incomplete` means
+    /// the input ended partway through one.
+    Read(read::Error),
+    /// A number couldn't be written under the destination rules, or there
+    /// was no room for it.
+    Write(write::Error),
+}
+
+impl From<read::Error> for Error {
+    fn from(e: read::Error) -> Self {
+        Error::Read(e)
+    }
+}
+
+impl From<write::Error> for Error {
+    fn from(e: write::Error) -> Self {
+        Error::Write(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Error::Read(ref e) => write!(f, "leb128::transcode::Error: {}", e),
+            Error::Write(ref e) => write!(f, "leb128::transcode::Error: {}", e),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
+        match *self {
+            Error::Read(ref e) => Some(e),
+            Error::Write(ref e) => Some(e),
+        }
+    }
+}
+
+/// Convert every unsigned number in `input` from the `src` rules to the `dst`
+/// rules, returning how many there were.
+///
+/// On failure, the numbers before the one that failed have been written to
+/// `output`, and `input` is left after it.
+#[cfg(not(feature = "signed-only"))]
+pub fn transcode_unsigned<R: Buf, W: BufMut>(src: &Config,
+                                             dst: &Config,
+                                             input: &mut R,
+                                             output: &mut W)
+                                             -> Result<usize, Error> {
+    let mut count = 0;
+    while input.has_remaining() {
+        let (val, _) = input.read_unsigned_with(src)?;
+        output.write_unsigned_with(val, dst)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Like `transcode_unsigned`, for signed numbers.
+#[cfg(not(feature = "unsigned-only"))]
+pub fn transcode_signed<R: Buf, W: BufMut>(src: &Config,
+                                           dst: &Config,
+                                           input: &mut R,
+                                           output: &mut W)
+                                           -> Result<usize, Error> {
+    let mut count = 0;
+    while input.has_remaining() {
+        let (val, _) = input.read_signed_with(src)?;
+        output.write_signed_with(val, dst)?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,107 @@
+use super::codec::{Leb128Decode, Leb128Encode};
+use super::read::{self, Leb128Read};
+use super::write::{self, Leb128Write};
+use super::io_error;
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::encoded_len_unsigned;
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::encoded_len_signed;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+
+/// A `u64` that is encoded as unsigned LEB128.
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use nt_leb128::Uleb128;
+///
+/// assert_eq!(Uleb128::try_from(&[0xe5, 0x8e, 0x26][..]).unwrap(), Uleb128(624485));
+/// assert!(Uleb128::try_from(&[0xe5, 0x8e, 0x26, 0x00][..]).is_err());
+/// ```
+#[cfg(not(feature = "signed-only"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uleb128(pub u64);
+
+/// An `i64` that is encoded as signed LEB128.
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use nt_leb128::Sleb128;
+///
+/// assert_eq!(Sleb128::try_from(&[0x7e][..]).unwrap(), Sleb128(-2));
+/// ```
+#[cfg(not(feature = "unsigned-only"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sleb128(pub i64);
+
+fn trailing_bytes() -> read::Error {
+    io_error(io::ErrorKind::InvalidData, "Trailing bytes after value").into()
+}
+
+macro_rules! impl_wrapper {
+    ($name:ident, $int:ty, $write:ident, $read:ident, $len:ident) => {
+        impl From<$int> for $name {
+            fn from(val: $int) -> Self {
+                $name(val)
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(wrapper: $name) -> Self {
+                wrapper.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        /// Decodes exactly one value, failing if any bytes follow it.
+        impl<'a> TryFrom<&'a [u8]> for $name {
+            type Error = read::Error;
+
+            fn try_from(mut bytes: &'a [u8]) -> Result<Self, read::Error> {
+                let (val, _) = bytes.$read()?;
+                if !bytes.is_empty() {
+                    return Err(trailing_bytes());
+                }
+                Ok($name(val))
+            }
+        }
+
+        impl Leb128Encode for $name {
+            fn encode<W: Leb128Write>(&self, buf: &mut W) -> Result<usize, write::Error> {
+                buf.$write(self.0)
+            }
+
+            fn encoded_len(&self) -> usize {
+                $len(self.0)
+            }
+        }
+
+        impl Leb128Decode for $name {
+            fn decode<R: Leb128Read>(buf: &mut R) -> Result<Self, read::Error> {
+                buf.$read().map(|(val, _)| $name(val))
+            }
+        }
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $name {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                <$int>::arbitrary(u).map($name)
+            }
+
+            fn size_hint(depth: usize) -> (usize, Option<usize>) {
+                <$int as arbitrary::Arbitrary>::size_hint(depth)
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "signed-only"))]
+impl_wrapper!(Uleb128, u64, write_unsigned, read_unsigned, encoded_len_unsigned);
+#[cfg(not(feature = "unsigned-only"))]
+impl_wrapper!(Sleb128, i64, write_signed, read_signed, encoded_len_signed);
@@ -0,0 +1,67 @@
+//! Kani proof harnesses for the core encoding and decoding routines.
+//!
+//! Run them with `cargo kani --features verification`. Each harness covers
+//! every possible input of its type, so a passing run shows the property holds
+//! for all values rather than for a sample of them.
+
+use super::{encoded_len_signed, encoded_len_unsigned};
+use super::read::{decode_signed, decode_unsigned, MAX_LEN};
+use super::write::Leb128Write;
+
+/// Unsigned encodings of any `u64` decode back to it, and take exactly
+/// `encoded_len_unsigned` bytes, which is never more than ten.
+#[kani::proof]
+#[kani::unwind(12)]
+fn unsigned_roundtrip() {
+    let val: u64 = kani::any();
+    let mut buf = [0u8; MAX_LEN];
+    let written = {
+        let mut writable = &mut buf[..];
+        writable.write_unsigned(val).unwrap()
+    };
+    assert!(written <= MAX_LEN);
+    assert_eq!(written, encoded_len_unsigned(val));
+    assert_eq!(decode_unsigned(buf[..written].iter().copied()).unwrap(), (val, written));
+}
+
+/// Signed encodings of any `i64` decode back to it, and take exactly
+/// `encoded_len_signed` bytes, which is never more than ten.
+#[kani::proof]
+#[kani::unwind(12)]
+fn signed_roundtrip() {
+    let val: i64 = kani::any();
+    let mut buf = [0u8; MAX_LEN];
+    let written = {
+        let mut writable = &mut buf[..];
+        writable.write_signed(val).unwrap()
+    };
+    assert!(written <= MAX_LEN);
+    assert_eq!(written, encoded_len_signed(val));
+    assert_eq!(decode_signed(buf[..written].iter().copied()).unwrap(), (val, written));
+}
+
+/// Decoding arbitrary bytes as unsigned never panics, and never consumes
+/// more than ten bytes when it succeeds.
+#[kani::proof]
+#[kani::unwind(13)]
+fn unsigned_decode_is_total() {
+    let bytes: [u8; MAX_LEN + 1] = kani::any();
+    let len: usize = kani::any();
+    kani::assume(len <= bytes.len());
+    if let Ok((_, read)) = decode_unsigned(bytes[..len].iter().copied()) {
+        assert!(read >= 1 && read <= MAX_LEN && read <= len);
+    }
+}
+
+/// Decoding arbitrary bytes as signed never panics, and never consumes more
+/// than ten bytes when it succeeds.
+#[kani::proof]
+#[kani::unwind(13)]
+fn signed_decode_is_total() {
+    let bytes: [u8; MAX_LEN + 1] = kani::any();
+    let len: usize = kani::any();
+    kani::assume(len <= bytes.len());
+    if let Ok((_, read)) = decode_signed(bytes[..len].iter().copied()) {
+        assert!(read >= 1 && read <= MAX_LEN && read <= len);
+    }
+}
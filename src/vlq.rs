@@ -0,0 +1,205 @@
+//! Base64 VLQ is the variable length encoding of the `mappings` of a
+//! JavaScript source map. Like signed LEB128 it stores a number in groups of
+//! bits, least significant first, each group flagged if more follow, but the
+//! groups are five bits written as base64 digits, and the sign is the lowest
+//! bit of the first group rather than two's complement.
+//!
+//! A `mappings` string is lines separated by `;`, each a list of segments
+//! separated by `,`, each segment one, four or five numbers written back to
+//! back. [`segments`](fn.segments.html) splits it up.
+//!
+//! ```
+//! use nt_leb128::vlq;
+//!
+//! let mut s = String::new();
+//! vlq::encode(16, &mut s);
+//! vlq::encode(-1, &mut s);
+//! assert_eq!(s, "gBD");
+//! assert_eq!(vlq::decode(&s).unwrap(), (16, 2));
+//! assert_eq!(vlq::decode(&s[2..]).unwrap(), (-1, 1));
+//!
+//! let segments: Vec<_> = vlq::segments("AAAA,EAAE;;IAAIA").map(Result::unwrap).collect();
+//! assert_eq!((segments[1].line(), segments[1].fields()), (0, &[2, 0, 0, 2][..]));
+//! assert_eq!((segments[2].line(), segments[2].fields()), (2, &[4, 0, 0, 4, 0][..]));
+//! ```
+
+use super::read::{not_enough_data, Error};
+use super::io_error;
+use std::io;
+
+/// The base64 digits, in order of value.
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The bit of a digit flagging that more digits follow.
+const CONTINUATION: u8 = 0x20;
+
+/// The most numbers a segment holds.
+const MAX_FIELDS: usize = 5;
+
+/// Append the Base64 VLQ encoding of `val` to `out`, returning the number of
+/// digits written.
+pub fn encode(val: i64, out: &mut String) -> usize {
+    let mut magnitude = val.unsigned_abs();
+    // The first digit has room for four bits beside the sign.
+    let mut digit = ((magnitude & 0xf) << 1) as u8 | (val < 0) as u8;
+    magnitude >>= 4;
+    let mut len = 1;
+    while magnitude != 0 {
+        out.push(ALPHABET[(digit | CONTINUATION) as usize] as char);
+        digit = (magnitude & 0x1f) as u8;
+        magnitude >>= 5;
+        len += 1;
+    }
+    out.push(ALPHABET[digit as usize] as char);
+    len
+}
+
+/// Append the numbers of a segment to `out` with no separators, returning
+/// the number of digits written. The caller writes the `,` or `;` between
+/// segments.
+pub fn encode_segment(fields: &[i64], out: &mut String) -> usize {
+    fields.iter().map(|&val| encode(val, out)).sum()
+}
+
+/// Decode the Base64 VLQ number at the start of `s`, returning the number
+/// and the length of its encoding in digits.
+///
+/// Fails with `Error::Incomplete` if `s` ends before the number does, with
+/// `Error::Overflow` if the number doesn't fit in an `i64`, and with an
+/// `InvalidData` error at a character that isn't a base64 digit. A negative
+/// zero decodes as zero.
+pub fn decode(s: &str) -> Result<(i64, usize), Error> {
+    let mut magnitude = 0u64;
+    let mut negative = false;
+    let mut shift = 0;
+    for (i, &c) in s.as_bytes().iter().enumerate() {
+        let digit = digit_value(c).ok_or_else(|| invalid_data("Invalid base64 digit"))?;
+        let (payload, bits) = if i == 0 {
+            negative = digit & 1 != 0;
+            ((digit >> 1) & 0xf, 4)
+        } else {
+            (digit & 0x1f, 5)
+        };
+        let wide = (payload as u128) << shift;
+        if wide >> 64 != 0 {
+            return Err(Error::Overflow);
+        }
+        magnitude |= wide as u64;
+        shift = (shift + bits).min(64);
+
+        if digit & CONTINUATION == 0 {
+            let val = match negative {
+                false if magnitude <= i64::MAX as u64 => magnitude as i64,
+                true if magnitude <= 1 << 63 => (magnitude as i64).wrapping_neg(),
+                _ => return Err(Error::Overflow),
+            };
+            return Ok((val, i + 1));
+        }
+    }
+    Err(not_enough_data())
+}
+
+/// Iterate over the segments of the source map `mappings` string, decoding
+/// each. Empty lines and segments are skipped.
+pub fn segments(mappings: &str) -> Segments<'_> {
+    Segments {
+        rest: Some(mappings),
+        line: 0,
+    }
+}
+
+/// One segment of a `mappings` string: the generated column, then optionally
+/// the source index, original line and original column, then optionally the
+/// name index.
+///
+/// As in the string, each number is relative to the same number in the
+/// previous segment, and the generated column to the previous segment on the
+/// same line only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    line: usize,
+    fields: [i64; MAX_FIELDS],
+    len: usize,
+}
+
+impl Segment {
+    /// The generated line the segment is on, counting from zero.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The one, four or five numbers of the segment.
+    pub fn fields(&self) -> &[i64] {
+        &self.fields[..self.len]
+    }
+
+    fn parse(mut s: &str, line: usize) -> Result<Self, Error> {
+        let mut segment = Segment {
+            line,
+            fields: [0; MAX_FIELDS],
+            len: 0,
+        };
+        while !s.is_empty() {
+            if segment.len == MAX_FIELDS {
+                return Err(invalid_data("Segment has more than five fields"));
+            }
+            let (val, len) = decode(s)?;
+            segment.fields[segment.len] = val;
+            segment.len += 1;
+            s = &s[len..];
+        }
+        if segment.len == 2 || segment.len == 3 {
+            return Err(invalid_data("Segment has two or three fields"));
+        }
+        Ok(segment)
+    }
+}
+
+/// An iterator over the segments of a `mappings` string.
+///
+/// Created by [`segments`](fn.segments.html). Each segment is decoded on its
+/// own, so one that is malformed is yielded as an error and iteration goes
+/// on with the next.
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    rest: Option<&'a str>,
+    line: usize,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Result<Segment, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = self.rest?;
+            let line = self.line;
+            let end = rest.find([',', ';']).unwrap_or(rest.len());
+            self.rest = match rest.as_bytes().get(end) {
+                Some(b';') => {
+                    self.line += 1;
+                    Some(&rest[end + 1..])
+                }
+                Some(_) => Some(&rest[end + 1..]),
+                None => None,
+            };
+            if end > 0 {
+                return Some(Segment::parse(&rest[..end], line));
+            }
+        }
+    }
+}
+
+fn digit_value(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+fn invalid_data(msg: &'static str) -> Error {
+    Error::IoError(io_error(io::ErrorKind::InvalidData, msg))
+}
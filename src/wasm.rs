@@ -0,0 +1,73 @@
+//! The functions are exported under camel case names, so from JavaScript:
+//!
+//! ```text
+//! import { encodeUnsigned, decodeUnsignedAll } from "nt-leb128";
+//!
+//! encodeUnsigned(624485n);                            // Uint8Array [0xe5, 0x8e, 0x26]
+//! decodeUnsignedAll(new Uint8Array([0x02, 0x7f]));    // BigUint64Array [2n, 127n]
+//! ```
+//!
+//! Decoding failures are thrown as `Error`s carrying the message of the
+//! underlying [`read::Error`](../read/enum.Error.html).
+
+use super::slice::{self, decode_all};
+use super::write::{put_signed, put_unsigned};
+use wasm_bindgen::prelude::*;
+
+/// Encode `val` as unsigned LEB128.
+#[wasm_bindgen(js_name = encodeUnsigned)]
+pub fn encode_unsigned(val: u64) -> Vec<u8> {
+    encode_unsigned_all(&[val])
+}
+
+/// Encode `val` as signed LEB128.
+#[wasm_bindgen(js_name = encodeSigned)]
+pub fn encode_signed(val: i64) -> Vec<u8> {
+    encode_signed_all(&[val])
+}
+
+/// Decode the unsigned LEB128 value at the start of `bytes`.
+#[wasm_bindgen(js_name = decodeUnsigned)]
+pub fn decode_unsigned(bytes: &[u8]) -> Result<u64, JsError> {
+    Ok(slice::decode_unsigned(bytes)?.0)
+}
+
+/// Decode the signed LEB128 value at the start of `bytes`.
+#[wasm_bindgen(js_name = decodeSigned)]
+pub fn decode_signed(bytes: &[u8]) -> Result<i64, JsError> {
+    Ok(slice::decode_signed(bytes)?.0)
+}
+
+/// Encode every value in `vals` as unsigned LEB128, back to back.
+#[wasm_bindgen(js_name = encodeUnsignedAll)]
+pub fn encode_unsigned_all(vals: &[u64]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(vals.len());
+    for &val in vals {
+        put_unsigned(&mut out, val);
+    }
+    out
+}
+
+/// Encode every value in `vals` as signed LEB128, back to back.
+#[wasm_bindgen(js_name = encodeSignedAll)]
+pub fn encode_signed_all(vals: &[i64]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(vals.len());
+    for &val in vals {
+        put_signed(&mut out, val);
+    }
+    out
+}
+
+/// Decode `bytes` as a sequence of unsigned LEB128 values, all of which must
+/// be complete.
+#[wasm_bindgen(js_name = decodeUnsignedAll)]
+pub fn decode_unsigned_all(bytes: &[u8]) -> Result<Vec<u64>, JsError> {
+    Ok(decode_all(bytes, slice::decode_unsigned)?)
+}
+
+/// Decode `bytes` as a sequence of signed LEB128 values, all of which must be
+/// complete.
+#[wasm_bindgen(js_name = decodeSignedAll)]
+pub fn decode_signed_all(bytes: &[u8]) -> Result<Vec<i64>, JsError> {
+    Ok(decode_all(bytes, slice::decode_signed)?)
+}
@@ -1,65 +1,1166 @@
-use super::{CONTINUATION_BIT, low_bits_of_u64};
+use super::{io_error, metrics};
+#[cfg(not(feature = "unsigned-only"))]
+use super::raw;
+use super::buf::Sink;
+use super::config::{Config, DEFAULT_FLAVORED};
+#[cfg(not(feature = "unsigned-only"))]
+use super::config::SignedEncoding;
+use nt_leb128_core::encode_unsigned_const;
+#[cfg(not(feature = "unsigned-only"))]
+use nt_leb128_core::{encode_signed_const, encoded_len_signed};
+#[cfg(not(feature = "signed-only"))]
+use nt_leb128_core::encoded_len_unsigned;
+#[cfg(feature = "bytes")]
+use super::buf::Bytes05;
+use super::read::MAX_LEN;
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+use super::slice::encode_unsigned_padded;
+use std::fmt;
 use std::io;
-use bytes::{BytesMut, BufMut};
+#[cfg(feature = "bytes")]
+use std::mem::MaybeUninit;
+#[cfg(feature = "bytes")]
+use bytes::BufMut;
+
+/// The old name of [`Leb128Write`](trait.Leb128Write.html), kept for one
+/// release.
+#[deprecated(note = "renamed to `Leb128Write`")]
+pub use self::Leb128Write as LEB128Write;
 
 /// Trait for writing signed and unsigned LEB128 encoded numbers
-pub trait LEB128Write {
+pub trait Leb128Write {
     /// Write the given signed number using the LEB128 encoding to the given
-    /// `std::io::Write`able. Returns the number of bytes written to `w`, or an
-    /// error if writing failed.
-    fn write_signed(&mut self, val: i64) -> Result<usize, io::Error>;
+    /// writer. Returns the number of bytes written, or an error if writing
+    /// failed.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed(&mut self, val: i64) -> Result<usize, Error>;
 
     /// Write the given unsigned number using the LEB128 encoding to the given
-    /// `std::io::Write`able. Returns the number of bytes written to `w`, or an
+    /// writer. Returns the number of bytes written, or an error if writing
+    /// failed.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned(&mut self, val: u64) -> Result<usize, Error>;
+
+    /// Like `write_signed`, but checks up front that the encoding fits in
+    /// the remaining capacity, failing with `Error::InsufficientSpace`
+    /// and writing nothing if it doesn't, where `write_signed` would panic.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn try_write_signed(&mut self, val: i64) -> Result<usize, Error>;
+
+    /// Like `write_unsigned`, but checks up front that the encoding fits in
+    /// the remaining capacity, failing with `Error::InsufficientSpace`
+    /// and writing nothing if it doesn't, where `write_unsigned` would panic.
+    #[cfg(not(feature = "signed-only"))]
+    fn try_write_unsigned(&mut self, val: u64) -> Result<usize, Error>;
+
+    /// Write an enum discriminant, or any other tag convertible to `u64`, as
+    /// unsigned LEB128. Returns the number of bytes written.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_enum<T: Into<u64>>(&mut self, val: T) -> Result<usize, Error>
+        where Self: Sized
+    {
+        self.write_unsigned(val.into())
+    }
+
+    /// Write a 32 bit signed number as signed LEB128. The encoding is at most
+    /// [`MAX_LEN_I32`](../constant.MAX_LEN_I32.html) (five) bytes, so callers
+    /// with fixed capacity can budget for it without the ten byte bound of
+    /// `write_signed`. Returns the number of bytes written.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed_i32(&mut self, val: i32) -> Result<usize, Error> {
+        self.write_signed(val as i64)
+    }
+
+    /// Write a 32 bit unsigned number as unsigned LEB128. The encoding is at
+    /// most [`MAX_LEN_U32`](../constant.MAX_LEN_U32.html) (five) bytes, so
+    /// callers with fixed capacity can budget for it without the ten byte
+    /// bound of `write_unsigned`. Returns the number of bytes written.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned_u32(&mut self, val: u32) -> Result<usize, Error> {
+        self.write_unsigned(val as u64)
+    }
+
+    /// Like `write_signed_i32`, but checks up front that the encoding fits,
+    /// as `try_write_signed` does.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn try_write_signed_i32(&mut self, val: i32) -> Result<usize, Error> {
+        self.try_write_signed(val as i64)
+    }
+
+    /// Like `write_unsigned_u32`, but checks up front that the encoding fits,
+    /// as `try_write_unsigned` does.
+    #[cfg(not(feature = "signed-only"))]
+    fn try_write_unsigned_u32(&mut self, val: u32) -> Result<usize, Error> {
+        self.try_write_unsigned(val as u64)
+    }
+
+    /// Write an unsigned number under the rules of `config`, which may
+    /// truncate it or reject it with `Error::OutOfRange` if it's wider than
+    /// they allow, or with `Error::LengthExceeded` if its encoding is longer.
+    ///
+    /// Only `config` applies, not a `default-flavor-*` feature's rules. The
+    /// default implementation writes through `write_unsigned`, so writers
+    /// outside this crate should override it to keep to that.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned_with(&mut self, val: u64, config: &Config) -> Result<usize, Error> {
+        let val = config.encodable_unsigned(val)?;
+        self.write_unsigned(val)
+    }
+
+    /// Write a signed number under the rules of `config`, which may truncate
+    /// it or reject it with `Error::OutOfRange` if it's wider than they
+    /// allow, or with `Error::LengthExceeded` if its encoding is longer.
+    /// Like `write_unsigned_with`, only `config` applies.
+    #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+    fn write_signed_with(&mut self, val: i64, config: &Config) -> Result<usize, Error> {
+        let val = config.encodable_signed(val)?;
+        match config.signed() {
+            SignedEncoding::Sleb128 => self.write_signed(val),
+            SignedEncoding::ZigZag => self.write_unsigned(raw::zigzag_encode(val)),
+            SignedEncoding::SignExtended => self.write_unsigned(val as u64),
+        }
+    }
+
+    /// Write a signed number under the rules of `config`, which may truncate
+    /// it or reject it with `Error::OutOfRange` if it's wider than they
+    /// allow, or with `Error::LengthExceeded` if its encoding is longer.
+    ///
+    /// Zigzag and sign extended numbers are written as unsigned LEB128, so
+    /// without that half there is no default implementation.
+    #[cfg(feature = "signed-only")]
+    fn write_signed_with(&mut self, val: i64, config: &Config) -> Result<usize, Error>;
+
+    /// Write two numbers using the unsigned LEB128 encoding, back to back, such
+    /// as the code and tag that open a DWARF abbreviation. Returns the total
+    /// number of bytes written, or an error if writing failed.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_uleb_pair(&mut self, first: u64, second: u64) -> Result<usize, Error> {
+        Ok(self.write_unsigned(first)? + self.write_unsigned(second)?)
+    }
+
+    /// Write an optional unsigned number as a one byte presence tag, `0x00`
+    /// for `None` or `0x01` for `Some`, followed in the latter case by the
+    /// number as unsigned LEB128. Returns the total number of bytes written,
+    /// or an error if writing failed.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_option_unsigned(&mut self, val: Option<u64>) -> Result<usize, Error> {
+        // Always the single byte documented above.
+        let tag = self.write_unsigned_with(val.is_some() as u64, &Config::new())?;
+        match val {
+            None => Ok(tag),
+            Some(val) => Ok(tag + self.write_unsigned(val)?),
+        }
+    }
+
+    /// Write the decimal `mantissa * 10^exponent` as its mantissa and then its
+    /// exponent, both as signed LEB128, for formats that carry exact decimal
+    /// amounts rather than floats. Returns the total number of bytes written,
+    /// or an error if writing failed.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_decimal(&mut self, mantissa: i64, exponent: i8) -> Result<usize, Error> {
+        Ok(self.write_signed(mantissa)? + self.write_signed(exponent as i64)?)
+    }
+
+    /// Write `bytes` prefixed with its length as unsigned LEB128. Returns the
+    /// total number of bytes written, or an error if writing failed.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error>;
+
+    /// Write `vals` prefixed with their count as unsigned LEB128, each as
+    /// signed LEB128. Returns the total number of bytes written, or an error
+    /// if writing failed.
+    #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))]
+    fn write_vec_signed(&mut self, vals: &[i64]) -> Result<usize, Error> {
+        let mut total = self.write_unsigned(vals.len() as u64)?;
+        for &val in vals {
+            total += self.write_signed(val)?;
+        }
+        Ok(total)
+    }
+
+    /// Write `vals` prefixed with their count as unsigned LEB128, each as
+    /// unsigned LEB128. Returns the total number of bytes written, or an
     /// error if writing failed.
-    fn write_unsigned(&mut self, val: u64) -> Result<usize, io::Error>;
+    #[cfg(not(feature = "signed-only"))]
+    fn write_vec_unsigned(&mut self, vals: &[u64]) -> Result<usize, Error> {
+        let mut total = self.write_unsigned(vals.len() as u64)?;
+        for &val in vals {
+            total += self.write_unsigned(val)?;
+        }
+        Ok(total)
+    }
+
+    /// Write `s` prefixed with its length in bytes as unsigned LEB128.
+    /// Returns the total number of bytes written, or an error if writing
+    /// failed.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_str(&mut self, s: &str) -> Result<usize, Error> {
+        self.write_bytes(s.as_bytes())
+    }
+
+    /// Write every number in `vals` using the signed LEB128 encoding, back to
+    /// back. Returns the total number of bytes written, or the first error.
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed_iter<I>(&mut self, vals: I) -> Result<usize, Error>
+        where I: IntoIterator<Item = i64>,
+              Self: Sized
+    {
+        vals.into_iter().try_fold(0, |total, val| Ok(total + self.write_signed(val)?))
+    }
+
+    /// Write every number in `vals` using the unsigned LEB128 encoding, back
+    /// to back. Returns the total number of bytes written, or the first error.
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned_iter<I>(&mut self, vals: I) -> Result<usize, Error>
+        where I: IntoIterator<Item = u64>,
+              Self: Sized
+    {
+        vals.into_iter().try_fold(0, |total, val| Ok(total + self.write_unsigned(val)?))
+    }
 }
 
-impl<W> LEB128Write for W
+#[cfg(feature = "bytes")]
+impl<W> Leb128Write for W
     where W: BufMut
 {
-    fn write_signed(&mut self, mut val: i64) -> Result<usize, io::Error> {
-        let mut bytes_written = 0;
-        loop {
-            let mut byte = val as u8;
-            // Keep the sign bit for testing
-            val >>= 6;
-            let done = val == 0 || val == -1;
-            if done {
-                byte &= !CONTINUATION_BIT;
-            } else {
-                // Remove the sign bit
-                val >>= 1;
-                // More bytes to come, so set the continuation bit.
-                byte |= CONTINUATION_BIT;
-            }
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        write_signed_to(&mut Bytes05(self), val)
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        write_unsigned_to(&mut Bytes05(self), val)
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn try_write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        try_put_signed(&mut Bytes05(self), val)
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn try_write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        try_put_unsigned(&mut Bytes05(self), val)
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        Ok(put_bytes(&mut Bytes05(self), bytes))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned_with(&mut self, val: u64, config: &Config) -> Result<usize, Error> {
+        put_unsigned_with(&mut Bytes05(self), val, config)
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed_with(&mut self, val: i64, config: &Config) -> Result<usize, Error> {
+        put_signed_with(&mut Bytes05(self), val, config)
+    }
+}
 
-            self.put_u8(byte);
-            bytes_written += 1;
+/// Without the `bytes` feature, mutable byte slices are written directly,
+/// advancing past what was written.
+#[cfg(not(feature = "bytes"))]
+impl Leb128Write for &mut [u8] {
+    forward_write!();
+}
+
+/// Without the `bytes` feature, `Vec`s are written directly, growing as
+/// needed.
+#[cfg(not(feature = "bytes"))]
+impl Leb128Write for Vec<u8> {
+    forward_write!();
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) fn put_signed<S: Sink>(dst: &mut S, val: i64) -> usize {
+    let (bytes, len) = encode_signed_const::<MAX_LEN>(val);
+    dst.put_slice(&bytes[..len]);
+    metrics::record_encoded(len);
+    len
+}
+
+pub(crate) fn put_unsigned<S: Sink>(dst: &mut S, val: u64) -> usize {
+    let (bytes, len) = encode_unsigned_const::<MAX_LEN>(val);
+    dst.put_slice(&bytes[..len]);
+    metrics::record_encoded(len);
+    len
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) fn write_signed_to<S: Sink>(dst: &mut S, val: i64) -> Result<usize, Error> {
+    let val = default_signed(val)?;
+    Ok(put_signed(dst, val))
+}
 
-            if done {
-                return Ok(bytes_written);
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn write_unsigned_to<S: Sink>(dst: &mut S, val: u64) -> Result<usize, Error> {
+    let val = default_unsigned(val)?;
+    Ok(put_unsigned(dst, val))
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) fn try_put_signed<S: Sink>(dst: &mut S, val: i64) -> Result<usize, Error> {
+    let val = default_signed(val)?;
+    check_space(encoded_len_signed(val), dst.remaining_mut())?;
+    Ok(put_signed(dst, val))
+}
+
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn try_put_unsigned<S: Sink>(dst: &mut S, val: u64) -> Result<usize, Error> {
+    let val = default_unsigned(val)?;
+    check_space(encoded_len_unsigned(val), dst.remaining_mut())?;
+    Ok(put_unsigned(dst, val))
+}
+
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn put_unsigned_with<S: Sink>(dst: &mut S,
+                                         val: u64,
+                                         config: &Config)
+                                         -> Result<usize, Error> {
+    let val = config.encodable_unsigned(val)?;
+    Ok(put_unsigned(dst, val))
+}
+
+#[cfg(not(feature = "unsigned-only"))]
+pub(crate) fn put_signed_with<S: Sink>(dst: &mut S,
+                                       val: i64,
+                                       config: &Config)
+                                       -> Result<usize, Error> {
+    let val = config.encodable_signed(val)?;
+    Ok(match config.signed() {
+        SignedEncoding::Sleb128 => put_signed(dst, val),
+        SignedEncoding::ZigZag => put_unsigned(dst, raw::zigzag_encode(val)),
+        SignedEncoding::SignExtended => put_unsigned(dst, val as u64),
+    })
+}
+
+#[cfg(not(feature = "signed-only"))]
+pub(crate) fn put_bytes<S: Sink>(dst: &mut S, bytes: &[u8]) -> usize {
+    let prefix = put_unsigned(dst, bytes.len() as u64);
+    dst.put_slice(bytes);
+    prefix + bytes.len()
+}
+
+/// Apply the rules of the flavor chosen by a `default-flavor-*` feature, if
+/// any, to a value about to be written.
+#[cfg(not(feature = "unsigned-only"))]
+fn default_signed(val: i64) -> Result<i64, Error> {
+    if !DEFAULT_FLAVORED {
+        return Ok(val);
+    }
+    Config::DEFAULT.encodable_signed(val)
+}
+
+/// Like `default_signed`, for unsigned values.
+#[cfg(not(feature = "signed-only"))]
+fn default_unsigned(val: u64) -> Result<u64, Error> {
+    if !DEFAULT_FLAVORED {
+        return Ok(val);
+    }
+    Config::DEFAULT.encodable_unsigned(val)
+}
+
+fn check_space(needed: usize, available: usize) -> Result<(), Error> {
+    if needed > available {
+        return Err(Error::InsufficientSpace { needed, available });
+    }
+    Ok(())
+}
+
+/// An enumeration of the possible errors that can occur when writing a
+/// number encoded with LEB128.
+///
+/// Writing to a `BufMut` can't fail the way I/O can, so apart from
+/// `Leb128Writer`'s `Io` this only describes encodings that don't fit where
+/// they are going. It converts into an `io::Error` for code that reports
+/// `io::Error`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The encoding needs more bytes than there is room for.
+    InsufficientSpace {
+        /// The length of the encoding.
+        needed: usize,
+        /// The room there is.
+        available: usize,
+    },
+    /// A length to be written is larger than the maximum allowed.
+    LengthExceeded {
+        /// The length.
+        len: usize,
+        /// The maximum allowed.
+        max: usize,
+    },
+    /// A value can't be represented in the chosen encoding, such as a
+    /// duration too long to count in microseconds as a `u64`.
+    OutOfRange,
+    /// The encoding would take a `BudgetedWriter` past its byte budget.
+    BudgetExceeded {
+        /// The length of the encoding.
+        needed: usize,
+        /// The bytes left in the budget.
+        remaining: usize,
+    },
+    /// A `Leb128Writer` failed to write out its buffer, with an error of
+    /// this kind. The writer keeps the error itself for `take_error`.
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Error::InsufficientSpace { needed, available } => {
+                write!(f,
+                       "leb128::write::Error: Needed {} bytes but only {} are available",
+                       needed,
+                       available)
+            }
+            Error::LengthExceeded { len, max } => {
+                write!(f,
+                       "leb128::write::Error: The length {} is larger than the maximum of {}",
+                       len,
+                       max)
+            }
+            Error::OutOfRange => {
+                write!(f,
+                       "leb128::write::Error: The value can't be represented in the encoding")
+            }
+            Error::BudgetExceeded { needed, remaining } => {
+                write!(f,
+                       "leb128::write::Error: Needed {} bytes but only {} are left in the budget",
+                       needed,
+                       remaining)
+            }
+            Error::Io(kind) => {
+                write!(f, "leb128::write::Error: Writing out the buffer failed: {}", kind)
             }
         }
     }
+}
+
+impl ::std::error::Error for Error {}
 
-    fn write_unsigned(&mut self, mut val: u64) -> Result<usize, io::Error> {
-        let mut bytes_written = 0;
-        loop {
-            let mut byte = low_bits_of_u64(val);
-            val >>= 7;
-            if val != 0 {
-                // More bytes to come, so set the continuation bit.
-                byte |= CONTINUATION_BIT;
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::InsufficientSpace { .. } => {
+                io_error(io::ErrorKind::WriteZero, "Not enough space for the encoding")
+            }
+            Error::LengthExceeded { .. } => {
+                io_error(io::ErrorKind::InvalidInput, "Length is larger than the maximum")
+            }
+            Error::OutOfRange => {
+                io_error(io::ErrorKind::InvalidInput, "Value can't be represented in the encoding")
+            }
+            Error::BudgetExceeded { .. } => {
+                io_error(io::ErrorKind::WriteZero, "Encoding would exceed the byte budget")
             }
+            Error::Io(kind) => io_error(kind, "Writing out the buffer failed"),
+        }
+    }
+}
+
+/// A writer that forwards to an inner writer while counting how many values
+/// and bytes have been written through it.
+///
+/// ```
+/// # #[cfg(not(any(feature = "signed-only", feature = "unsigned-only")))] {
+/// use nt_leb128::write::{CountingWriter, Leb128Write};
+///
+/// let mut writer = CountingWriter::new(Vec::new());
+/// writer.write_unsigned(624485).expect("Should write number");
+/// writer.write_signed(-1).expect("Should write number");
+/// assert_eq!(writer.total_values(), 2);
+/// assert_eq!(writer.total_bytes(), 4);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CountingWriter<W> {
+    inner: W,
+    values: usize,
+    bytes: usize,
+}
+
+impl<W: Leb128Write> CountingWriter<W> {
+    /// Wrap `inner`, with both counters starting at zero.
+    pub fn new(inner: W) -> Self {
+        CountingWriter {
+            inner,
+            values: 0,
+            bytes: 0,
+        }
+    }
+
+    /// The number of values written so far.
+    pub fn total_values(&self) -> usize {
+        self.values
+    }
+
+    /// The number of bytes written so far.
+    pub fn total_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Reset both counters to zero.
+    pub fn reset(&mut self) {
+        self.values = 0;
+        self.bytes = 0;
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer. Bytes written directly
+    /// to it are not counted.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn count(&mut self, written: usize) -> usize {
+        self.values += 1;
+        self.bytes += written;
+        written
+    }
+}
+
+impl<W: Leb128Write> Leb128Write for CountingWriter<W> {
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        let written = self.inner.write_signed(val)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        let written = self.inner.write_unsigned(val)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn try_write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        let written = self.inner.try_write_signed(val)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn try_write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        let written = self.inner.try_write_unsigned(val)?;
+        Ok(self.count(written))
+    }
 
-            let buf = [byte];
-            self.put_u8(byte);
-            bytes_written += 1;
+    #[cfg(not(feature = "signed-only"))]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let written = self.inner.write_bytes(bytes)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned_with(&mut self, val: u64, config: &Config) -> Result<usize, Error> {
+        let written = self.inner.write_unsigned_with(val, config)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed_with(&mut self, val: i64, config: &Config) -> Result<usize, Error> {
+        let written = self.inner.write_signed_with(val, config)?;
+        Ok(self.count(written))
+    }
+}
+
+/// A writer that forwards to an inner writer until a total number of bytes
+/// has been written, then fails with `Error::BudgetExceeded` rather than
+/// write anything past it, as when assembling a packet that must fit an MTU.
+///
+/// Each write is checked before anything is written, so a failed write
+/// leaves both the inner writer and the budget as they were.
+///
+/// ```
+/// # #[cfg(not(feature = "signed-only"))] {
+/// use nt_leb128::write::{BudgetedWriter, Error, Leb128Write};
+///
+/// let mut writer = BudgetedWriter::new(Vec::new(), 4);
+/// writer.write_unsigned(624485).expect("Should write number");
+/// assert_eq!(writer.write_unsigned(300),
+///            Err(Error::BudgetExceeded { needed: 2, remaining: 1 }));
+/// writer.write_unsigned(1).expect("Should write number");
+/// assert_eq!(writer.remaining(), 0);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BudgetedWriter<W> {
+    inner: W,
+    budget: usize,
+    used: usize,
+}
+
+impl<W: Leb128Write> BudgetedWriter<W> {
+    /// Wrap `inner`, allowing `budget` bytes to be written through it.
+    pub fn new(inner: W, budget: usize) -> Self {
+        BudgetedWriter {
+            inner,
+            budget,
+            used: 0,
+        }
+    }
 
-            if val == 0 {
-                return Ok(bytes_written);
+    /// The bytes written so far.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// The bytes left in the budget.
+    pub fn remaining(&self) -> usize {
+        self.budget - self.used
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer. Bytes written directly
+    /// to it don't count against the budget.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn check(&self, needed: usize) -> Result<(), Error> {
+        if needed > self.remaining() {
+            return Err(Error::BudgetExceeded {
+                needed,
+                remaining: self.remaining(),
+            });
+        }
+        Ok(())
+    }
+
+    fn spend(&mut self, written: usize) -> usize {
+        self.used += written;
+        written
+    }
+}
+
+impl<W: Leb128Write> Leb128Write for BudgetedWriter<W> {
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        self.check(encoded_len_signed(val))?;
+        let written = self.inner.write_signed(val)?;
+        Ok(self.spend(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        self.check(encoded_len_unsigned(val))?;
+        let written = self.inner.write_unsigned(val)?;
+        Ok(self.spend(written))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn try_write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        self.check(encoded_len_signed(val))?;
+        let written = self.inner.try_write_signed(val)?;
+        Ok(self.spend(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn try_write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        self.check(encoded_len_unsigned(val))?;
+        let written = self.inner.try_write_unsigned(val)?;
+        Ok(self.spend(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        self.check(encoded_len_unsigned(bytes.len() as u64).saturating_add(bytes.len()))?;
+        let written = self.inner.write_bytes(bytes)?;
+        Ok(self.spend(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned_with(&mut self, val: u64, config: &Config) -> Result<usize, Error> {
+        self.check(encoded_len_unsigned(config.encodable_unsigned(val)?))?;
+        let written = self.inner.write_unsigned_with(val, config)?;
+        Ok(self.spend(written))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed_with(&mut self, val: i64, config: &Config) -> Result<usize, Error> {
+        self.check(config.encoded_len_signed(config.encodable_signed(val)?))?;
+        let written = self.inner.write_signed_with(val, config)?;
+        Ok(self.spend(written))
+    }
+}
+
+/// The buffer size of a `Leb128Writer` made with `new`.
+#[cfg(feature = "alloc")]
+const DEFAULT_WRITER_CAPACITY: usize = 8 * 1024;
+
+/// A writer that encodes into a buffer of its own and writes it to an
+/// `io::Write` whenever it fills, so a file can be written a value at a time
+/// without a system call for each.
+///
+/// The `Leb128Write` methods fail with `Error::Io` if writing out the buffer
+/// fails, and the writer keeps the `io::Error` itself for
+/// [`take_error`](#method.take_error). Anything still buffered when the
+/// writer is dropped is written then, with errors ignored, so call
+/// [`flush`](#method.flush) to find out about them.
+///
+/// ```
+/// # #[cfg(not(feature = "signed-only"))] {
+/// use nt_leb128::write::{Leb128Write, Leb128Writer};
+///
+/// let mut writer = Leb128Writer::with_capacity(64, Vec::new());
+/// writer.write_unsigned(624485).expect("Should write number");
+/// writer.write_str("hello").expect("Should write string");
+/// assert!(writer.get_ref().is_empty());
+///
+/// writer.flush().expect("Should flush");
+/// assert_eq!(writer.get_ref(), b"\xe5\x8e\x26\x05hello");
+/// assert_eq!(writer.total_bytes(), 9);
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct Leb128Writer<W: io::Write> {
+    // Only `None` once `into_inner` has taken it.
+    inner: Option<W>,
+    buf: Vec<u8>,
+    capacity: usize,
+    total: u64,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "alloc")]
+impl<W: io::Write> Leb128Writer<W> {
+    /// Wrap `inner` with an 8 KiB buffer.
+    pub fn new(inner: W) -> Self {
+        Leb128Writer::with_capacity(DEFAULT_WRITER_CAPACITY, inner)
+    }
+
+    /// Wrap `inner` with a buffer of `capacity` bytes. Byte strings longer
+    /// than the buffer are written straight through.
+    pub fn with_capacity(capacity: usize, inner: W) -> Self {
+        Leb128Writer {
+            inner: Some(inner),
+            buf: Vec::with_capacity(capacity),
+            capacity,
+            total: 0,
+            error: None,
+        }
+    }
+
+    /// Write out everything buffered, then flush the inner writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.flush_buf()?;
+        self.inner_mut().flush()
+    }
+
+    /// The number of bytes written so far, whether or not they have left
+    /// the buffer yet.
+    pub fn total_bytes(&self) -> u64 {
+        self.total
+    }
+
+    /// The bytes buffered but not yet written to the inner writer.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// The size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Take the `io::Error` behind the last `Error::Io` a `Leb128Write`
+    /// method failed with, if it hasn't been taken already.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.as_ref().expect("inner writer is only taken by into_inner")
+    }
+
+    /// Get a mutable reference to the inner writer. Bytes written directly
+    /// to it land ahead of anything still buffered, and are not counted.
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner_mut()
+    }
+
+    /// Write out everything buffered and unwrap the inner writer.
+    ///
+    /// If writing out the buffer fails, the error comes back together with
+    /// the writer, still holding what it couldn't write.
+    pub fn into_inner(mut self) -> Result<W, IntoInnerError<W>> {
+        match self.flush_buf() {
+            Ok(()) => Ok(self.inner.take().expect("inner writer is only taken by into_inner")),
+            Err(error) => Err(IntoInnerError { writer: self, error }),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut W {
+        self.inner.as_mut().expect("inner writer is only taken by into_inner")
+    }
+
+    /// Write the buffer to the inner writer, keeping whatever it didn't
+    /// take if it fails.
+    fn flush_buf(&mut self) -> io::Result<()> {
+        let inner = match self.inner {
+            Some(ref mut inner) => inner,
+            None => return Ok(()),
+        };
+        let mut written = 0;
+        let result = loop {
+            if written == self.buf.len() {
+                break Ok(());
+            }
+            match inner.write(&self.buf[written..]) {
+                Ok(0) => {
+                    break Err(io_error(io::ErrorKind::WriteZero,
+                                       "Failed to write the buffered data"))
+                }
+                Ok(n) => written += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
             }
+        };
+        self.buf.drain(..written);
+        result
+    }
+
+    /// Write out the buffer if `len` more bytes wouldn't fit in it.
+    fn make_room(&mut self, len: usize) -> Result<(), Error> {
+        if self.buf.len().saturating_add(len) > self.capacity {
+            let result = self.flush_buf();
+            self.keep_error(result)?;
+        }
+        Ok(())
+    }
+
+    /// Turn an `io::Error` into `Error::Io`, keeping the original for
+    /// `take_error`.
+    fn keep_error(&mut self, result: io::Result<()>) -> Result<(), Error> {
+        result.map_err(|e| {
+            let kind = e.kind();
+            self.error = Some(e);
+            Error::Io(kind)
+        })
+    }
+
+    fn count(&mut self, written: usize) -> usize {
+        self.total += written as u64;
+        written
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: io::Write> Leb128Write for Leb128Writer<W> {
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        self.make_room(MAX_LEN)?;
+        let written = write_signed_to(&mut self.buf, val)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        self.make_room(MAX_LEN)?;
+        let written = write_unsigned_to(&mut self.buf, val)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn try_write_signed(&mut self, val: i64) -> Result<usize, Error> {
+        self.make_room(MAX_LEN)?;
+        let written = try_put_signed(&mut self.buf, val)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn try_write_unsigned(&mut self, val: u64) -> Result<usize, Error> {
+        self.make_room(MAX_LEN)?;
+        let written = try_put_unsigned(&mut self.buf, val)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_unsigned_with(&mut self, val: u64, config: &Config) -> Result<usize, Error> {
+        self.make_room(MAX_LEN)?;
+        let written = put_unsigned_with(&mut self.buf, val, config)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "unsigned-only"))]
+    fn write_signed_with(&mut self, val: i64, config: &Config) -> Result<usize, Error> {
+        self.make_room(MAX_LEN)?;
+        let written = put_signed_with(&mut self.buf, val, config)?;
+        Ok(self.count(written))
+    }
+
+    #[cfg(not(feature = "signed-only"))]
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        let (prefix, prefix_len) = encode_unsigned_const::<MAX_LEN>(bytes.len() as u64);
+        let prefix = &prefix[..prefix_len];
+        let len = prefix_len.saturating_add(bytes.len());
+        self.make_room(len)?;
+        if len > self.capacity {
+            // The buffer is empty now, so the prefix goes straight through
+            // with the payload rather than being left behind in the buffer
+            // if the payload fails.
+            let inner = self.inner_mut();
+            let result = inner.write_all(prefix).and_then(|()| inner.write_all(bytes));
+            self.keep_error(result)?;
+        } else {
+            self.buf.extend_from_slice(prefix);
+            self.buf.extend_from_slice(bytes);
+        }
+        metrics::record_encoded(prefix_len);
+        Ok(self.count(len))
+    }
+}
+
+/// Raw bytes, such as a file header, can be written between the numbers.
+#[cfg(feature = "alloc")]
+impl<W: io::Write> io::Write for Leb128Writer<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + bytes.len() > self.capacity {
+            self.flush_buf()?;
+        }
+        let written = if bytes.len() > self.capacity {
+            self.inner_mut().write(bytes)?
+        } else {
+            self.buf.extend_from_slice(bytes);
+            bytes.len()
+        };
+        self.total += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Leb128Writer::flush(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: io::Write> Drop for Leb128Writer<W> {
+    fn drop(&mut self) {
+        let _ = self.flush_buf();
+    }
+}
+
+/// The error from [`Leb128Writer::into_inner`](struct.Leb128Writer.html#method.into_inner)
+/// when the buffer couldn't be written out, holding the writer so nothing
+/// buffered is lost.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct IntoInnerError<W: io::Write> {
+    writer: Leb128Writer<W>,
+    error: io::Error,
+}
+
+#[cfg(feature = "alloc")]
+impl<W: io::Write> IntoInnerError<W> {
+    /// The error writing out the buffer failed with.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Take back the writer, to try again or recover what it buffered.
+    pub fn into_inner(self) -> Leb128Writer<W> {
+        self.writer
+    }
+
+    /// Take the error, dropping the writer.
+    pub fn into_error(self) -> io::Error {
+        self.error
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: io::Write> fmt::Display for IntoInnerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: io::Write + fmt::Debug> std::error::Error for IntoInnerError<W> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: io::Write> From<IntoInnerError<W>> for io::Error {
+    fn from(err: IntoInnerError<W>) -> io::Error {
+        err.error
+    }
+}
+
+/// Space reserved in a buffer for an unsigned LEB128 number that isn't known
+/// yet, such as the length of a body that is about to be written.
+///
+/// Created by [`reserve_unsigned`](fn.reserve_unsigned.html).
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "a placeholder should be filled in once its value is known"]
+pub struct Placeholder {
+    offset: usize,
+    len: usize,
+}
+
+/// Reserve `max_len` bytes at the end of `buf` for an unsigned number to be
+/// filled in later with [`Placeholder::fill`](struct.Placeholder.html#method.fill).
+///
+/// The reserved bytes hold a padded encoding of zero until then. `max_len`
+/// must be between 1 and 10.
+///
+/// ```
+/// use nt_leb128::write;
+///
+/// let mut buf = Vec::new();
+/// let len = write::reserve_unsigned(&mut buf, 2);
+/// buf.extend_from_slice(b"body");
+/// len.fill(&mut buf, 4).unwrap();
+/// assert_eq!(buf, b"\x84\x00body");
+/// ```
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+pub fn reserve_unsigned<W>(buf: &mut W, max_len: usize) -> Placeholder
+    where W: BufMut + AsRef<[u8]>
+{
+    assert!((1..=MAX_LEN).contains(&max_len),
+            "a placeholder must be between 1 and 10 bytes long");
+    let offset = buf.as_ref().len();
+    let mut padding = [0; MAX_LEN];
+    encode_unsigned_padded(&mut padding[..max_len], 0);
+    buf.put_slice(&padding[..max_len]);
+    Placeholder {
+        offset,
+        len: max_len,
+    }
+}
+
+#[cfg(all(feature = "bytes", not(feature = "signed-only")))]
+impl Placeholder {
+    /// The offset of the reserved bytes in the buffer.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The number of reserved bytes.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Write `val` into the reserved bytes of `buf`, padded to fill them.
+    ///
+    /// `buf` must be the buffer the space was reserved in (or a copy of it).
+    /// Fails with `Error::InsufficientSpace`, leaving `buf` untouched, if
+    /// `val` needs more bytes than were reserved.
+    pub fn fill(self, buf: &mut [u8], val: u64) -> Result<(), Error> {
+        check_space(encoded_len_unsigned(val), self.len)?;
+        encode_unsigned_padded(&mut buf[self.offset..self.offset + self.len], val);
+        Ok(())
+    }
+}
+
+/// A writer that discards everything written to it, only counting the bytes.
+/// With the `bytes` feature it is a `BufMut`.
+///
+/// Encoding into a `Measure` computes the encoded length of anything that can
+/// be written, without allocating.
+///
+/// ```
+/// # #[cfg(not(feature = "signed-only"))] {
+/// use nt_leb128::write::{Leb128Write, Measure};
+///
+/// let mut measure = Measure::new();
+/// measure.write_str("hello").unwrap();
+/// measure.write_unsigned(624485).unwrap();
+/// assert_eq!(measure.len(), 9);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Measure {
+    len: usize,
+    #[cfg(feature = "bytes")]
+    scratch: [MaybeUninit<u8>; 16],
+}
+
+impl Measure {
+    /// Create a `Measure` that has counted nothing yet.
+    pub fn new() -> Self {
+        Measure {
+            len: 0,
+            #[cfg(feature = "bytes")]
+            scratch: [MaybeUninit::uninit(); 16],
         }
     }
-}
\ No newline at end of file
+
+    /// The number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for Measure {
+    fn default() -> Self {
+        Measure::new()
+    }
+}
+
+impl Sink for Measure {
+    fn remaining_mut(&self) -> usize {
+        usize::MAX - self.len
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.len += src.len();
+    }
+}
+
+#[cfg(not(feature = "bytes"))]
+impl Leb128Write for Measure {
+    forward_write!();
+}
+
+#[cfg(feature = "bytes")]
+impl BufMut for Measure {
+    fn remaining_mut(&self) -> usize {
+        usize::MAX - self.len
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.len += cnt;
+    }
+
+    fn bytes_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        // Writes land in the scratch space and are overwritten by the next.
+        &mut self.scratch
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.len += src.len();
+    }
+}